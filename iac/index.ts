@@ -10,5 +10,22 @@ const app = new cdk.App();
 const stage =
   (app.node.tryGetContext('stage') as string | undefined) ?? defaultStage;
 
-const { eventBusName } = new TodoAppStack(app, `todos-api-${stage}`);
+// `-c lambdalith=true` deploys all the HTTP routes in a single lambda
+const lambdalith = String(app.node.tryGetContext('lambdalith')) === 'true';
+
+// `-c jwtIssuer=... -c jwtAudience=... -c jwksUrl=...` authorizes bearer tokens instead of SigV4
+const jwtIssuer = app.node.tryGetContext('jwtIssuer') as string | undefined;
+const jwtAuthorizer =
+  jwtIssuer === undefined
+    ? undefined
+    : {
+        issuer: jwtIssuer,
+        audience: app.node.tryGetContext('jwtAudience') as string,
+        jwksUrl: app.node.tryGetContext('jwksUrl') as string,
+      };
+
+const { eventBusName } = new TodoAppStack(app, `todos-api-${stage}`, {
+  lambdalith,
+  jwtAuthorizer,
+});
 new TestStack(app, `test-todos-api-${stage}`, { eventBusName });
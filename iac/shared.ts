@@ -4,3 +4,6 @@ export const getEventScoutEndpointExportName = (stage: string): string =>
   `event-scout-endpoint-${stage}`;
 
 export const defaultStage = 'dev';
+
+// must match `shared::EVENT_SOURCE`
+export const eventSource = 'api.todos';
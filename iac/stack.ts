@@ -1,26 +1,46 @@
 /* eslint-disable max-lines */
-import { CfnOutput, RemovalPolicy, Stack, StackProps } from 'aws-cdk-lib';
+import {
+  CfnOutput,
+  Duration,
+  RemovalPolicy,
+  Stack,
+  StackProps,
+} from 'aws-cdk-lib';
 import { HttpApi, HttpMethod } from 'aws-cdk-lib/aws-apigatewayv2';
-import { HttpIamAuthorizer } from 'aws-cdk-lib/aws-apigatewayv2-authorizers';
+import {
+  HttpIamAuthorizer,
+  HttpLambdaAuthorizer,
+  HttpLambdaResponseType,
+} from 'aws-cdk-lib/aws-apigatewayv2-authorizers';
 import { HttpLambdaIntegration } from 'aws-cdk-lib/aws-apigatewayv2-integrations';
-import { AttributeType, BillingMode, Table } from 'aws-cdk-lib/aws-dynamodb';
-import { EventBus, EventPattern, Rule } from 'aws-cdk-lib/aws-events';
+import {
+  AttributeType,
+  BillingMode,
+  StreamViewType,
+  Table,
+} from 'aws-cdk-lib/aws-dynamodb';
+import { EventBus, Rule } from 'aws-cdk-lib/aws-events';
 import { LambdaFunction } from 'aws-cdk-lib/aws-events-targets';
 import { Effect, PolicyStatement } from 'aws-cdk-lib/aws-iam';
 import {
   Architecture,
   Code,
+  FilterCriteria,
+  FilterRule,
   Function,
   LoggingFormat,
   Runtime,
+  StartingPosition,
   Tracing,
 } from 'aws-cdk-lib/aws-lambda';
+import { DynamoEventSource } from 'aws-cdk-lib/aws-lambda-event-sources';
 import { LogGroup, LogGroupProps, RetentionDays } from 'aws-cdk-lib/aws-logs';
 import { Construct } from 'constructs';
 import path, { join } from 'path';
 import { fileURLToPath } from 'url';
 
-import { defaultStage, getHttpApiExportName } from './shared';
+import type { TodoEvent } from '../integration-tests/bindings/TodoEvent';
+import { defaultStage, eventSource, getHttpApiExportName } from './shared';
 
 const __filename = fileURLToPath(import.meta.url);
 
@@ -38,24 +58,44 @@ type HttpLambdaConfig = LambdaConfig & {
   httpMethod: HttpMethod;
 };
 
+// detail types are generated from the Rust event catalog
+type TodoEventType = TodoEvent['type'];
+
 type AsyncLambdaConfig = LambdaConfig & {
-  eventPattern: EventPattern;
+  detailTypes: TodoEventType[];
+  // defaults to the 3 seconds of the lambda runtime
+  timeout?: Duration;
+};
+
+type JwtAuthorizerProps = {
+  issuer: string;
+  // comma separated, a token must have one of them
+  audience: string;
+  jwksUrl: string;
+};
+
+type TodoAppStackProps = StackProps & {
+  // serve all the HTTP routes from a single `todos-api` lambda
+  lambdalith?: boolean;
+  // authenticate the routes with bearer tokens instead of SigV4
+  jwtAuthorizer?: JwtAuthorizerProps;
 };
 
 export class TodoAppStack extends Stack {
   eventBusName: string;
 
-  constructor(scope: Construct, id: string, props?: StackProps) {
+  constructor(scope: Construct, id: string, props?: TodoAppStackProps) {
     super(scope, id, props);
 
-    const httpApi = new HttpApi(this, 'HttpApi', {
-      defaultAuthorizer: new HttpIamAuthorizer(),
-    });
 
     const todosTable = new Table(this, 'TodosTable', {
       partitionKey: { name: 'PK', type: AttributeType.STRING },
       sortKey: { name: 'SK', type: AttributeType.STRING },
       billingMode: BillingMode.PAY_PER_REQUEST,
+      // processed event records of the idempotent consumers
+      timeToLiveAttribute: 'expiresAt',
+      // outbox records are relayed from the stream
+      stream: StreamViewType.NEW_IMAGE,
       removalPolicy: RemovalPolicy.DESTROY,
     });
 
@@ -68,6 +108,43 @@ export class TodoAppStack extends Stack {
       removalPolicy: RemovalPolicy.DESTROY, // do not keep log group if it is no longer included in a deployment
     };
 
+    const createJwtAuthorizer = ({
+      issuer,
+      audience,
+      jwksUrl,
+    }: JwtAuthorizerProps): HttpLambdaAuthorizer => {
+      const lambda = new Function(this, 'JwtAuthorizerFunction', {
+        architecture: Architecture.ARM_64,
+        runtime: Runtime.PROVIDED_AL2023,
+        code: Code.fromAsset(
+          join(__dirname, baseLambdaDir, 'jwt-authorizer/bootstrap.zip'),
+        ),
+        handler: 'useless',
+        memorySize: 1024,
+        loggingFormat: LoggingFormat.JSON,
+        tracing: Tracing.ACTIVE,
+        logGroup: new LogGroup(this, 'JwtAuthorizerLogs', logGroupProps),
+        environment: {
+          JWKS_URL: jwksUrl,
+          JWT_ISSUER: issuer,
+          JWT_AUDIENCE: audience,
+          RUST_LOG: 'info',
+        },
+      });
+
+      return new HttpLambdaAuthorizer('JwtAuthorizer', lambda, {
+        responseTypes: [HttpLambdaResponseType.SIMPLE],
+        identitySource: ['$request.header.Authorization'],
+      });
+    };
+
+    const httpApi = new HttpApi(this, 'HttpApi', {
+      defaultAuthorizer:
+        props?.jwtAuthorizer === undefined
+          ? new HttpIamAuthorizer()
+          : createJwtAuthorizer(props.jwtAuthorizer),
+    });
+
     const httpLambdasConfig: Record<string, HttpLambdaConfig> = {
       CreateTodo: {
         codePath: 'create-todo/bootstrap.zip',
@@ -77,12 +154,12 @@ export class TodoAppStack extends Stack {
           new PolicyStatement({
             effect: Effect.ALLOW,
             resources: [todosTable.tableArn],
-            actions: ['dynamodb:PutItem'],
-          }),
-          new PolicyStatement({
-            effect: Effect.ALLOW,
-            resources: [eventBus.eventBusArn],
-            actions: ['events:PutEvents'],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:PutItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:ConditionCheckItem',
+            ],
           }),
         ],
       },
@@ -94,7 +171,53 @@ export class TodoAppStack extends Stack {
           new PolicyStatement({
             effect: Effect.ALLOW,
             resources: [todosTable.tableArn],
-            actions: ['dynamodb:Query'],
+            actions: ['dynamodb:GetItem', 'dynamodb:Query'],
+          }),
+        ],
+      },
+      GetTodo: {
+        codePath: 'get-todo/bootstrap.zip',
+        httpMethod: HttpMethod.GET,
+        httpPath: '/todos/{listId}/{todoId}',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem'],
+          }),
+        ],
+      },
+      UpdateTodo: {
+        codePath: 'update-todo/bootstrap.zip',
+        httpMethod: HttpMethod.PATCH,
+        httpPath: '/todos/{listId}/{todoId}',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:PutItem',
+              'dynamodb:ConditionCheckItem',
+            ],
+          }),
+        ],
+      },
+      CompleteTodo: {
+        codePath: 'complete-todo/bootstrap.zip',
+        httpMethod: HttpMethod.PUT,
+        httpPath: '/todos/{listId}/{todoId}/completion',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:PutItem',
+              'dynamodb:ConditionCheckItem',
+            ],
           }),
         ],
       },
@@ -106,26 +229,151 @@ export class TodoAppStack extends Stack {
           new PolicyStatement({
             effect: Effect.ALLOW,
             resources: [todosTable.tableArn],
-            actions: ['dynamodb:DeleteItem'],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:DeleteItem',
+              'dynamodb:PutItem',
+              'dynamodb:ConditionCheckItem',
+            ],
+          }),
+        ],
+      },
+      CreateList: {
+        codePath: 'create-list/bootstrap.zip',
+        httpMethod: HttpMethod.POST,
+        httpPath: '/lists',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:PutItem'],
+          }),
+        ],
+      },
+      ListLists: {
+        codePath: 'list-lists/bootstrap.zip',
+        httpMethod: HttpMethod.GET,
+        httpPath: '/lists',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:Query'],
+          }),
+        ],
+      },
+      GetList: {
+        codePath: 'get-list/bootstrap.zip',
+        httpMethod: HttpMethod.GET,
+        httpPath: '/lists/{listId}',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem'],
+          }),
+        ],
+      },
+      RenameList: {
+        codePath: 'rename-list/bootstrap.zip',
+        httpMethod: HttpMethod.PATCH,
+        httpPath: '/lists/{listId}',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem', 'dynamodb:PutItem'],
+          }),
+        ],
+      },
+      ArchiveList: {
+        codePath: 'archive-list/bootstrap.zip',
+        httpMethod: HttpMethod.PUT,
+        httpPath: '/lists/{listId}/archive',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem', 'dynamodb:PutItem'],
+          }),
+        ],
+      },
+      DeleteList: {
+        codePath: 'delete-list/bootstrap.zip',
+        httpMethod: HttpMethod.DELETE,
+        httpPath: '/lists/{listId}',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem', 'dynamodb:PutItem'],
+          }),
+        ],
+      },
+      GetListDeletion: {
+        codePath: 'get-list-deletion/bootstrap.zip',
+        httpMethod: HttpMethod.GET,
+        httpPath: '/lists/{listId}/deletion',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem'],
+          }),
+        ],
+      },
+      // the member is passed as the `userId` query parameter, ARNs contain slashes
+      InviteMember: {
+        codePath: 'invite-member/bootstrap.zip',
+        httpMethod: HttpMethod.POST,
+        httpPath: '/lists/{listId}/members',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem', 'dynamodb:PutItem'],
+          }),
+        ],
+      },
+      UpdateMember: {
+        codePath: 'update-member/bootstrap.zip',
+        httpMethod: HttpMethod.PATCH,
+        httpPath: '/lists/{listId}/members',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: ['dynamodb:GetItem', 'dynamodb:PutItem'],
           }),
+        ],
+      },
+      RemoveMember: {
+        codePath: 'remove-member/bootstrap.zip',
+        httpMethod: HttpMethod.DELETE,
+        httpPath: '/lists/{listId}/members',
+        policy: [
           new PolicyStatement({
             effect: Effect.ALLOW,
-            resources: [eventBus.eventBusArn],
-            actions: ['events:PutEvents'],
+            resources: [todosTable.tableArn],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:DeleteItem',
+              'dynamodb:PutItem',
+            ],
           }),
         ],
       },
     };
 
-    // HTTP Lambdas config
-    Object.entries(httpLambdasConfig).map(([lambdaName, lambdaConfig]) => {
-      // create the lambda
-      const lambda = new Function(this, lambdaName, {
+    const createHttpLambda = (
+      lambdaName: string,
+      codePath: string,
+      policy: PolicyStatement[],
+    ): Function =>
+      new Function(this, lambdaName, {
         architecture: Architecture.ARM_64,
         runtime: Runtime.PROVIDED_AL2023,
-        code: Code.fromAsset(
-          join(__dirname, baseLambdaDir, lambdaConfig.codePath),
-        ),
+        code: Code.fromAsset(join(__dirname, baseLambdaDir, codePath)),
         handler: 'useless',
         memorySize: 1024,
         loggingFormat: LoggingFormat.JSON,
@@ -136,19 +384,49 @@ export class TodoAppStack extends Stack {
           EVENT_BUS_NAME: eventBus.eventBusName,
           RUST_LOG: 'info',
         },
-        initialPolicy: lambdaConfig.policy,
+        initialPolicy: policy,
       });
 
-      // add it to the http api
-      httpApi.addRoutes({
-        path: lambdaConfig.httpPath,
-        methods: [lambdaConfig.httpMethod],
-        integration: new HttpLambdaIntegration(
-          `${lambdaName}Integration`,
-          lambda,
-        ),
+    if (props?.lambdalith === true) {
+      // a single lambda with the permissions of all the routes
+      const lambda = createHttpLambda(
+        'TodosApi',
+        'todos-api/bootstrap.zip',
+        Object.values(httpLambdasConfig).flatMap(({ policy }) => policy),
+      );
+      const integration = new HttpLambdaIntegration(
+        'TodosApiIntegration',
+        lambda,
+      );
+
+      Object.values(httpLambdasConfig).map((lambdaConfig) => {
+        httpApi.addRoutes({
+          path: lambdaConfig.httpPath,
+          methods: [lambdaConfig.httpMethod],
+          integration,
+        });
       });
-    });
+    } else {
+      // HTTP Lambdas config
+      Object.entries(httpLambdasConfig).map(([lambdaName, lambdaConfig]) => {
+        // create the lambda
+        const lambda = createHttpLambda(
+          lambdaName,
+          lambdaConfig.codePath,
+          lambdaConfig.policy,
+        );
+
+        // add it to the http api
+        httpApi.addRoutes({
+          path: lambdaConfig.httpPath,
+          methods: [lambdaConfig.httpMethod],
+          integration: new HttpLambdaIntegration(
+            `${lambdaName}Integration`,
+            lambda,
+          ),
+        });
+      });
+    }
 
     const asyncLambdasConfig: Record<string, AsyncLambdaConfig> = {
       OnTodoCreated: {
@@ -157,13 +435,14 @@ export class TodoAppStack extends Stack {
           new PolicyStatement({
             effect: Effect.ALLOW,
             resources: [todosTable.tableArn],
-            actions: ['dynamodb:UpdateItem'],
+            actions: [
+              'dynamodb:PutItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:ConditionCheckItem',
+            ],
           }),
         ],
-        eventPattern: {
-          source: ['api.todos'],
-          detailType: ['TODO_CREATED'],
-        },
+        detailTypes: ['TODO_CREATED'],
       },
       OnTodoDeleted: {
         codePath: 'on-todo-deleted/bootstrap.zip',
@@ -171,13 +450,48 @@ export class TodoAppStack extends Stack {
           new PolicyStatement({
             effect: Effect.ALLOW,
             resources: [todosTable.tableArn],
-            actions: ['dynamodb:UpdateItem'],
+            actions: [
+              'dynamodb:PutItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:ConditionCheckItem',
+            ],
           }),
         ],
-        eventPattern: {
-          source: ['api.todos'],
-          detailType: ['TODO_DELETED'],
-        },
+        detailTypes: ['TODO_DELETED'],
+      },
+      OnTodoCompletionChanged: {
+        codePath: 'on-todo-completion-changed/bootstrap.zip',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: [
+              'dynamodb:PutItem',
+              'dynamodb:UpdateItem',
+              'dynamodb:ConditionCheckItem',
+            ],
+          }),
+        ],
+        detailTypes: ['TODO_COMPLETED', 'TODO_REOPENED'],
+      },
+      OnListDeletionRequested: {
+        codePath: 'on-list-deletion-requested/bootstrap.zip',
+        policy: [
+          new PolicyStatement({
+            effect: Effect.ALLOW,
+            resources: [todosTable.tableArn],
+            actions: [
+              'dynamodb:GetItem',
+              'dynamodb:Query',
+              'dynamodb:BatchWriteItem',
+              'dynamodb:PutItem',
+              'dynamodb:DeleteItem',
+            ],
+          }),
+        ],
+        detailTypes: ['LIST_DELETION_REQUESTED'],
+        // the todos of large lists are deleted page by page
+        timeout: Duration.minutes(15),
       },
     };
 
@@ -194,6 +508,7 @@ export class TodoAppStack extends Stack {
         memorySize: 1024,
         loggingFormat: LoggingFormat.JSON,
         tracing: Tracing.ACTIVE,
+        timeout: lambdaConfig.timeout,
         logGroup: new LogGroup(this, `${lambdaName}Logs`, logGroupProps),
         environment: {
           TODOS_TABLE_NAME: todosTable.tableName,
@@ -204,12 +519,62 @@ export class TodoAppStack extends Stack {
 
       // add the rule
       new Rule(this, `${lambdaName}Rule`, {
-        eventPattern: lambdaConfig.eventPattern,
+        eventPattern: {
+          source: [eventSource],
+          detailType: lambdaConfig.detailTypes,
+        },
         eventBus,
         targets: [new LambdaFunction(lambda)],
       });
     });
 
+    // Outbox relay, publishes the events written along with the todos
+    const outboxRelay = new Function(this, 'OutboxRelay', {
+      architecture: Architecture.ARM_64,
+      runtime: Runtime.PROVIDED_AL2023,
+      code: Code.fromAsset(
+        join(__dirname, baseLambdaDir, 'outbox-relay/bootstrap.zip'),
+      ),
+      handler: 'useless',
+      memorySize: 1024,
+      loggingFormat: LoggingFormat.JSON,
+      tracing: Tracing.ACTIVE,
+      logGroup: new LogGroup(this, 'OutboxRelayLogs', logGroupProps),
+      environment: {
+        TODOS_TABLE_NAME: todosTable.tableName,
+        EVENT_BUS_NAME: eventBus.eventBusName,
+        RUST_LOG: 'info',
+      },
+      initialPolicy: [
+        new PolicyStatement({
+          effect: Effect.ALLOW,
+          resources: [todosTable.tableArn],
+          actions: ['dynamodb:UpdateItem'],
+        }),
+        new PolicyStatement({
+          effect: Effect.ALLOW,
+          resources: [eventBus.eventBusArn],
+          actions: ['events:PutEvents'],
+        }),
+      ],
+    });
+
+    outboxRelay.addEventSource(
+      new DynamoEventSource(todosTable, {
+        startingPosition: StartingPosition.TRIM_HORIZON,
+        reportBatchItemFailures: true,
+        retryAttempts: 10,
+        filters: [
+          FilterCriteria.filter({
+            eventName: FilterRule.isEqual('INSERT'),
+            dynamodb: {
+              Keys: { PK: { S: FilterRule.beginsWith('OUTBOX#') } },
+            },
+          }),
+        ],
+      }),
+    );
+
     const httpApiExportName = getHttpApiExportName(
       (this.node.tryGetContext('stage') as string | undefined) ?? defaultStage,
     );
@@ -3,9 +3,28 @@ import { SignatureV4 } from '@smithy/signature-v4';
 import axios from 'axios';
 import { randomUUID } from 'crypto';
 
+import { ErrorResponse } from './bindings/ErrorResponse';
+import { EventEnvelope } from './bindings/EventEnvelope';
+import { List } from './bindings/List';
+import { ListSummary } from './bindings/ListSummary';
+import { Page } from './bindings/Page';
 import { Todo } from './bindings/Todo';
+import { TodoEvent } from './bindings/TodoEvent';
+import { TodoUpdate } from './bindings/TodoUpdate';
 import { getSignedAxiosConfig } from './get-signed-request';
 
+const todoEventMatching = (event: TodoEvent): unknown =>
+  expect.objectContaining({
+    source: 'api.todos',
+    'detail-type': event.type,
+    // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
+    detail: {
+      ...event,
+      id: expect.any(String),
+      version: 1,
+    } satisfies EventEnvelope,
+  });
+
 describe('todos CRUD API', () => {
   const httpApiUrl = globalThis.httpApiUrl;
   // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
@@ -13,16 +32,214 @@ describe('todos CRUD API', () => {
   // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
   const eventScoutClient: EventScoutClient = globalThis.eventScoutClient;
 
-  const listId = randomUUID();
+  // the todos are created in a list of the signing user
+  let list: List;
+  let listId: string;
 
   beforeAll(
     async () => {
       await eventScoutClient.start({
         eventPattern: {
           source: ['api.todos'],
-          'detail-type': ['TODO_CREATED', 'TODO_DELETED'],
+          'detail-type': [
+            'TODO_CREATED',
+            'TODO_UPDATED',
+            'TODO_COMPLETED',
+            'TODO_REOPENED',
+            'TODO_DELETED',
+          ] satisfies TodoEvent['type'][],
         },
       });
+
+      const createListRequest = await getSignedAxiosConfig(
+        signatureV4,
+        `${httpApiUrl}lists`,
+        'POST',
+        { name: `List ${randomUUID()}` },
+      );
+      const createListResponse = await axios<List>(createListRequest);
+
+      list = createListResponse.data;
+      listId = list.id;
+    },
+    30 * 1000, // 30s timeout
+  );
+
+  describe('authentication', () => {
+    it('should return a 403 when calling with no authorization', async () => {
+      const response = await fetch(`${httpApiUrl}todos/${listId}`);
+
+      expect(response.status).toBe(403);
+    });
+
+    it('should return a 200 when properly authorizing', async () => {
+      const signedRequest = await getSignedAxiosConfig(
+        signatureV4,
+        `${httpApiUrl}todos/${listId}`,
+        'GET',
+      );
+
+      const response = await axios(signedRequest);
+
+      expect(response.status).toBe(200);
+    });
+  });
+
+  describe(
+    'CRUD scenario',
+    () => {
+      it('should perform basic CRUD scenario', async () => {
+        // Create a Todo
+        const createTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}`,
+          'POST',
+          {
+            title: `Todo ${randomUUID()}`,
+            description: `Super, this is description number ${randomUUID()}`,
+          },
+        );
+        const createTodoResponse = await axios<Todo>(createTodoRequest);
+
+        const todo = createTodoResponse.data;
+
+        expect(createTodoResponse.status).toBe(201);
+        expect(todo).toMatchObject(todo);
+
+        // List todos
+        const listTodosRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}`,
+          'GET',
+        );
+        const listTodosResponse = await axios<Page<Todo>>(listTodosRequest);
+
+        expect(listTodosResponse.status).toBe(200);
+        expect(listTodosResponse.data.items).toEqual(
+          expect.arrayContaining([todo]),
+        );
+        expect(listTodosResponse.data.next_cursor).toBeNull();
+
+        // Get todo
+        const getTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}`,
+          'GET',
+        );
+        const getTodoResponse = await axios<Todo>(getTodoRequest);
+
+        expect(getTodoResponse.status).toBe(200);
+        expect(getTodoResponse.data).toEqual(todo);
+
+        // Update todo
+        const updatedTitle = `Updated todo ${randomUUID()}`;
+        const updateTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}`,
+          'PATCH',
+          { title: updatedTitle },
+        );
+        const updateTodoResponse = await axios<Todo>(updateTodoRequest);
+
+        const updatedTodo = updateTodoResponse.data;
+
+        expect(updateTodoResponse.status).toBe(200);
+        expect(updatedTodo).toEqual({ ...todo, title: updatedTitle });
+
+        // Complete todo
+        const completeTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}/completion`,
+          'PUT',
+          { completed: true },
+        );
+        const completeTodoResponse = await axios<Todo>(completeTodoRequest);
+
+        const completedTodo = completeTodoResponse.data;
+
+        expect(completeTodoResponse.status).toBe(200);
+        expect(completedTodo).toMatchObject({
+          ...updatedTodo,
+          completed: true,
+          completed_at: expect.any(String) as string,
+        });
+
+        // Reopen todo
+        const reopenTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}/completion`,
+          'PUT',
+          { completed: false },
+        );
+        const reopenTodoResponse = await axios<Todo>(reopenTodoRequest);
+
+        expect(reopenTodoResponse.status).toBe(200);
+        expect(reopenTodoResponse.data).toEqual(updatedTodo);
+
+        // Delete
+        const deleteTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}`,
+          'DELETE',
+        );
+        const deleteTodoResponse = await axios(deleteTodoRequest);
+        expect(deleteTodoResponse.status).toBe(204);
+
+        const getTodoAfterDeletionResponse = await axios<ErrorResponse>({
+          ...getTodoRequest,
+          validateStatus: () => true,
+        });
+        expect(getTodoAfterDeletionResponse.status).toBe(404);
+        expect(getTodoAfterDeletionResponse.data).toEqual(
+          expect.objectContaining({
+            code: 'TODO_NOT_FOUND',
+          } satisfies Partial<ErrorResponse>),
+        );
+
+        const listTodosAfterDeletionResponse =
+          await axios<Page<Todo>>(listTodosRequest);
+        expect(listTodosAfterDeletionResponse.data.items).not.toEqual(
+          expect.arrayContaining([todo]),
+        );
+
+        // we want the give time to the async process, say 10s
+        await new Promise(r => setTimeout(r, 10 * 1000));
+
+        // check counters
+        const getListRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}lists/${listId}`,
+          'GET',
+        );
+        const getListResponse = await axios<ListSummary>(getListRequest);
+
+        expect(getListResponse.status).toBe(200);
+        expect(getListResponse.data).toEqual({
+          list_id: listId,
+          name: list.name,
+          owner: list.owner,
+          created_at: list.created_at,
+          archived: false,
+          todos_count: 0,
+          completed_count: 0,
+        } satisfies ListSummary);
+
+        // check events
+        const events = await eventScoutClient.query();
+
+        expect(events).toEqual(
+          expect.arrayContaining([
+            todoEventMatching({ type: 'TODO_CREATED', data: todo }),
+            todoEventMatching({
+              type: 'TODO_UPDATED',
+              data: { old: todo, new: updatedTodo } satisfies TodoUpdate,
+            }),
+            todoEventMatching({ type: 'TODO_COMPLETED', data: completedTodo }),
+            todoEventMatching({ type: 'TODO_REOPENED', data: updatedTodo }),
+            todoEventMatching({ type: 'TODO_DELETED', data: updatedTodo }),
+          ]),
+        );
+      });
     },
     30 * 1000, // 30s timeout
   );
@@ -74,10 +291,69 @@ describe('todos CRUD API', () => {
           `${httpApiUrl}todos/${listId}`,
           'GET',
         );
-        const listTodosResponse = await axios(listTodosRequest);
+        const listTodosResponse = await axios<Page<Todo>>(listTodosRequest);
 
         expect(listTodosResponse.status).toBe(200);
-        expect(listTodosResponse.data).toEqual(expect.arrayContaining([todo]));
+        expect(listTodosResponse.data.items).toEqual(
+          expect.arrayContaining([todo]),
+        );
+        expect(listTodosResponse.data.next_cursor).toBeNull();
+
+        // Get todo
+        const getTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}`,
+          'GET',
+        );
+        const getTodoResponse = await axios<Todo>(getTodoRequest);
+
+        expect(getTodoResponse.status).toBe(200);
+        expect(getTodoResponse.data).toEqual(todo);
+
+        // Update todo
+        const updatedTitle = `Updated todo ${randomUUID()}`;
+        const updateTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}`,
+          'PATCH',
+          { title: updatedTitle },
+        );
+        const updateTodoResponse = await axios<Todo>(updateTodoRequest);
+
+        const updatedTodo = updateTodoResponse.data;
+
+        expect(updateTodoResponse.status).toBe(200);
+        expect(updatedTodo).toEqual({ ...todo, title: updatedTitle });
+
+        // Complete todo
+        const completeTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}/completion`,
+          'PUT',
+          { completed: true },
+        );
+        const completeTodoResponse = await axios<Todo>(completeTodoRequest);
+
+        const completedTodo = completeTodoResponse.data;
+
+        expect(completeTodoResponse.status).toBe(200);
+        expect(completedTodo).toMatchObject({
+          ...updatedTodo,
+          completed: true,
+          completed_at: expect.any(String) as string,
+        });
+
+        // Reopen todo
+        const reopenTodoRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}todos/${listId}/${todo.id}/completion`,
+          'PUT',
+          { completed: false },
+        );
+        const reopenTodoResponse = await axios<Todo>(reopenTodoRequest);
+
+        expect(reopenTodoResponse.status).toBe(200);
+        expect(reopenTodoResponse.data).toEqual(updatedTodo);
 
         // Delete
         const deleteTodoRequest = await getSignedAxiosConfig(
@@ -88,14 +364,45 @@ describe('todos CRUD API', () => {
         const deleteTodoResponse = await axios(deleteTodoRequest);
         expect(deleteTodoResponse.status).toBe(204);
 
-        const listTodosAfterDeletionResponse = await axios(listTodosRequest);
-        expect(listTodosAfterDeletionResponse.data).not.toEqual(
+        const getTodoAfterDeletionResponse = await axios<ErrorResponse>({
+          ...getTodoRequest,
+          validateStatus: () => true,
+        });
+        expect(getTodoAfterDeletionResponse.status).toBe(404);
+        expect(getTodoAfterDeletionResponse.data).toEqual(
+          expect.objectContaining({
+            code: 'TODO_NOT_FOUND',
+          } satisfies Partial<ErrorResponse>),
+        );
+
+        const listTodosAfterDeletionResponse =
+          await axios<Page<Todo>>(listTodosRequest);
+        expect(listTodosAfterDeletionResponse.data.items).not.toEqual(
           expect.arrayContaining([todo]),
         );
 
         // we want the give time to the async process, say 10s
         await new Promise(r => setTimeout(r, 10 * 1000));
 
+        // check counters
+        const getListRequest = await getSignedAxiosConfig(
+          signatureV4,
+          `${httpApiUrl}lists/${listId}`,
+          'GET',
+        );
+        const getListResponse = await axios<ListSummary>(getListRequest);
+
+        expect(getListResponse.status).toBe(200);
+        expect(getListResponse.data).toEqual({
+          list_id: listId,
+          name: list.name,
+          owner: list.owner,
+          created_at: list.created_at,
+          archived: false,
+          todos_count: 0,
+          completed_count: 0,
+        } satisfies ListSummary);
+
         // check events
         const events = await eventScoutClient.query();
 
@@ -107,11 +414,26 @@ describe('todos CRUD API', () => {
               // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
               detail: expect.objectContaining(todo),
             }),
+            expect.objectContaining({
+              source: 'api.todos',
+              'detail-type': 'TODO_UPDATED',
+              detail: { old: todo, new: updatedTodo } satisfies TodoUpdate,
+            }),
+            expect.objectContaining({
+              source: 'api.todos',
+              'detail-type': 'TODO_COMPLETED',
+              detail: completedTodo,
+            }),
+            expect.objectContaining({
+              source: 'api.todos',
+              'detail-type': 'TODO_REOPENED',
+              detail: updatedTodo,
+            }),
             expect.objectContaining({
               source: 'api.todos',
               'detail-type': 'TODO_DELETED',
               // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
-              detail: expect.objectContaining(todo),
+              detail: expect.objectContaining(updatedTodo),
             }),
           ]),
         );
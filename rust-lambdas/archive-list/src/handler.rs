@@ -0,0 +1,189 @@
+use aws_lambda_events::http::StatusCode;
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::ListKey, parse_body, ErrorCode, EventEnvelope, FailureResponse, List,
+    OutboxRecord, RepositoryError, Role, TodoEvent, TodoRepository, Validate, ValidationErrors,
+    Validator,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct ArchiveList {
+    archived: bool,
+}
+
+impl Validate for ArchiveList {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        Validator::new().finish(self)
+    }
+}
+
+/// Archives or unarchives a list, the archived lists don't accept new todos.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    ListKey::new(list_id).map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let body = parse_body::<ArchiveList>(&request)?;
+
+    let start = Instant::now();
+
+    let access = caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to archive list")
+        })?;
+
+    let list = access.existing_list()?;
+
+    access.require(Role::Owner)?;
+
+    // archiving twice doesn't publish another event
+    if list.archived == body.archived {
+        let list = serde_json::to_value(list).map_err(|_| {
+            FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list")
+        })?;
+
+        return Ok((StatusCode::OK, list));
+    }
+
+    let list = List {
+        archived: body.archived,
+        ..list.clone()
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListUpdated(
+        list.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize list");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .update_list(&list, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::ListNotFound, "List not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to update list");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to archive list")
+            }
+        })?;
+
+    info!(
+        list_id = list_id,
+        archived = list.archived,
+        "Successfully archived list"
+    );
+
+    debug!("Item updated in {:.2?}", start.elapsed());
+
+    let list = serde_json::to_value(list)
+        .map_err(|_| FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list"))?;
+
+    Ok((StatusCode::OK, list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+
+    fn get_request_as(body: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("PUT")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .body(body)
+            .build()
+    }
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", &iam_id(OWNER))).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_list().await;
+
+        for _ in 0..2 {
+            let (status, res) = handler(get_request_as("{\"archived\": true}", OWNER), &repository)
+                .await
+                .expect("failed to handle event");
+
+            assert_eq!(status, 200);
+
+            let list: List = serde_json::from_value(res).unwrap();
+
+            assert!(list.archived);
+        }
+
+        let list = repository.get_list("toto").await.unwrap().unwrap();
+
+        assert!(list.archived);
+
+        // the second call didn't change the list
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 2);
+        assert_eq!(outbox_records[1].detail_type, "LIST_UPDATED");
+
+        let (_, res) = handler(get_request_as("{\"archived\": false}", OWNER), &repository)
+            .await
+            .expect("failed to handle event");
+
+        let list: List = serde_json::from_value(res).unwrap();
+
+        assert!(!list.archived);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_owner() {
+        let repository = repository_with_list().await;
+
+        let err = handler(
+            get_request_as(
+                "{\"archived\": true}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("only the owner should archive the list");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
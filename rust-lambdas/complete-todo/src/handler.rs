@@ -0,0 +1,346 @@
+use aws_lambda_events::http::StatusCode;
+use chrono::Utc;
+
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::TodoKey, ErrorCode, EventEnvelope, FailureResponse, OutboxRecord,
+    RepositoryError, Role, Todo, TodoEvent, TodoRepository,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Body, Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct SetCompletion {
+    completed: bool,
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    let todo_id = path_parameters.first("todoId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing todo id",
+    ))?;
+
+    let key = TodoKey::new(list_id, todo_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let body = match request.body() {
+        Body::Text(body) => serde_json::from_str::<SetCompletion>(body)
+            .map_err(|_| FailureResponse::new(ErrorCode::InvalidRequest, "Invalid request")),
+        _ => Err(FailureResponse::new(
+            ErrorCode::InvalidRequest,
+            "Invalid request",
+        )),
+    }?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to set todo completion")
+        })?
+        .require(Role::Editor)?;
+
+    // transactions can't return the updated item, which the event needs
+    let todo = repository
+        .get(&key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get todo");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to set todo completion")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::TodoNotFound,
+            "Todo not found",
+        ))?;
+
+    // only transition from the opposite state, so that events and counters are
+    // not emitted twice
+    if todo.completed == body.completed {
+        info!(
+            todo_id = todo_id,
+            list_id = list_id,
+            completed = body.completed,
+            "Todo completion already set",
+        );
+
+        return serialize_todo(&todo).map(|todo| (StatusCode::OK, todo));
+    }
+
+    let todo = Todo {
+        completed: body.completed,
+        completed_at: body.completed.then(Utc::now),
+        ..todo
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(match body.completed {
+        true => TodoEvent::TodoCompleted(todo.clone()),
+        false => TodoEvent::TodoReopened(todo.clone()),
+    }))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize todo");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .set_completion(&todo, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            // its completion changed since it was read
+            RepositoryError::Conflict => {
+                FailureResponse::new(ErrorCode::Conflict, "Todo was modified during completion")
+            }
+            RepositoryError::ListClosed => {
+                FailureResponse::new(ErrorCode::Conflict, "The list is archived")
+            }
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::TodoNotFound, "Todo not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to set todo completion");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to set todo completion")
+            }
+        })?;
+
+    debug!("Item updated in {:.2?}", start.elapsed());
+
+    info!(
+        todo_id = todo_id,
+        list_id = list_id,
+        completed = todo.completed,
+        "Successfully set todo completion",
+    );
+
+    Ok((StatusCode::OK, serialize_todo(&todo)?))
+}
+
+fn serialize_todo(todo: &Todo) -> Result<serde_json::Value, FailureResponse> {
+    serde_json::to_value(todo).map_err(|err| {
+        error!(err = ?err, "Unable to serialize todo");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{self, iam_id, list, member, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    fn get_request(body: &str) -> Request {
+        get_request_as(body, "arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(body: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("PUT")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .path_parameter("todoId", "tata")
+            .body(body)
+            .build()
+    }
+
+    fn todo(completed: bool) -> Todo {
+        Todo {
+            completed,
+            completed_at: completed.then(testing::fixture_date),
+            owner: Some(iam_id("arn:aws:iam::123456789012:user/toto")),
+            ..testing::todo("toto", "tata")
+        }
+    }
+
+    async fn repository_with_todo(completed: bool) -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+        repository.seed_todo(&todo(completed)).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler_complete() {
+        let repository = repository_with_todo(false).await;
+
+        let (status, res) = handler(get_request("{\"completed\": true}"), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let todo: Todo = serde_json::from_value(res).unwrap();
+
+        assert!(todo.completed);
+        assert!(todo.completed_at.is_some());
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert!(repository.get(&key).await.unwrap().unwrap().completed);
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records[2].detail_type, "TODO_COMPLETED");
+    }
+
+    #[tokio::test]
+    async fn test_handler_reopen() {
+        let repository = repository_with_todo(true).await;
+
+        let (status, res) = handler(get_request("{\"completed\": false}"), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let todo: Todo = serde_json::from_value(res).unwrap();
+
+        assert!(!todo.completed);
+        assert_eq!(todo.completed_at, None);
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+        let stored = repository.get(&key).await.unwrap().unwrap();
+
+        assert!(!stored.completed);
+        assert_eq!(stored.completed_at, None);
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records[2].detail_type, "TODO_REOPENED");
+    }
+
+    #[tokio::test]
+    async fn test_handler_already_completed() {
+        let repository = repository_with_todo(true).await;
+
+        let (status, res) = handler(get_request("{\"completed\": true}"), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let todo: Todo = serde_json::from_value(res).unwrap();
+
+        assert!(todo.completed);
+        // no transition happened, so no event must be stored
+        assert_eq!(repository.outbox_records().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        let err = handler(get_request("{\"completed\": true}"), &repository)
+            .await
+            .expect_err("todo should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_todo(false).await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Editor,
+            ))
+            .await;
+
+        let (status, _) = handler(
+            get_request_as(
+                "{\"completed\": true}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect("an editor should complete todos");
+
+        assert_eq!(status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_handler_viewer() {
+        let repository = repository_with_todo(false).await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let err = handler(
+            get_request_as(
+                "{\"completed\": true}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("a viewer should not complete todos");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(err.message, "Insufficient role on the list");
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_todo(false).await;
+
+        let err = handler(
+            get_request_as(
+                "{\"completed\": true}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert!(!repository.get(&key).await.unwrap().unwrap().completed);
+    }
+}
@@ -0,0 +1,150 @@
+use aws_lambda_events::http::StatusCode;
+use chrono::Utc;
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::OwnerListKey, parse_body, ErrorCode, EventEnvelope, FailureResponse, List,
+    OutboxRecord, RepositoryError, TodoEvent, TodoRepository, Validate, ValidationErrors,
+    Validator, LIST_NAME,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request,
+};
+use ulid::Ulid;
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct CreateList {
+    name: String,
+}
+
+impl Validate for CreateList {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::new();
+
+        let list = CreateList {
+            name: validator.text("name", &self.name, &LIST_NAME),
+        };
+
+        validator.finish(list)
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let owner = Caller::from_request(&request)?.id();
+
+    let body = parse_body::<CreateList>(&request)?;
+
+    let start = Instant::now();
+
+    // generate ulid in order to have the lists of an owner sorted by creation
+    let list_id = Ulid::new().to_string();
+
+    OwnerListKey::new(&owner, &list_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let list = List {
+        id: list_id,
+        name: body.name,
+        created_at: Utc::now(),
+        owner,
+        archived: false,
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListCreated(
+        list.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize list");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .create_list(&list, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::Conflict => {
+                FailureResponse::new(ErrorCode::Conflict, "The list already exists")
+            }
+            err => {
+                error!(err = ?err, "Unable to create list");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to create list")
+            }
+        })?;
+
+    info!(list_id = list.id, "Successfully created list");
+
+    debug!("Item stored in {:.2?}", start.elapsed());
+
+    let list = serde_json::to_value(list)
+        .map_err(|_| FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list"))?;
+
+    Ok((StatusCode::CREATED, list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    fn get_request(body: &str) -> Request {
+        ApiGatewayRequest::new("POST")
+            .user_arn("arn:aws:iam::123456789012:user/toto")
+            .body(body)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = InMemoryTodoRepository::new();
+
+        let (status, res) = handler(get_request("{\"name\": \"  Toto list \"}"), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 201);
+
+        let list: List = serde_json::from_value(res).unwrap();
+
+        assert_eq!(list.name, "Toto list");
+        assert_eq!(list.owner, iam_id("arn:aws:iam::123456789012:user/toto"));
+        assert!(!list.archived);
+
+        let stored = repository
+            .get_list(&list.id)
+            .await
+            .unwrap()
+            .expect("list should be stored");
+
+        assert_eq!(stored, list);
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 1);
+        assert_eq!(outbox_records[0].detail_type, "LIST_CREATED");
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_name() {
+        let repository = InMemoryTodoRepository::new();
+
+        let err = handler(get_request("{\"name\": \" \"}"), &repository)
+            .await
+            .expect_err("name should be invalid");
+
+        assert_eq!(err.status_code, 422);
+        assert!(repository.outbox_records().is_empty());
+    }
+}
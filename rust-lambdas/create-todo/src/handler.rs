@@ -1,13 +1,15 @@
 use aws_lambda_events::http::StatusCode;
-use aws_sdk_dynamodb::types::AttributeValue;
-use aws_sdk_eventbridge::types::PutEventsRequestEntry;
 
 use serde::Deserialize;
-use shared::{FailureResponse, Todo};
+use shared::{
+    auth::Caller, keys::TodoKey, parse_body, ErrorCode, EventEnvelope, FailureResponse,
+    OutboxRecord, RepositoryError, Role, Todo, TodoEvent, TodoRepository, Validate,
+    ValidationErrors, Validator, TODO_DESCRIPTION, TODO_TITLE,
+};
 
 use lambda_http::{
     tracing::{self, debug, error, info},
-    Body, Request, RequestExt,
+    Request, RequestExt,
 };
 use ulid::Ulid;
 
@@ -19,101 +21,106 @@ struct CreateTodo {
     description: String,
 }
 
+impl Validate for CreateTodo {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::new();
+
+        let todo = CreateTodo {
+            title: validator.text("title", &self.title, &TODO_TITLE),
+            description: validator.text("description", &self.description, &TODO_DESCRIPTION),
+        };
+
+        validator.finish(todo)
+    }
+}
+
 #[tracing::instrument(skip_all)]
-pub(crate) async fn handler(
+pub async fn handler(
     request: Request,
-    dynamodb_client: &aws_sdk_dynamodb::Client,
-    eventbridge_client: &aws_sdk_eventbridge::Client,
-    todos_table_name: &str,
-    event_bus_name: &str,
+    repository: &impl TodoRepository,
 ) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
     let path_parameters = request.path_parameters();
 
-    let list_id = path_parameters.first("listId").ok_or(FailureResponse {
-        status_code: StatusCode::BAD_REQUEST,
-        body: "Missing list id".into(),
-    })?;
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
 
-    let body = match request.body() {
-        Body::Text(body) => serde_json::from_str::<CreateTodo>(body).map_err(|_| FailureResponse {
-            status_code: StatusCode::BAD_REQUEST,
-            body: "Invalid request".into(),
-        }),
-        _ => Err(FailureResponse {
-            status_code: StatusCode::BAD_REQUEST,
-            body: "Invalid request".into(),
-        }),
-    }?;
+    // generate ulid in order to have sorted items
+    let key = TodoKey::new(list_id, &Ulid::new().to_string())
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
 
-    let start = Instant::now();
+    let caller = Caller::from_request(&request)?;
 
-    // generate ulid in order to have sorted items
-    let todo_id = Ulid::new().to_string();
-
-    dynamodb_client
-        .put_item()
-        .table_name(todos_table_name)
-        .item("PK", AttributeValue::S(format!("TODO#{list_id}")))
-        .item("SK", AttributeValue::S(format!("ID#{todo_id}")))
-        .item("id", AttributeValue::S(todo_id.clone()))
-        .item("list_id", AttributeValue::S(list_id.to_string()))
-        .item("title", AttributeValue::S(body.title.to_string()))
-        .item(
-            "description",
-            AttributeValue::S(body.description.to_string()),
-        )
-        .send()
+    let body = parse_body::<CreateTodo>(&request)?;
+
+    let access = caller
+        .list_access(repository, list_id)
         .await
         .map_err(|err| {
-            error!(err = ?err, "Unable to set todo");
+            error!(err = ?err, "Unable to get list access");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to set todo".into(),
-            }
+            FailureResponse::new(ErrorCode::InternalError, "Unable to set todo")
         })?;
 
-    info!(
-        todo_id = todo_id,
-        list_id = list_id,
-        "Successfully created todo",
-    );
+    let list = access.existing_list()?;
 
-    debug!("Item stored in {:.2?}", start.elapsed());
+    access.require(Role::Editor)?;
+
+    if list.archived {
+        return Err(FailureResponse::new(
+            ErrorCode::Conflict,
+            "The list is archived",
+        ));
+    }
+
+    let start = Instant::now();
 
     let todo = Todo {
-        id: todo_id,
-        list_id: list_id.into(),
+        id: key.todo_id,
+        list_id: key.list_id,
         title: body.title,
         description: body.description,
+        completed: false,
+        completed_at: None,
+        owner: Some(caller.id()),
     };
 
-    let todo = serde_json::to_value(todo).map_err(|_| FailureResponse {
-        status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        body: "Unable to serialize todo".into(),
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::TodoCreated(
+        todo.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize todo");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
     })?;
 
-    let entries = PutEventsRequestEntry::builder()
-        .event_bus_name(event_bus_name)
-        .source("api.todos")
-        .detail_type("TODO_CREATED")
-        .detail(todo.to_string())
-        .build();
-
-    // ignore the errors here
-    let _ = eventbridge_client
-        .put_events()
-        .entries(entries)
-        .send()
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .create(&todo, &outbox_record)
         .await
-        .map_err(|err| {
-            error!(err = ?err, "Unable to send confirmation event");
+        .map_err(|err| match err {
+            // archived since the access was checked
+            RepositoryError::ListClosed => {
+                FailureResponse::new(ErrorCode::Conflict, "The list is archived")
+            }
+            err => {
+                error!(err = ?err, "Unable to set todo");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to send confirmation event".into(),
+                FailureResponse::new(ErrorCode::InternalError, "Unable to set todo")
             }
-        });
+        })?;
+
+    info!(
+        todo_id = todo.id,
+        list_id = list_id,
+        "Successfully created todo",
+    );
+
+    debug!("Item stored in {:.2?}", start.elapsed());
+
+    let todo = serde_json::to_value(todo)
+        .map_err(|_| FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo"))?;
 
     Ok((StatusCode::CREATED, todo))
 }
@@ -121,57 +128,45 @@ pub(crate) async fn handler(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use aws_sdk_eventbridge::operation::put_events::PutEventsOutput;
-    use aws_smithy_mocks_experimental::{mock, mock_client};
-
-    use aws_sdk_dynamodb::operation::put_item::PutItemOutput;
     use serde_json::json;
+    use shared::{
+        testing::{iam_id, list, member, ApiGatewayRequest},
+        InMemoryTodoRepository, List,
+    };
+
+    fn get_request(body: &str) -> Request {
+        get_request_as(body, "arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(body: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("POST")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .body(body)
+            .build()
+    }
+
+    async fn repository_with_list(archived: bool) -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&List {
+                archived,
+                ..list("toto", &iam_id("arn:aws:iam::123456789012:user/toto"))
+            })
+            .await;
+
+        repository
+    }
 
     #[tokio::test]
     async fn test_handler() {
-        let mock_put_item = mock!(aws_sdk_dynamodb::Client::put_item)
-            .then_output(|| PutItemOutput::builder().build());
-        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_put_item]);
-
-        let mock_put_events = mock!(aws_sdk_eventbridge::Client::put_events)
-            .then_output(|| PutEventsOutput::builder().build());
-        let eventbridge_client = mock_client!(aws_sdk_eventbridge, &[&mock_put_events]);
-
-        let req = json!({
-          "version": "2.0",
-          "routeKey": "$default",
-          "rawPath": "/my/path",
-          "rawQueryString": "",
-          "cookies": [],
-          "headers": {},
-          "queryStringParameters": {},
-          "requestContext": {
-            "accountId": "123456789012",
-            "apiId": "api-id",
-            "domainName": "id.execute-api.us-east-1.amazonaws.com",
-            "domainPrefix": "id",
-            "http": {
-              "method": "POST",
-              "path": "/my/path",
-              "protocol": "HTTP/1.1",
-              "sourceIp": "IP",
-              "userAgent": "agent"
-            },
-            "requestId": "id",
-            "routeKey": "$default",
-            "stage": "$default",
-            "time": "12/Mar/2020:19:03:58 +0000"
-          },
-          "body": "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}",
-          "pathParameters": { "listId": "toto" },
-          "isBase64Encoded": false,
-          "stageVariables": {}
-        })
-        .to_string();
-
-        let event = lambda_http::request::from_str(&req).unwrap();
-
-        let (status, res) = handler(event, &dynamodb_client, &eventbridge_client, "toto", "tata")
+        let repository = repository_with_list(false).await;
+
+        let event = get_request(
+            "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}",
+        );
+
+        let (status, res) = handler(event, &repository)
             .await
             .expect("failed to handle event");
 
@@ -181,5 +176,160 @@ mod tests {
 
         assert_eq!(todo.title, "Toto todo");
         assert_eq!(todo.description, "This is a great description");
+        assert!(!todo.completed);
+        assert_eq!(
+            todo.owner,
+            Some(iam_id("arn:aws:iam::123456789012:user/toto"))
+        );
+
+        let stored = repository
+            .get(&TodoKey::new("toto", &todo.id).unwrap())
+            .await
+            .unwrap()
+            .expect("todo should be stored");
+
+        assert_eq!(stored.title, "Toto todo");
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 2);
+        assert_eq!(outbox_records[1].detail_type, "TODO_CREATED");
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_fields() {
+        let repository = InMemoryTodoRepository::new();
+
+        let description = "a".repeat(2001);
+        let body = json!({ "title": "   ", "description": description }).to_string();
+
+        let err = handler(get_request(&body), &repository)
+            .await
+            .expect_err("fields should be invalid");
+
+        assert_eq!(err.status_code, 422);
+        assert_eq!(
+            err.details,
+            Some(json!({
+                "fields": [
+                    { "field": "title", "reason": "must not be blank" },
+                    { "field": "description", "reason": "must be at most 2000 characters" },
+                ]
+            }))
+        );
+        assert!(repository.outbox_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handler_malformed_body() {
+        let repository = InMemoryTodoRepository::new();
+
+        let err = handler(get_request("{\"description\": \"No title\"}"), &repository)
+            .await
+            .expect_err("body should be malformed");
+
+        assert_eq!(err.status_code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_list(false).await;
+        let body = "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}";
+
+        handler(get_request(body), &repository)
+            .await
+            .expect("failed to handle event");
+
+        let err = handler(
+            get_request_as(body, "arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("the list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(repository.outbox_records().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_list(false).await;
+        let body = "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}";
+
+        handler(get_request(body), &repository)
+            .await
+            .expect("failed to handle event");
+
+        for (user, role) in [("tata", Role::Editor), ("titi", Role::Viewer)] {
+            let user_id = iam_id(&format!("arn:aws:iam::123456789012:user/{user}"));
+
+            repository
+                .seed_member(&member("toto", &user_id, role))
+                .await;
+        }
+
+        let (status, _) = handler(
+            get_request_as(body, "arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect("an editor should create todos");
+
+        assert_eq!(status, 201);
+
+        let err = handler(
+            get_request_as(body, "arn:aws:iam::123456789012:user/titi"),
+            &repository,
+        )
+        .await
+        .expect_err("a viewer should not create todos");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(err.message, "Insufficient role on the list");
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_not_found() {
+        let repository = InMemoryTodoRepository::new();
+        let body = "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}";
+
+        let err = handler(get_request(body), &repository)
+            .await
+            .expect_err("the list should not exist");
+
+        assert_eq!(err.status_code, 404);
+        assert_eq!(err.code, ErrorCode::ListNotFound);
+        assert!(repository.outbox_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handler_archived_list() {
+        let repository = repository_with_list(true).await;
+        let body = "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}";
+
+        let err = handler(get_request(body), &repository)
+            .await
+            .expect_err("the list should be archived");
+
+        assert_eq!(err.status_code, 409);
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_list_id() {
+        let repository = InMemoryTodoRepository::new();
+        let body = "{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}";
+        let event = ApiGatewayRequest::new("POST")
+            .user_arn("arn:aws:iam::123456789012:user/toto")
+            .path_parameter("listId", "to#to")
+            .body(body)
+            .build();
+
+        // rejected before the list is looked up, which would be a 404
+        let err = handler(event, &repository)
+            .await
+            .expect_err("the list id should be invalid");
+
+        assert_eq!(err.status_code, 400);
+        assert_eq!(err.code, ErrorCode::InvalidId);
     }
 }
@@ -0,0 +1,196 @@
+use aws_lambda_events::http::StatusCode;
+use chrono::Utc;
+use shared::{
+    auth::Caller, keys::ListKey, DeletionStatus, ErrorCode, EventEnvelope, FailureResponse, List,
+    ListDeletion, OutboxRecord, RepositoryError, Role, TodoEvent, TodoRepository,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+/// Requests the deletion of a list along with its todos and members.
+///
+/// The list is archived until the `on-list-deletion-requested` consumer deletes it, the progress
+/// is polled on `GET /lists/{listId}/deletion`.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    ListKey::new(list_id).map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    let access = caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to delete list")
+        })?;
+
+    let list = access.existing_list()?;
+
+    access.require(Role::Owner)?;
+
+    let deletion = ListDeletion {
+        list_id: list_id.into(),
+        status: DeletionStatus::InProgress,
+        requested_by: caller.id(),
+        requested_at: Utc::now(),
+        deleted_todos: 0,
+        completed_at: None,
+    };
+
+    // no todo is added to an archived list while its todos are deleted
+    let list = List {
+        archived: true,
+        ..list.clone()
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListDeletionRequested(
+        deletion.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize list deletion");
+
+        FailureResponse::new(
+            ErrorCode::InternalError,
+            "Unable to serialize list deletion",
+        )
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .request_list_deletion(&list, &deletion, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::Conflict => {
+                FailureResponse::new(ErrorCode::Conflict, "The list is already being deleted")
+            }
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::ListNotFound, "List not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to request list deletion");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to delete list")
+            }
+        })?;
+
+    debug!("Item updated in {:.2?}", start.elapsed());
+
+    info!(list_id = list_id, "Successfully requested list deletion");
+
+    let deletion = serde_json::to_value(deletion).map_err(|err| {
+        error!(err = ?err, "Unable to serialize list deletion");
+
+        FailureResponse::new(
+            ErrorCode::InternalError,
+            "Unable to serialize list deletion",
+        )
+    })?;
+
+    Ok((StatusCode::ACCEPTED, deletion))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+
+    fn get_request_as(user_arn: &str) -> Request {
+        ApiGatewayRequest::new("DELETE")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .build()
+    }
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", &iam_id(OWNER))).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_list().await;
+
+        let (status, res) = handler(get_request_as(OWNER), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 202);
+
+        let deletion: ListDeletion = serde_json::from_value(res).unwrap();
+
+        assert_eq!(deletion.status, DeletionStatus::InProgress);
+        assert_eq!(deletion.requested_by, iam_id(OWNER));
+        assert_eq!(
+            repository.get_list_deletion("toto").await.unwrap(),
+            Some(deletion)
+        );
+
+        // the list is archived until the consumer deletes it
+        let list = repository.get_list("toto").await.unwrap().unwrap();
+
+        assert!(list.archived);
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 2);
+        assert_eq!(outbox_records[1].detail_type, "LIST_DELETION_REQUESTED");
+
+        let err = handler(get_request_as(OWNER), &repository)
+            .await
+            .expect_err("deletion should already be requested");
+
+        assert_eq!(err.status_code, 409);
+        assert_eq!(err.message, "The list is already being deleted");
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+
+        let err = handler(get_request_as(OWNER), &repository)
+            .await
+            .expect_err("list should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_owner() {
+        let repository = repository_with_list().await;
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("only the owner should delete the list");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
@@ -1,111 +1,424 @@
 use aws_lambda_events::http::StatusCode;
-use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
-use aws_sdk_eventbridge::types::PutEventsRequestEntry;
-use shared::{DynamoDBError, FailureResponse, Todo};
+use shared::{
+    auth::Caller, keys::TodoKey, ErrorCode, EventEnvelope, FailureResponse, OutboxRecord,
+    RepositoryError, Role, TodoEvent, TodoRepository,
+};
 
 use lambda_http::{
     tracing::{self, debug, error, info},
     Request, RequestExt,
 };
 
-use std::{collections::HashMap, time::Instant};
+use std::time::Instant;
 
 #[tracing::instrument(skip_all)]
-pub(crate) async fn handler(
+pub async fn handler(
     request: Request,
-    dynamodb_client: &aws_sdk_dynamodb::Client,
-    eventbridge_client: &aws_sdk_eventbridge::Client,
-    todos_table_name: &str,
-    event_bus_name: &str,
+    repository: &impl TodoRepository,
 ) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
     let path_parameters = request.path_parameters();
 
-    let list_id = path_parameters.first("listId").ok_or(FailureResponse {
-        status_code: StatusCode::BAD_REQUEST,
-        body: "Missing list id".into(),
-    })?;
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
 
-    let todo_id = path_parameters.first("todoId").ok_or(FailureResponse {
-        status_code: StatusCode::BAD_REQUEST,
-        body: "Invalid request".into(),
-    })?;
+    let todo_id = path_parameters.first("todoId").ok_or(FailureResponse::new(
+        ErrorCode::InvalidRequest,
+        "Invalid request",
+    ))?;
+
+    let key = TodoKey::new(list_id, todo_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
 
     let start = Instant::now();
 
-    let res = dynamodb_client
-        .delete_item()
-        .table_name(todos_table_name)
-        .set_key(Some(HashMap::from([
-            ("PK".into(), AttributeValue::S(format!("TODO#{list_id}"))),
-            ("SK".into(), AttributeValue::S(format!("ID#{todo_id}"))),
-        ])))
-        .return_values(ReturnValue::AllOld)
-        .send()
+    caller
+        .list_access(repository, list_id)
         .await
         .map_err(|err| {
-            error!(err = ?err, "Unable to set todo");
+            error!(err = ?err, "Unable to get list access");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to delete todo".into(),
-            }
-        })?;
+            FailureResponse::new(ErrorCode::InternalError, "Unable to delete todo")
+        })?
+        .require(Role::Editor)?;
 
-    debug!("Item deleted in {:.2?}", start.elapsed());
-
-    let todo = res
-        .attributes
-        .ok_or_else(|| {
-            error!("Unexpected empty attributes");
-
-            DynamoDBError::EmptyAttributes
-        })
-        .and_then(Todo::try_from)
+    // transactions can't return the deleted item, which the event needs
+    let todo = repository
+        .get(&key)
+        .await
         .map_err(|err| {
-            error!(err = ?err, "Unable to deserialize todo");
+            error!(err = ?err, "Unable to get todo");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to deserialize todo".into(),
-            }
-        })?;
+            FailureResponse::new(ErrorCode::InternalError, "Unable to delete todo")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::TodoNotFound,
+            "Todo not found",
+        ))?;
 
-    let todo = serde_json::to_value(todo).map_err(|err| {
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::TodoDeleted(
+        todo.clone(),
+    )))
+    .map_err(|err| {
         error!(err = ?err, "Unable to serialize todo");
 
-        FailureResponse {
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            body: "Unable to serialize todo".into(),
-        }
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
     })?;
 
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .delete(&todo, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::Conflict => {
+                FailureResponse::new(ErrorCode::Conflict, "Todo was modified during deletion")
+            }
+            RepositoryError::ListClosed => {
+                FailureResponse::new(ErrorCode::Conflict, "The list is archived")
+            }
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::TodoNotFound, "Todo not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to delete todo");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to delete todo")
+            }
+        })?;
+
+    debug!("Item deleted in {:.2?}", start.elapsed());
+
     info!(
         todo_id = todo_id,
         list_id = list_id,
         "Successfully deleted todo",
     );
 
-    let entries = PutEventsRequestEntry::builder()
-        .event_bus_name(event_bus_name)
-        .source("api.todos")
-        .detail_type("TODO_DELETED")
-        .detail(todo.to_string())
-        .build();
-
-    // ignore the errors here
-    let _ = eventbridge_client
-        .put_events()
-        .entries(entries)
-        .send()
+    Ok((StatusCode::NO_CONTENT, "".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_dynamodb::types::AttributeValue;
+    use shared::{
+        keys::ListMemberKey,
+        testing::{self, iam_id, list, ApiGatewayRequest},
+        CounterIncrement, DeletedTodos, InMemoryTodoRepository, List, ListCounter, ListDeletion,
+        ListMember, ListPage, Processing, Todo, TodoPage,
+    };
+    use std::collections::HashMap;
+
+    fn get_request() -> Request {
+        get_request_as("arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(user_arn: &str) -> Request {
+        ApiGatewayRequest::new("DELETE")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .path_parameter("todoId", "tata")
+            .build()
+    }
+
+    fn todo(completed: bool) -> Todo {
+        Todo {
+            completed,
+            owner: Some(iam_id("arn:aws:iam::123456789012:user/toto")),
+            ..testing::todo("toto", "tata")
+        }
+    }
+
+    async fn repository_with_todo() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+        repository.seed_todo(&todo(true)).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_todo().await;
+
+        let (status, _) = handler(get_request(), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 204);
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert!(repository.get(&key).await.unwrap().is_none());
+
+        let outbox_records = repository.outbox_records();
+        let envelope = outbox_records[2].envelope().unwrap();
+
+        // the deleted todo is carried by the event, for the counters
+        assert!(matches!(envelope.event, TodoEvent::TodoDeleted(todo) if todo.completed));
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        let err = handler(get_request(), &repository)
+            .await
+            .expect_err("todo should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_todo_of_another_user() {
+        let repository = repository_with_todo().await;
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
         .await
-        .map_err(|err| {
-            error!(err = ?err, "Unable to send confirmation event");
+        .expect_err("todo should belong to another user");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to send confirmation event".into(),
-            }
-        });
+        assert_eq!(err.status_code, 403);
 
-    Ok((StatusCode::NO_CONTENT, "".into()))
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert!(repository.get(&key).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handler_viewer() {
+        let repository = repository_with_todo().await;
+        repository
+            .seed_member(&testing::member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("a viewer should not delete todos");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(err.message, "Insufficient role on the list");
+    }
+
+    /// Reads the todos as they were before being reopened.
+    struct StaleRepository(InMemoryTodoRepository);
+
+    impl TodoRepository for StaleRepository {
+        async fn create(
+            &self,
+            todo: &Todo,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.create(todo, outbox_record).await
+        }
+
+        async fn get(&self, key: &TodoKey) -> Result<Option<Todo>, RepositoryError> {
+            let todo = self.0.get(key).await?;
+
+            Ok(todo.map(|todo| Todo {
+                completed: true,
+                ..todo
+            }))
+        }
+
+        async fn list(
+            &self,
+            list_id: &str,
+            limit: i32,
+            exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+        ) -> Result<TodoPage, RepositoryError> {
+            self.0.list(list_id, limit, exclusive_start_key).await
+        }
+
+        async fn delete(
+            &self,
+            todo: &Todo,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.delete(todo, outbox_record).await
+        }
+
+        async fn update(
+            &self,
+            old: &Todo,
+            new: &Todo,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.update(old, new, outbox_record).await
+        }
+
+        async fn set_completion(
+            &self,
+            todo: &Todo,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.set_completion(todo, outbox_record).await
+        }
+
+        async fn get_counter(&self, list_id: &str) -> Result<ListCounter, RepositoryError> {
+            self.0.get_counter(list_id).await
+        }
+
+        async fn increment_counter(
+            &self,
+            list_id: &str,
+            increment: CounterIncrement,
+            consumer: &str,
+            event_id: &str,
+        ) -> Result<Processing, RepositoryError> {
+            self.0
+                .increment_counter(list_id, increment, consumer, event_id)
+                .await
+        }
+
+        async fn get_member(
+            &self,
+            key: &ListMemberKey,
+        ) -> Result<Option<ListMember>, RepositoryError> {
+            self.0.get_member(key).await
+        }
+
+        async fn add_member(
+            &self,
+            member: &ListMember,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.add_member(member, outbox_record).await
+        }
+
+        async fn update_member(
+            &self,
+            member: &ListMember,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.update_member(member, outbox_record).await
+        }
+
+        async fn remove_member(
+            &self,
+            member: &ListMember,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.remove_member(member, outbox_record).await
+        }
+
+        async fn create_list(
+            &self,
+            list: &List,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.create_list(list, outbox_record).await
+        }
+
+        async fn get_list(&self, list_id: &str) -> Result<Option<List>, RepositoryError> {
+            self.0.get_list(list_id).await
+        }
+
+        async fn update_list(
+            &self,
+            list: &List,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.update_list(list, outbox_record).await
+        }
+
+        async fn request_list_deletion(
+            &self,
+            list: &List,
+            deletion: &ListDeletion,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0
+                .request_list_deletion(list, deletion, outbox_record)
+                .await
+        }
+
+        async fn get_list_deletion(
+            &self,
+            list_id: &str,
+        ) -> Result<Option<ListDeletion>, RepositoryError> {
+            self.0.get_list_deletion(list_id).await
+        }
+
+        async fn update_list_deletion(
+            &self,
+            deletion: &ListDeletion,
+        ) -> Result<(), RepositoryError> {
+            self.0.update_list_deletion(deletion).await
+        }
+
+        async fn delete_todos(
+            &self,
+            list_id: &str,
+            limit: i32,
+            exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+        ) -> Result<DeletedTodos, RepositoryError> {
+            self.0
+                .delete_todos(list_id, limit, exclusive_start_key)
+                .await
+        }
+
+        async fn delete_members(&self, list_id: &str) -> Result<usize, RepositoryError> {
+            self.0.delete_members(list_id).await
+        }
+
+        async fn delete_list(
+            &self,
+            list: &List,
+            deletion: &ListDeletion,
+            outbox_record: &OutboxRecord,
+        ) -> Result<(), RepositoryError> {
+            self.0.delete_list(list, deletion, outbox_record).await
+        }
+
+        async fn owner_lists(
+            &self,
+            owner: &str,
+            limit: i32,
+            exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+        ) -> Result<ListPage, RepositoryError> {
+            self.0.owner_lists(owner, limit, exclusive_start_key).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_concurrent_update() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+        repository.seed_todo(&todo(false)).await;
+
+        let err = handler(get_request(), &StaleRepository(repository))
+            .await
+            .expect_err("deletion should conflict");
+
+        assert_eq!(err.status_code, 409);
+    }
 }
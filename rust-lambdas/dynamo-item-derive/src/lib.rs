@@ -0,0 +1,143 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derive `shared::DynamoItem` and `shared::DynamoAttribute` for a struct with named fields.
+///
+/// Each field is stored under its own name, unless renamed with `#[dynamo(rename = "...")]`.
+/// Fields marked with `#[dynamo(default)]` fall back to `Default::default()` when the
+/// attribute is missing from the item.
+#[proc_macro_derive(DynamoItem, attributes(dynamo))]
+pub fn derive_dynamo_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct FieldOptions {
+    rename: Option<String>,
+    default: bool,
+}
+
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions {
+        rename: None,
+        default: false,
+    };
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("dynamo"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                options.default = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported dynamo attribute"))
+            }
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "DynamoItem can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "DynamoItem can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut to_item = Vec::with_capacity(fields.len());
+    let mut from_item = Vec::with_capacity(fields.len());
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let options = field_options(field)?;
+        let attribute = options.rename.unwrap_or_else(|| ident.to_string());
+
+        to_item.push(quote! {
+            if let ::std::option::Option::Some(value) =
+                ::shared::DynamoAttribute::to_attribute(&self.#ident)
+            {
+                item.insert(#attribute.to_string(), value);
+            }
+        });
+
+        from_item.push(match options.default {
+            true => quote! {
+                #ident: match item.get(#attribute) {
+                    ::std::option::Option::None => ::std::default::Default::default(),
+                    value => <#ty as ::shared::DynamoAttribute>::from_attribute(value, #attribute)?,
+                }
+            },
+            false => quote! {
+                #ident: <#ty as ::shared::DynamoAttribute>::from_attribute(item.get(#attribute), #attribute)?
+            },
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::shared::DynamoItem for #name #ty_generics #where_clause {
+            fn to_item(&self) -> ::std::collections::HashMap<::std::string::String, ::shared::AttributeValue> {
+                let mut item = ::std::collections::HashMap::new();
+
+                #(#to_item)*
+
+                item
+            }
+
+            fn from_item(
+                item: ::std::collections::HashMap<::std::string::String, ::shared::AttributeValue>,
+            ) -> ::std::result::Result<Self, ::shared::DynamoDBError> {
+                ::std::result::Result::Ok(Self {
+                    #(#from_item),*
+                })
+            }
+        }
+
+        impl #impl_generics ::shared::DynamoAttribute for #name #ty_generics #where_clause {
+            fn to_attribute(&self) -> ::std::option::Option<::shared::AttributeValue> {
+                ::std::option::Option::Some(::shared::AttributeValue::M(
+                    ::shared::DynamoItem::to_item(self),
+                ))
+            }
+
+            fn from_attribute(
+                value: ::std::option::Option<&::shared::AttributeValue>,
+                attribute: &str,
+            ) -> ::std::result::Result<Self, ::shared::DynamoDBError> {
+                let item = <::std::collections::HashMap<
+                    ::std::string::String,
+                    ::shared::AttributeValue,
+                > as ::shared::DynamoAttribute>::from_attribute(value, attribute)?;
+
+                <Self as ::shared::DynamoItem>::from_item(item)
+                    .map_err(|err| ::shared::DynamoDBError::nested(attribute, err))
+            }
+        }
+    })
+}
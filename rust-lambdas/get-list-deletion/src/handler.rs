@@ -0,0 +1,142 @@
+use aws_lambda_events::http::StatusCode;
+use shared::{auth::Caller, keys::ListDeletionKey, ErrorCode, FailureResponse, TodoRepository};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+/// The progress of a list deletion, only visible to the owner who requested it.
+///
+/// The deletion outlives the list, so that its completion can be polled.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    ListDeletionKey::new(list_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    let deletion = repository
+        .get_list_deletion(list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list deletion");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to get deletion")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::DeletionNotFound,
+            "Deletion not found",
+        ))?;
+
+    debug!("Item retrieved in {:.2?}", start.elapsed());
+
+    if deletion.requested_by != caller.id() {
+        return Err(FailureResponse::new(
+            ErrorCode::Forbidden,
+            "The deletion was requested by another user",
+        ));
+    }
+
+    info!(
+        list_id = list_id,
+        status = deletion.status.as_str(),
+        "Successfully retrieved list deletion"
+    );
+
+    let deletion = serde_json::to_value(deletion).map_err(|err| {
+        error!(err = ?err, "Unable to serialize list deletion");
+
+        FailureResponse::new(
+            ErrorCode::InternalError,
+            "Unable to serialize list deletion",
+        )
+    })?;
+
+    Ok((StatusCode::OK, deletion))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        DeletionStatus, EventEnvelope, InMemoryTodoRepository, ListDeletion, OutboxRecord,
+        TodoEvent,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+
+    fn get_request_as(user_arn: &str) -> Request {
+        ApiGatewayRequest::new("GET")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = InMemoryTodoRepository::new();
+
+        let err = handler(get_request_as(OWNER), &repository)
+            .await
+            .expect_err("deletion should not be found");
+
+        assert_eq!(err.status_code, 404);
+        assert_eq!(err.code, ErrorCode::DeletionNotFound);
+
+        let list = list("toto", &iam_id(OWNER));
+        repository.seed_list(&list).await;
+
+        let deletion = ListDeletion {
+            list_id: "toto".into(),
+            status: DeletionStatus::InProgress,
+            requested_by: iam_id(OWNER),
+            requested_at: "2024-05-01T11:00:00Z".parse().unwrap(),
+            deleted_todos: 0,
+            completed_at: None,
+        };
+        let outbox_record = OutboxRecord::new(&EventEnvelope::new(
+            TodoEvent::ListDeletionRequested(deletion.clone()),
+        ))
+        .unwrap();
+
+        repository
+            .request_list_deletion(&list, &deletion, &outbox_record)
+            .await
+            .unwrap();
+
+        let (status, res) = handler(get_request_as(OWNER), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+        assert_eq!(
+            serde_json::from_value::<ListDeletion>(res).unwrap(),
+            deletion
+        );
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("another user should not see the deletion");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
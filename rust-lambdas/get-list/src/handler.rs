@@ -0,0 +1,210 @@
+use aws_lambda_events::http::StatusCode;
+use shared::{
+    auth::Caller, keys::ListCounterKey, ErrorCode, FailureResponse, ListSummary, Role,
+    TodoRepository,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    ListCounterKey::new(list_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    let access = caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to get list")
+        })?;
+
+    let list = access.existing_list()?;
+
+    access.require(Role::Viewer)?;
+
+    let counter = repository.get_counter(list_id).await.map_err(|err| {
+        error!(err = ?err, "Unable to get list counter");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to get list")
+    })?;
+
+    debug!("Item retrieved in {:.2?}", start.elapsed());
+
+    info!(list_id = list_id, "Successfully retrieved list");
+
+    let list = serde_json::to_value(ListSummary {
+        list_id: list.id.clone(),
+        name: list.name.clone(),
+        owner: list.owner.clone(),
+        created_at: list.created_at,
+        archived: list.archived,
+        todos_count: counter.todos_count,
+        completed_count: counter.completed_count,
+    })
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize list");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize list")
+    })?;
+
+    Ok((StatusCode::OK, list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{fixture_date, iam_id, list, member, ApiGatewayRequest},
+        CounterIncrement, InMemoryTodoRepository,
+    };
+
+    fn get_request() -> Request {
+        get_request_as("arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(user_arn: &str) -> Request {
+        ApiGatewayRequest::new("GET")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .build()
+    }
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_list().await;
+
+        for (event_id, increment) in [
+            (
+                "event-1",
+                CounterIncrement {
+                    todos_count: 3,
+                    completed_count: 0,
+                },
+            ),
+            (
+                "event-2",
+                CounterIncrement {
+                    todos_count: 0,
+                    completed_count: 1,
+                },
+            ),
+        ] {
+            repository
+                .increment_counter("toto", increment, "toto", event_id)
+                .await
+                .unwrap();
+        }
+
+        let (status, res) = handler(get_request(), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let list: ListSummary = serde_json::from_value(res).unwrap();
+
+        assert_eq!(list.list_id, "toto");
+        assert_eq!(list.name, "Toto list");
+        assert_eq!(list.owner, iam_id("arn:aws:iam::123456789012:user/toto"));
+        assert_eq!(list.created_at, fixture_date());
+        assert!(!list.archived);
+        assert_eq!(list.todos_count, 3);
+        assert_eq!(list.completed_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_empty_list() {
+        let repository = repository_with_list().await;
+
+        let (status, res) = handler(get_request(), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let list: ListSummary = serde_json::from_value(res).unwrap();
+
+        assert_eq!(list.todos_count, 0);
+        assert_eq!(list.completed_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+
+        let err = handler(get_request(), &repository)
+            .await
+            .expect_err("list should not be found");
+
+        assert_eq!(err.status_code, 404);
+        assert_eq!(err.code, ErrorCode::ListNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_list().await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let (status, _) = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect("a viewer should get the list");
+
+        assert_eq!(status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_list().await;
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
@@ -0,0 +1,184 @@
+use aws_lambda_events::http::StatusCode;
+use shared::{auth::Caller, keys::TodoKey, ErrorCode, FailureResponse, Role, TodoRepository};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    let todo_id = path_parameters.first("todoId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing todo id",
+    ))?;
+
+    let key = TodoKey::new(list_id, todo_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to get todo")
+        })?
+        .require(Role::Viewer)?;
+
+    let todo = repository
+        .get(&key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get todo");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to get todo")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::TodoNotFound,
+            "Todo not found",
+        ))?;
+
+    debug!("Item retrieved in {:.2?}", start.elapsed());
+
+    info!(
+        todo_id = todo_id,
+        list_id = list_id,
+        "Successfully retrieved todo",
+    );
+
+    let todo = serde_json::to_value(todo).map_err(|err| {
+        error!(err = ?err, "Unable to serialize todo");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
+    })?;
+
+    Ok((StatusCode::OK, todo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, member, todo, ApiGatewayRequest},
+        InMemoryTodoRepository, Todo,
+    };
+
+    fn get_request() -> Request {
+        get_request_as("arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(user_arn: &str) -> Request {
+        ApiGatewayRequest::new("GET")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .path_parameter("todoId", "tata")
+            .build()
+    }
+
+    async fn repository_with_todo() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+        repository
+            .seed_todo(&Todo {
+                owner: Some(iam_id("arn:aws:iam::123456789012:user/toto")),
+                ..todo("toto", "tata")
+            })
+            .await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_todo().await;
+
+        let (status, res) = handler(get_request(), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let todo: Todo = serde_json::from_value(res).unwrap();
+
+        assert_eq!(todo.id, "tata");
+        assert_eq!(todo.list_id, "toto");
+        assert_eq!(todo.title, "Toto todo");
+        assert_eq!(todo.description, "This is a great description");
+        assert!(!todo.completed);
+        assert_eq!(todo.completed_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        let err = handler(get_request(), &repository)
+            .await
+            .expect_err("todo should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_todo().await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let (status, _) = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect("a viewer should get todos");
+
+        assert_eq!(status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_todo().await;
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
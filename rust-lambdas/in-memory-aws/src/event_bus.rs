@@ -0,0 +1,293 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use aws_lambda_events::eventbridge::EventBridgeEvent;
+use aws_sdk_eventbridge::{
+    operation::put_events::PutEventsOutput,
+    types::{PutEventsRequestEntry, PutEventsResultEntry},
+};
+use aws_smithy_mocks::{mock, mock_client, RuleMode};
+use chrono::Utc;
+use lambda_runtime::{Context, Error, LambdaEvent};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tokio::sync::Notify;
+use tracing::{debug, error};
+use ulid::Ulid;
+
+type Consumer =
+    Box<dyn Fn(LambdaEvent<serde_json::Value>) -> ConsumerFuture + Send + Sync + 'static>;
+type ConsumerFuture = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+/// The `source` and `detail-type` filters of an EventBridge rule.
+#[derive(Clone, Debug)]
+pub struct EventPattern {
+    pub sources: Vec<String>,
+    pub detail_types: Vec<String>,
+}
+
+impl EventPattern {
+    pub fn new(source: &str, detail_types: &[&str]) -> Self {
+        EventPattern {
+            sources: vec![source.into()],
+            detail_types: detail_types
+                .iter()
+                .map(|&detail_type| detail_type.into())
+                .collect(),
+        }
+    }
+
+    pub fn matches(&self, entry: &PutEventsRequestEntry) -> bool {
+        let matches = |values: &[String], value: Option<&str>| {
+            value.is_some_and(|value| values.iter().any(|expected| expected == value))
+        };
+
+        matches(&self.sources, entry.source()) && matches(&self.detail_types, entry.detail_type())
+    }
+}
+
+struct Subscription {
+    pattern: EventPattern,
+    consumer: Consumer,
+}
+
+#[derive(Default)]
+struct Bus {
+    pending: Mutex<VecDeque<PutEventsRequestEntry>>,
+    published: Mutex<Vec<PutEventsRequestEntry>>,
+    subscriptions: Mutex<Vec<Arc<Subscription>>>,
+    notify: Notify,
+}
+
+/// An event bus delivering the published entries to in-process consumers.
+///
+/// The entries are queued by the client and delivered by [`InMemoryEventBus::dispatch_pending`],
+/// so that a test decides when the consumers run.
+#[derive(Clone, Default)]
+pub struct InMemoryEventBus {
+    bus: Arc<Bus>,
+}
+
+impl InMemoryEventBus {
+    pub fn new() -> Self {
+        InMemoryEventBus::default()
+    }
+
+    /// Deliver the entries matching the pattern to the handler, like a rule targeting a lambda.
+    pub fn subscribe<T, F, Fut>(&self, pattern: EventPattern, handler: F)
+    where
+        T: DeserializeOwned + Serialize,
+        F: Fn(LambdaEvent<EventBridgeEvent<T>>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let consumer: Consumer = Box::new(move |event| {
+            let handler = handler.clone();
+
+            Box::pin(async move {
+                let payload = serde_json::from_value(event.payload)?;
+
+                handler(LambdaEvent::new(payload, event.context)).await
+            })
+        });
+
+        self.bus
+            .subscriptions
+            .lock()
+            .unwrap()
+            .push(Arc::new(Subscription { pattern, consumer }));
+    }
+
+    /// A client publishing to this bus, whatever the bus name.
+    pub fn client(&self) -> aws_sdk_eventbridge::Client {
+        let bus = self.bus.clone();
+        let put_events =
+            mock!(aws_sdk_eventbridge::Client::put_events).then_compute_output(move |input| {
+                let entries = input
+                    .entries()
+                    .iter()
+                    .map(|entry| {
+                        bus.published.lock().unwrap().push(entry.clone());
+                        bus.pending.lock().unwrap().push_back(entry.clone());
+
+                        PutEventsResultEntry::builder()
+                            .event_id(Ulid::new().to_string())
+                            .build()
+                    })
+                    .collect();
+
+                bus.notify.notify_one();
+
+                PutEventsOutput::builder()
+                    .failed_entry_count(0)
+                    .set_entries(Some(entries))
+                    .build()
+            });
+
+        mock_client!(aws_sdk_eventbridge, RuleMode::MatchAny, &[&put_events])
+    }
+
+    /// All the entries published so far, in order.
+    pub fn published(&self) -> Vec<PutEventsRequestEntry> {
+        self.bus.published.lock().unwrap().clone()
+    }
+
+    /// Deliver the pending entries, including the ones published by the consumers meanwhile.
+    ///
+    /// Returns the number of entries delivered, or the first consumer error.
+    pub async fn dispatch_pending(&self) -> Result<usize, Error> {
+        let mut count = 0;
+
+        loop {
+            let Some(entry) = self.bus.pending.lock().unwrap().pop_front() else {
+                return Ok(count);
+            };
+
+            self.dispatch(&entry).await?;
+            count += 1;
+        }
+    }
+
+    /// Deliver the entries as they are published, until the task is dropped.
+    pub async fn run(self) {
+        loop {
+            self.bus.notify.notified().await;
+
+            if let Err(err) = self.dispatch_pending().await {
+                // EventBridge would retry, here the error is only logged
+                error!(err = ?err, "Unable to dispatch event");
+            }
+        }
+    }
+
+    async fn dispatch(&self, entry: &PutEventsRequestEntry) -> Result<(), Error> {
+        let subscriptions: Vec<_> = self
+            .bus
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|subscription| subscription.pattern.matches(entry))
+            .cloned()
+            .collect();
+
+        if subscriptions.is_empty() {
+            debug!(
+                source = entry.source(),
+                detail_type = entry.detail_type(),
+                "No consumer for event"
+            );
+        }
+
+        for subscription in subscriptions {
+            (subscription.consumer)(to_lambda_event(entry)?).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The event a consumer receives from EventBridge.
+fn to_lambda_event(entry: &PutEventsRequestEntry) -> Result<LambdaEvent<serde_json::Value>, Error> {
+    let detail: serde_json::Value = serde_json::from_str(entry.detail().unwrap_or("{}"))?;
+
+    let event = json!({
+        "version": "0",
+        "id": Ulid::new().to_string(),
+        "detail-type": entry.detail_type(),
+        "source": entry.source(),
+        "account": "000000000000",
+        "time": Utc::now(),
+        "region": "local",
+        "resources": entry.resources(),
+        "detail": detail,
+    });
+
+    Ok(LambdaEvent::new(event, Context::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dispatch_pending() {
+        let bus = InMemoryEventBus::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let consumer_received = received.clone();
+        bus.subscribe(
+            EventPattern::new("toto", &["TOTO_CREATED", "TOTO_DELETED"]),
+            move |event: LambdaEvent<EventBridgeEvent<serde_json::Value>>| {
+                let received = consumer_received.clone();
+
+                async move {
+                    received
+                        .lock()
+                        .unwrap()
+                        .push(event.payload.detail["id"].clone());
+
+                    Ok(())
+                }
+            },
+        );
+
+        let entry = |source: &str, detail_type: &str, id: &str| {
+            PutEventsRequestEntry::builder()
+                .source(source)
+                .detail_type(detail_type)
+                .detail(json!({ "id": id }).to_string())
+                .event_bus_name("toto")
+                .build()
+        };
+
+        bus.client()
+            .put_events()
+            .entries(entry("toto", "TOTO_CREATED", "tata"))
+            .entries(entry("toto", "TOTO_UPDATED", "titi"))
+            .entries(entry("tutu", "TOTO_DELETED", "tete"))
+            .entries(entry("toto", "TOTO_DELETED", "tata"))
+            .send()
+            .await
+            .unwrap();
+
+        assert!(received.lock().unwrap().is_empty());
+
+        assert_eq!(bus.dispatch_pending().await.unwrap(), 4);
+        assert_eq!(bus.dispatch_pending().await.unwrap(), 0);
+        assert_eq!(bus.published().len(), 4);
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![json!("tata"), json!("tata")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_pending_error() {
+        let bus = InMemoryEventBus::new();
+
+        bus.subscribe(
+            EventPattern::new("toto", &["TOTO_CREATED"]),
+            |_: LambdaEvent<EventBridgeEvent<serde_json::Value>>| async { Err("toto".into()) },
+        );
+
+        bus.client()
+            .put_events()
+            .entries(
+                PutEventsRequestEntry::builder()
+                    .source("toto")
+                    .detail_type("TOTO_CREATED")
+                    .detail("{}")
+                    .build(),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        assert!(bus.dispatch_pending().await.is_err());
+    }
+}
@@ -0,0 +1,8 @@
+//! An in-memory stand-in for the EventBridge bus of the stack.
+//!
+//! It serves a real SDK client, so that the handlers and the consumers can be run together
+//! without AWS, along with a `shared::InMemoryTodoRepository`.
+
+mod event_bus;
+
+pub use event_bus::{EventPattern, InMemoryEventBus};
@@ -0,0 +1,224 @@
+use aws_lambda_events::http::StatusCode;
+use chrono::Utc;
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::ListMemberKey, parse_body, ErrorCode, EventEnvelope, FailureResponse,
+    ListMember, OutboxRecord, RepositoryError, Role, TodoEvent, TodoRepository, Validate,
+    ValidationErrors, Validator, MEMBER_USER_ID,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct InviteMember {
+    user_id: String,
+    role: Role,
+}
+
+impl Validate for InviteMember {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::new();
+
+        // a list has a single owner
+        if self.role == Role::Owner {
+            validator.reject("role", "must be viewer or editor");
+        }
+
+        let member = InviteMember {
+            user_id: validator.text("user_id", &self.user_id, &MEMBER_USER_ID),
+            role: self.role,
+        };
+
+        validator.finish(member)
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let body = parse_body::<InviteMember>(&request)?;
+
+    ListMemberKey::new(list_id, &body.user_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let start = Instant::now();
+
+    let access = caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to invite member")
+        })?;
+
+    access.require(Role::Owner)?;
+
+    if access.owner.as_deref() == Some(body.user_id.as_str()) {
+        return Err(FailureResponse::new(
+            ErrorCode::Conflict,
+            "The user owns the list",
+        ));
+    }
+
+    let member = ListMember {
+        list_id: list_id.into(),
+        user_id: body.user_id,
+        role: body.role,
+        added_at: Utc::now(),
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListShared(
+        member.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize member");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize member")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .add_member(&member, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::Conflict => FailureResponse::new(
+                ErrorCode::Conflict,
+                "The user is already a member of the list",
+            ),
+            err => {
+                error!(err = ?err, "Unable to add member");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to invite member")
+            }
+        })?;
+
+    info!(list_id = list_id, role = ?member.role, "Successfully invited member");
+
+    debug!("Item stored in {:.2?}", start.elapsed());
+
+    let member = serde_json::to_value(member).map_err(|_| {
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize member")
+    })?;
+
+    Ok((StatusCode::CREATED, member))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+
+    fn get_request(body: &str) -> Request {
+        get_request_as(body, OWNER)
+    }
+
+    fn get_request_as(body: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("POST")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .body(body)
+            .build()
+    }
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", &iam_id(OWNER))).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_list().await;
+        let body =
+            json!({ "user_id": "iam:arn:aws:iam::123456789012:user/tata", "role": "editor" })
+                .to_string();
+
+        let (status, res) = handler(get_request(&body), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 201);
+
+        let member: ListMember = serde_json::from_value(res).unwrap();
+
+        assert_eq!(member.role, Role::Editor);
+
+        let key = ListMemberKey::new("toto", "iam:arn:aws:iam::123456789012:user/tata").unwrap();
+
+        assert!(repository.get_member(&key).await.unwrap().is_some());
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 2);
+        assert_eq!(outbox_records[1].detail_type, "LIST_SHARED");
+
+        let err = handler(get_request(&body), &repository)
+            .await
+            .expect_err("the user should already be a member");
+
+        assert_eq!(err.status_code, 409);
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_role() {
+        let repository = repository_with_list().await;
+        let body = json!({ "user_id": "  ", "role": "owner" }).to_string();
+
+        let err = handler(get_request(&body), &repository)
+            .await
+            .expect_err("fields should be invalid");
+
+        assert_eq!(err.status_code, 422);
+        assert_eq!(
+            err.details,
+            Some(json!({
+                "fields": [
+                    { "field": "role", "reason": "must be viewer or editor" },
+                    { "field": "user_id", "reason": "must not be blank" },
+                ]
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_owner() {
+        let repository = repository_with_list().await;
+        let body =
+            json!({ "user_id": "iam:arn:aws:iam::123456789012:user/titi", "role": "viewer" })
+                .to_string();
+
+        let err = handler(
+            get_request_as(&body, "arn:aws:iam::123456789012:user/tata"),
+            &repository,
+        )
+        .await
+        .expect_err("only the owner should invite");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(repository.outbox_records().len(), 1);
+    }
+}
@@ -0,0 +1,176 @@
+//! Settings of the authorizer, loaded from the environment once per execution environment.
+
+use std::{env, path::PathBuf, time::Duration};
+
+use shared::{ConfigError, ConfigIssue};
+
+use crate::JwksSource;
+
+const DEFAULT_JWKS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// The validated settings of the authorizer.
+#[derive(Debug, Clone)]
+pub struct AuthorizerConfig {
+    jwks_source: JwksSource,
+    jwks_cache_ttl: Duration,
+    issuer: String,
+    audiences: Vec<String>,
+}
+
+impl AuthorizerConfig {
+    /// Either `JWKS_URL` or `JWKS_FILE`.
+    pub fn jwks_source(&self) -> &JwksSource {
+        &self.jwks_source
+    }
+
+    /// How long the keys are used before being reloaded, 5 minutes by default.
+    pub fn jwks_cache_ttl(&self) -> Duration {
+        self.jwks_cache_ttl
+    }
+
+    /// The expected `iss` claim.
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// The accepted `aud` claims, a token must have at least one of them.
+    pub fn audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Load the config from the process environment.
+    pub fn load_env() -> Result<AuthorizerConfig, ConfigError> {
+        AuthorizerConfig::load(|name| env::var(name).ok())
+    }
+
+    /// Load the config from the variables returned by `lookup`, reporting all the issues at once.
+    pub fn load(lookup: impl Fn(&str) -> Option<String>) -> Result<AuthorizerConfig, ConfigError> {
+        let mut issues = Vec::new();
+
+        // an empty variable is as good as a missing one
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+
+        let jwks_source = match (var("JWKS_URL"), var("JWKS_FILE")) {
+            (Some(url), None) => Some(JwksSource::Url(url)),
+            (None, Some(path)) => Some(JwksSource::File(PathBuf::from(path))),
+            (Some(_), Some(_)) => {
+                issues.push(ConfigIssue::Invalid {
+                    name: "JWKS_FILE",
+                    reason: "JWKS_URL is already set".into(),
+                });
+                None
+            }
+            (None, None) => {
+                issues.push(ConfigIssue::Missing { name: "JWKS_URL" });
+                None
+            }
+        };
+
+        let jwks_cache_ttl = match var("JWKS_CACHE_TTL_SECS") {
+            Some(ttl) => match ttl.parse::<u64>() {
+                Ok(ttl) => Duration::from_secs(ttl),
+                Err(_) => {
+                    issues.push(ConfigIssue::Invalid {
+                        name: "JWKS_CACHE_TTL_SECS",
+                        reason: "expected a number of seconds".into(),
+                    });
+                    DEFAULT_JWKS_CACHE_TTL
+                }
+            },
+            None => DEFAULT_JWKS_CACHE_TTL,
+        };
+
+        let issuer = var("JWT_ISSUER");
+        if issuer.is_none() {
+            issues.push(ConfigIssue::Missing { name: "JWT_ISSUER" });
+        }
+
+        let audiences: Vec<String> = var("JWT_AUDIENCE")
+            .map(|audiences| {
+                audiences
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|audience| !audience.is_empty())
+                    .map(Into::into)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if audiences.is_empty() {
+            issues.push(ConfigIssue::Missing {
+                name: "JWT_AUDIENCE",
+            });
+        }
+
+        match (jwks_source, issuer, issues.is_empty()) {
+            (Some(jwks_source), Some(issuer), true) => Ok(AuthorizerConfig {
+                jwks_source,
+                jwks_cache_ttl,
+                issuer,
+                audiences,
+            }),
+            _ => Err(ConfigError { issues }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn load(vars: &[(&str, &str)]) -> Result<AuthorizerConfig, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        AuthorizerConfig::load(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_load() {
+        let config = load(&[
+            ("JWKS_FILE", "/opt/jwks.json"),
+            ("JWT_ISSUER", "https://toto.com"),
+            ("JWT_AUDIENCE", "tata, titi"),
+        ])
+        .unwrap();
+
+        assert!(
+            matches!(config.jwks_source(), JwksSource::File(path) if path.ends_with("jwks.json"))
+        );
+        assert_eq!(config.jwks_cache_ttl(), DEFAULT_JWKS_CACHE_TTL);
+        assert_eq!(config.issuer(), "https://toto.com");
+        assert_eq!(config.audiences(), ["tata", "titi"]);
+    }
+
+    #[test]
+    fn test_load_reports_all_issues() {
+        let err = load(&[
+            ("JWKS_URL", "https://toto.com/.well-known/jwks.json"),
+            ("JWKS_FILE", "/opt/jwks.json"),
+            ("JWKS_CACHE_TTL_SECS", "soon"),
+            ("JWT_AUDIENCE", " , "),
+        ])
+        .unwrap_err();
+
+        assert_eq!(
+            err.issues,
+            vec![
+                ConfigIssue::Invalid {
+                    name: "JWKS_FILE",
+                    reason: "JWKS_URL is already set".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "JWKS_CACHE_TTL_SECS",
+                    reason: "expected a number of seconds".into()
+                },
+                ConfigIssue::Missing { name: "JWT_ISSUER" },
+                ConfigIssue::Missing {
+                    name: "JWT_AUDIENCE"
+                },
+            ]
+        );
+    }
+}
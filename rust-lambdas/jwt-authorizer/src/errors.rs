@@ -0,0 +1,25 @@
+use jsonwebtoken::Algorithm;
+
+#[derive(thiserror::Error, Debug)]
+pub enum JwksError {
+    #[error("unable to read the JWKS file: {0}")]
+    Read(#[from] std::io::Error),
+    #[error("unable to fetch the JWKS: {0}")]
+    Fetch(#[from] reqwest::Error),
+    #[error("invalid JWKS: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TokenError {
+    #[error("malformed token: {0}")]
+    Malformed(jsonwebtoken::errors::Error),
+    #[error("unsupported algorithm {0:?}")]
+    UnsupportedAlgorithm(Algorithm),
+    #[error("unknown key {kid:?}")]
+    UnknownKey { kid: Option<String> },
+    #[error("invalid token: {0}")]
+    Invalid(jsonwebtoken::errors::Error),
+    #[error(transparent)]
+    Jwks(#[from] JwksError),
+}
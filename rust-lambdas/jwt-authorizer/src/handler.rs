@@ -0,0 +1,166 @@
+use aws_lambda_events::apigw::{
+    ApiGatewayV2CustomAuthorizerSimpleResponse, ApiGatewayV2CustomAuthorizerV2Request,
+};
+use lambda_runtime::{
+    tracing::{self, error, info},
+    Error, LambdaEvent,
+};
+use serde_json::{Map, Value};
+
+use crate::{TokenError, TokenValidator};
+
+/// Authorize the requests bearing a valid token, with its claims as context.
+///
+/// The routes read the claims from `requestContext.authorizer.lambda`. API Gateway answers a
+/// 401 without a token and a 403 with an invalid one, a failure to load the keys is a 500.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    event: LambdaEvent<ApiGatewayV2CustomAuthorizerV2Request>,
+    validator: &TokenValidator,
+) -> Result<ApiGatewayV2CustomAuthorizerSimpleResponse<Map<String, Value>>, Error> {
+    let mut response = ApiGatewayV2CustomAuthorizerSimpleResponse::default();
+
+    // the identity source is the `Authorization` header
+    let token = event
+        .payload
+        .identity_source
+        .as_deref()
+        .and_then(<[String]>::first)
+        .map(|authorization| {
+            authorization
+                .strip_prefix("Bearer ")
+                .unwrap_or(authorization)
+        });
+
+    let Some(token) = token else {
+        info!("Missing token");
+
+        return Ok(response);
+    };
+
+    match validator.validate(token).await {
+        Ok(claims) => {
+            info!(sub = claims.sub, "Authorized token");
+
+            response.is_authorized = true;
+            response.context = serde_json::from_value(serde_json::to_value(claims)?)?;
+        }
+        Err(TokenError::Jwks(err)) => {
+            error!(err = ?err, "Unable to load JWKS");
+
+            return Err(err.into());
+        }
+        Err(err) => info!(err = %err, "Unauthorized token"),
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use jsonwebtoken::Algorithm;
+    use lambda_runtime::Context;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        test_keys::{now, token, validator, ISSUER},
+        JwksCache, JwksSource,
+    };
+
+    fn get_event(identity_source: Value) -> LambdaEvent<ApiGatewayV2CustomAuthorizerV2Request> {
+        let event = json!({
+          "version": "2.0",
+          "type": "REQUEST",
+          "routeArn": "arn:aws:execute-api:us-east-1:123456789012:api-id/$default/GET/todos/toto",
+          "identitySource": identity_source,
+          "routeKey": "GET /todos/{listId}",
+          "rawPath": "/todos/toto",
+          "rawQueryString": "",
+          "headers": {},
+          "requestContext": {
+            "accountId": "123456789012",
+            "apiId": "api-id",
+            "domainName": "id.execute-api.us-east-1.amazonaws.com",
+            "domainPrefix": "id",
+            "http": {
+              "method": "GET",
+              "path": "/todos/toto",
+              "protocol": "HTTP/1.1",
+              "sourceIp": "IP",
+              "userAgent": "agent"
+            },
+            "requestId": "id",
+            "routeKey": "GET /todos/{listId}",
+            "stage": "$default",
+            "time": "12/Mar/2020:19:03:58 +0000",
+            "timeEpoch": 1583348638390_i64
+          },
+          "pathParameters": { "listId": "toto" }
+        });
+
+        LambdaEvent::new(serde_json::from_value(event).unwrap(), Context::default())
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let exp = now() + 60;
+        let token = token(
+            Algorithm::RS256,
+            Some("rsa"),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": exp }),
+        );
+
+        let response = handler(get_event(json!([format!("Bearer {token}")])), &validator())
+            .await
+            .unwrap();
+
+        assert!(response.is_authorized);
+        assert_eq!(
+            Value::Object(response.context),
+            json!({ "sub": "toto", "iss": ISSUER, "exp": exp })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_unauthorized() {
+        let expired = token(
+            Algorithm::ES256,
+            Some("ec"),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": now() - 120 }),
+        );
+
+        for identity_source in [json!([format!("Bearer {expired}")]), Value::Null] {
+            let response = handler(get_event(identity_source), &validator())
+                .await
+                .unwrap();
+
+            assert!(!response.is_authorized);
+            assert!(response.context.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_missing_jwks() {
+        let token = token(
+            Algorithm::ES256,
+            Some("ec"),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": now() + 60 }),
+        );
+
+        let validator = TokenValidator::new(
+            JwksCache::new(
+                JwksSource::File("/nonexistent/jwks.json".into()),
+                Duration::from_secs(60),
+            ),
+            ISSUER,
+            vec!["tata".into()],
+        );
+
+        handler(get_event(json!([format!("Bearer {token}")])), &validator)
+            .await
+            .expect_err("keys should not be loaded");
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use jsonwebtoken::jwk::JwkSet;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::JwksError;
+
+/// Where the JSON Web Key Set of the issuer is published.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JwksSource {
+    /// Usually the `jwks_uri` of the OpenID configuration of the issuer.
+    Url(String),
+    /// A file bundled with the lambda, for the issuers without a JWKS endpoint.
+    File(PathBuf),
+}
+
+impl JwksSource {
+    pub async fn load(&self, http_client: &reqwest::Client) -> Result<JwkSet, JwksError> {
+        let body = match self {
+            JwksSource::Url(url) => http_client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec(),
+            JwksSource::File(path) => std::fs::read(path)?,
+        };
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+struct CachedJwks {
+    jwks: Arc<JwkSet>,
+    loaded_at: Instant,
+}
+
+/// The keys of the issuer, loaded on first use and kept across the invocations.
+///
+/// Once older than the TTL, the keys are reloaded, so that the rotated keys are picked up.
+/// When the reload fails, the stale keys are used until the next invocation retries it.
+pub struct JwksCache {
+    source: JwksSource,
+    ttl: Duration,
+    http_client: reqwest::Client,
+    cached: RwLock<Option<CachedJwks>>,
+}
+
+impl JwksCache {
+    pub fn new(source: JwksSource, ttl: Duration) -> Self {
+        JwksCache {
+            source,
+            ttl,
+            http_client: reqwest::Client::new(),
+            cached: RwLock::default(),
+        }
+    }
+
+    pub async fn get(&self) -> Result<Arc<JwkSet>, JwksError> {
+        if let Some(cached) = self.cached.read().await.as_ref() {
+            if cached.loaded_at.elapsed() < self.ttl {
+                return Ok(cached.jwks.clone());
+            }
+        }
+
+        let mut cached = self.cached.write().await;
+
+        match self.source.load(&self.http_client).await {
+            Ok(jwks) => {
+                let jwks = Arc::new(jwks);
+
+                *cached = Some(CachedJwks {
+                    jwks: jwks.clone(),
+                    loaded_at: Instant::now(),
+                });
+
+                Ok(jwks)
+            }
+            Err(err) => match cached.as_ref() {
+                Some(cached) => {
+                    warn!(err = ?err, "Unable to reload JWKS, using the stale keys");
+
+                    Ok(cached.jwks.clone())
+                }
+                None => Err(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    const JWKS: &str = r#"{"keys":[{"kty":"EC","kid":"toto","crv":"P-256","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}]}"#;
+
+    fn jwks_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{name}-{}.json", std::process::id()));
+
+        std::fs::write(&path, content).unwrap();
+
+        path
+    }
+
+    #[tokio::test]
+    async fn test_get_cached() {
+        let path = jwks_file("test_get_cached", JWKS);
+        let cache = JwksCache::new(JwksSource::File(path.clone()), Duration::from_secs(60));
+
+        assert!(cache.get().await.unwrap().find("toto").is_some());
+
+        // not read again until stale
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(cache.get().await.unwrap().find("toto").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_stale() {
+        let path = jwks_file("test_get_stale", JWKS);
+        let cache = JwksCache::new(JwksSource::File(path.clone()), Duration::ZERO);
+
+        cache.get().await.unwrap();
+
+        std::fs::write(&path, JWKS.replace("toto", "tata")).unwrap();
+
+        assert!(cache.get().await.unwrap().find("tata").is_some());
+
+        // the stale keys are better than none
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(cache.get().await.unwrap().find("tata").is_some());
+
+        let cache = JwksCache::new(JwksSource::File(path), Duration::ZERO);
+
+        assert!(matches!(cache.get().await, Err(JwksError::Read(_))));
+    }
+
+    #[tokio::test]
+    async fn test_load_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/.well-known/jwks.json",
+            listener.local_addr().unwrap()
+        );
+
+        // answer a single request, returning its head
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut head = String::new();
+
+            // the head ends with an empty line
+            while !head.ends_with("\r\n\r\n") && reader.read_line(&mut head).unwrap() > 0 {}
+
+            (&stream)
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\n\
+                         Content-Type: application/json\r\n\
+                         Content-Length: {}\r\n\r\n{JWKS}",
+                        JWKS.len()
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+
+            head
+        });
+
+        let jwks = JwksSource::Url(url)
+            .load(&reqwest::Client::new())
+            .await
+            .unwrap();
+
+        assert!(jwks.find("toto").is_some());
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET /.well-known/jwks.json HTTP/1.1"));
+    }
+}
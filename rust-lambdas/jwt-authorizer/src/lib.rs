@@ -0,0 +1,13 @@
+mod config;
+mod errors;
+mod handler;
+mod jwks;
+#[cfg(test)]
+mod test_keys;
+mod token;
+
+pub use config::*;
+pub use errors::*;
+pub use handler::handler;
+pub use jwks::*;
+pub use token::*;
@@ -0,0 +1,18 @@
+use lambda_runtime::{service_fn, tracing, Error};
+
+use jwt_authorizer::{handler, AuthorizerConfig, TokenValidator};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    tracing::init_default_subscriber();
+
+    let config = AuthorizerConfig::load_env()?;
+
+    // the keys are cached across the invocations of the execution environment
+    let validator = TokenValidator::from_config(&config);
+
+    let func = service_fn(|event| handler(event, &validator));
+    lambda_runtime::run(func).await?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Keys generated for the tests, published in a temporary JWKS file.
+
+use std::{
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts, RsaPrivateKey};
+use serde_json::{json, Value};
+
+use crate::{JwksCache, JwksSource, TokenValidator};
+
+pub const ISSUER: &str = "https://toto.com";
+
+struct Keys {
+    rsa: EncodingKey,
+    ec: EncodingKey,
+    jwks_path: PathBuf,
+}
+
+/// Generated once, an RSA key takes a few seconds in debug builds.
+fn keys() -> &'static Keys {
+    static KEYS: OnceLock<Keys> = OnceLock::new();
+
+    KEYS.get_or_init(|| {
+        let rsa_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+
+        let rng = SystemRandom::new();
+        let ec_pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let ec_key =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, ec_pkcs8.as_ref(), &rng)
+                .unwrap();
+
+        // an uncompressed point, 0x04 then the coordinates
+        let (x, y) = ec_key.public_key().as_ref()[1..].split_at(32);
+
+        let encode = |bytes: &[u8]| BASE64_URL_SAFE_NO_PAD.encode(bytes);
+
+        let jwks = json!({
+                "keys": [
+                    {
+                        "kty": "RSA",
+                        "kid": "rsa",
+                        "alg": "RS256",
+                        "use": "sig",
+                        "n": encode(&rsa_key.n().to_bytes_be()),
+                        "e": encode(&rsa_key.e().to_bytes_be()),
+                    },
+                    {
+                        "kty": "EC",
+                        "kid": "ec",
+                        "alg": "ES256",
+                        "use": "sig",
+                        "crv": "P-256",
+                        "x": encode(x),
+                        "y": encode(y),
+                    },
+                ]
+        });
+
+        let jwks_path = std::env::temp_dir().join(format!("jwks-{}.json", std::process::id()));
+        std::fs::write(&jwks_path, jwks.to_string()).unwrap();
+
+        Keys {
+            rsa: EncodingKey::from_rsa_der(rsa_key.to_pkcs1_der().unwrap().as_bytes()),
+            ec: EncodingKey::from_ec_der(ec_pkcs8.as_ref()),
+            jwks_path,
+        }
+    })
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// A token signed with the generated key of the algorithm, HS256 tokens with a shared secret.
+pub fn token(alg: Algorithm, kid: Option<&str>, claims: Value) -> String {
+    let keys = keys();
+    let hmac_key = EncodingKey::from_secret(b"secret");
+
+    let key = match alg {
+        Algorithm::RS256 => &keys.rsa,
+        Algorithm::ES256 => &keys.ec,
+        _ => &hmac_key,
+    };
+
+    let mut header = Header::new(alg);
+    header.kid = kid.map(Into::into);
+
+    encode(&header, &claims, key).unwrap()
+}
+
+/// A validator of the tokens issued by [`ISSUER`] for the `tata` audience.
+pub fn validator() -> TokenValidator {
+    TokenValidator::new(
+        JwksCache::new(
+            JwksSource::File(keys().jwks_path.clone()),
+            Duration::from_secs(60),
+        ),
+        ISSUER,
+        vec!["tata".into()],
+    )
+}
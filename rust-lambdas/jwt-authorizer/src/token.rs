@@ -0,0 +1,165 @@
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::{AuthorizerConfig, JwksCache, TokenError};
+
+/// Only asymmetric algorithms, the issuer keeps the signing keys.
+const ALGORITHMS: [Algorithm; 2] = [Algorithm::RS256, Algorithm::ES256];
+
+/// The claims of a valid token, passed to the routes as the authorizer context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    /// The caller, see `shared::auth::Caller`.
+    pub sub: String,
+    pub iss: String,
+    pub exp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Checks the signature, issuer, audience and expiry of the tokens.
+pub struct TokenValidator {
+    jwks: JwksCache,
+    issuer: String,
+    audiences: Vec<String>,
+}
+
+impl TokenValidator {
+    pub fn new(jwks: JwksCache, issuer: impl Into<String>, audiences: Vec<String>) -> Self {
+        TokenValidator {
+            jwks,
+            issuer: issuer.into(),
+            audiences,
+        }
+    }
+
+    pub fn from_config(config: &AuthorizerConfig) -> Self {
+        TokenValidator::new(
+            JwksCache::new(config.jwks_source().clone(), config.jwks_cache_ttl()),
+            config.issuer(),
+            config.audiences().to_vec(),
+        )
+    }
+
+    pub async fn validate(&self, token: &str) -> Result<Claims, TokenError> {
+        let header = decode_header(token).map_err(TokenError::Malformed)?;
+
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(TokenError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let jwks = self.jwks.get().await?;
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            // without a key id, only a single key is unambiguous
+            None => match jwks.keys.as_slice() {
+                [jwk] => Some(jwk),
+                _ => None,
+            },
+        }
+        .ok_or(TokenError::UnknownKey {
+            kid: header.kid.clone(),
+        })?;
+
+        let key = DecodingKey::from_jwk(jwk).map_err(TokenError::Invalid)?;
+
+        // the expiry is checked by default, with a minute of leeway
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&self.audiences);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<Claims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(TokenError::Invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_keys::{now, token, validator, ISSUER};
+
+    #[tokio::test]
+    async fn test_validate() {
+        let validator = validator();
+        let exp = now() + 60;
+
+        for (alg, kid) in [(Algorithm::RS256, "rsa"), (Algorithm::ES256, "ec")] {
+            let token = token(
+                alg,
+                Some(kid),
+                json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": exp, "scope": "todos" }),
+            );
+
+            assert_eq!(
+                validator.validate(&token).await.unwrap(),
+                Claims {
+                    sub: "toto".into(),
+                    iss: ISSUER.into(),
+                    exp,
+                    scope: Some("todos".into()),
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_claims() {
+        let validator = validator();
+
+        for claims in [
+            json!({ "sub": "toto", "iss": "https://tata.com", "aud": "tata", "exp": now() + 60 }),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "titi", "exp": now() + 60 }),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": now() - 120 }),
+            json!({ "sub": "toto", "iss": ISSUER, "aud": "tata" }),
+        ] {
+            let token = token(Algorithm::ES256, Some("ec"), claims);
+
+            assert!(matches!(
+                validator.validate(&token).await,
+                Err(TokenError::Invalid(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_key() {
+        let validator = validator();
+        let claims = json!({ "sub": "toto", "iss": ISSUER, "aud": "tata", "exp": now() + 60 });
+
+        // signed with the EC key, verified with the RSA one
+        let signed = token(Algorithm::ES256, Some("rsa"), claims.clone());
+        assert!(matches!(
+            validator.validate(&signed).await,
+            Err(TokenError::Invalid(_))
+        ));
+
+        let signed = token(Algorithm::ES256, Some("titi"), claims.clone());
+        assert!(matches!(
+            validator.validate(&signed).await,
+            Err(TokenError::UnknownKey { kid: Some(_) })
+        ));
+
+        // ambiguous with two keys in the set
+        let signed = token(Algorithm::ES256, None, claims.clone());
+        assert!(matches!(
+            validator.validate(&signed).await,
+            Err(TokenError::UnknownKey { kid: None })
+        ));
+
+        let signed = token(Algorithm::HS256, Some("rsa"), claims);
+        assert!(matches!(
+            validator.validate(&signed).await,
+            Err(TokenError::UnsupportedAlgorithm(Algorithm::HS256))
+        ));
+
+        assert!(matches!(
+            validator.validate("toto").await,
+            Err(TokenError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::Instant;
+
+use lambda_http::{
+    http::StatusCode,
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use shared::{
+    auth::Caller,
+    config::ListLimits,
+    decode_cursor, encode_cursor,
+    keys::{owner_lists_partition_key, OwnerListKey, TableKey},
+    ErrorCode, FailureResponse, Page, TodoRepository,
+};
+
+/// The lists owned by the caller, the lists shared with them are not included.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+    cursor_signing_key: Option<&[u8]>,
+    list_limits: ListLimits,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let owner = Caller::from_request(&request)?.id();
+
+    let query_string_parameters = request.query_string_parameters();
+
+    let limit = query_string_parameters
+        .first("limit")
+        .map(|limit| {
+            limit
+                .parse::<i32>()
+                .ok()
+                .filter(|limit| (1..=list_limits.max).contains(limit))
+                .ok_or(FailureResponse::new(
+                    ErrorCode::InvalidParameter,
+                    format!(
+                        "Invalid limit, expected a number between 1 and {}",
+                        list_limits.max
+                    ),
+                ))
+        })
+        .transpose()?
+        .unwrap_or(list_limits.default);
+
+    owner_lists_partition_key(&owner)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let exclusive_start_key = query_string_parameters
+        .first("cursor")
+        .map(|cursor| {
+            decode_cursor(cursor, cursor_signing_key)
+                .ok()
+                // a cursor is only valid for the owner it was issued for
+                .filter(|key| OwnerListKey::from_key(key).is_ok_and(|key| key.owner == owner))
+                .ok_or(FailureResponse::new(
+                    ErrorCode::InvalidCursor,
+                    "Invalid cursor",
+                ))
+        })
+        .transpose()?;
+
+    let start = Instant::now();
+
+    let page = repository
+        .owner_lists(&owner, limit, exclusive_start_key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to query table");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to list lists")
+        })?;
+
+    info!(lists_count = page.lists.len(), "Retrieved lists");
+
+    let next_cursor = page
+        .last_evaluated_key
+        .map(|key| encode_cursor(&key, cursor_signing_key))
+        .transpose()
+        .map_err(|err| {
+            error!(err = ?err, "Unable to encode cursor");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to encode cursor")
+        })?;
+
+    debug!("Items retrieved in {:.2?}", start.elapsed());
+
+    let page = serde_json::to_value(Page {
+        items: page.lists,
+        next_cursor,
+    })
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize lists");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize lists")
+    })?;
+
+    Ok((StatusCode::OK, page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        InMemoryTodoRepository, List,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+
+    fn get_request_as(raw_query_string: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("GET")
+            .user_arn(user_arn)
+            .query(raw_query_string)
+            .build()
+    }
+
+    async fn create_list(repository: &InMemoryTodoRepository, list_id: &str, owner: &str) {
+        repository.seed_list(&list(list_id, &iam_id(owner))).await;
+    }
+
+    #[tokio::test]
+    async fn test_handler_paginates() {
+        let repository = InMemoryTodoRepository::new();
+
+        for list_id in ["tata", "titi", "tutu"] {
+            create_list(&repository, list_id, OWNER).await;
+        }
+        create_list(&repository, "toto", "arn:aws:iam::123456789012:user/tata").await;
+
+        let (status, res) = handler(
+            get_request_as("limit=2", OWNER),
+            &repository,
+            Some(b"secret"),
+            ListLimits::default(),
+        )
+        .await
+        .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let page: Page<List> = serde_json::from_value(res).unwrap();
+
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items[0].id, "tata");
+
+        let next_cursor = page.next_cursor.expect("missing next cursor");
+
+        let (_, res) = handler(
+            get_request_as(&format!("limit=2&cursor={next_cursor}"), OWNER),
+            &repository,
+            Some(b"secret"),
+            ListLimits::default(),
+        )
+        .await
+        .expect("failed to handle event");
+
+        let page: Page<List> = serde_json::from_value(res).unwrap();
+
+        // the list of the other owner is not included
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, "tutu");
+        assert_eq!(page.next_cursor, None);
+
+        // the cursor was issued for another owner
+        let err = handler(
+            get_request_as(
+                &format!("cursor={next_cursor}"),
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+            Some(b"secret"),
+            ListLimits::default(),
+        )
+        .await
+        .expect_err("cursor should be rejected");
+
+        assert_eq!(err.status_code, 400);
+    }
+}
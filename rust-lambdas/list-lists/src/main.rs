@@ -0,0 +1,25 @@
+use lambda_http::Error;
+
+use list_lists::handler;
+use shared::{http::HttpLambda, DynamoTodoRepository};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    HttpLambda::new()
+        .dynamodb()
+        .run(|request, resources| async move {
+            let repository = DynamoTodoRepository::new(
+                resources.dynamodb_client(),
+                resources.config().todos_table_name(),
+            );
+
+            handler(
+                request,
+                &repository,
+                resources.config().cursor_signing_key(),
+                resources.config().list_limits(),
+            )
+            .await
+        })
+        .await
+}
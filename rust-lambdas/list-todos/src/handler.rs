@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::{collections::HashMap, time::Instant};
 
 use aws_sdk_dynamodb::types::AttributeValue;
 use lambda_http::{
@@ -7,64 +7,302 @@ use lambda_http::{
     Request, RequestExt,
 };
 
-use shared::{FailureResponse, Todo};
+use shared::{
+    auth::Caller,
+    config::ListLimits,
+    decode_cursor, encode_cursor,
+    keys::{todo_list_partition_key, ListCounterKey, TableKey, TodoKey},
+    ErrorCode, FailureResponse, Page, Role, TodoRepository,
+};
 
 #[tracing::instrument(skip_all)]
-pub(crate) async fn handler(
+pub async fn handler(
     request: Request,
-    dynamodb_client: &aws_sdk_dynamodb::Client,
-    todos_table_name: &str,
+    repository: &impl TodoRepository,
+    cursor_signing_key: Option<&[u8]>,
+    list_limits: ListLimits,
 ) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
     let path_parameters = request.path_parameters();
 
-    let list_id = path_parameters.first("listId").ok_or(FailureResponse {
-        status_code: StatusCode::BAD_REQUEST,
-        body: "Missing list id".into(),
-    })?;
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let query_string_parameters = request.query_string_parameters();
+
+    let limit = query_string_parameters
+        .first("limit")
+        .map(|limit| {
+            limit
+                .parse::<i32>()
+                .ok()
+                .filter(|limit| (1..=list_limits.max).contains(limit))
+                .ok_or(FailureResponse::new(
+                    ErrorCode::InvalidParameter,
+                    format!(
+                        "Invalid limit, expected a number between 1 and {}",
+                        list_limits.max
+                    ),
+                ))
+        })
+        .transpose()?
+        .unwrap_or(list_limits.default);
+
+    todo_list_partition_key(list_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let exclusive_start_key = query_string_parameters
+        .first("cursor")
+        .map(|cursor| {
+            decode_cursor(cursor, cursor_signing_key)
+                .ok()
+                // a cursor is only valid for the list it was issued for
+                .filter(|key| key_list_id(key).as_deref() == Some(list_id))
+                .ok_or(FailureResponse::new(
+                    ErrorCode::InvalidCursor,
+                    "Invalid cursor",
+                ))
+        })
+        .transpose()?;
 
     let start = Instant::now();
 
-    let result = dynamodb_client
-        .query()
-        .table_name(todos_table_name)
-        .key_condition_expression("PK = :PK AND begins_with(SK, :SK)")
-        .expression_attribute_values(":PK", AttributeValue::S(format!("TODO#{list_id}")))
-        .expression_attribute_values(":SK", AttributeValue::S("ID#".into()))
-        .send()
+    caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to list todos")
+        })?
+        .require(Role::Viewer)?;
+
+    let page = repository
+        .list(list_id, limit, exclusive_start_key)
         .await
         .map_err(|err| {
             error!(err = ?err, "Unable to query table");
 
-            FailureResponse {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: "Unable to set todo".into(),
-            }
+            FailureResponse::new(ErrorCode::InternalError, "Unable to list todos")
         })?;
 
     info!(list_id = list_id, "Retrieved list");
 
-    debug!("{result:?}");
+    let next_cursor = page
+        .last_evaluated_key
+        .map(|key| encode_cursor(&key, cursor_signing_key))
+        .transpose()
+        .map_err(|err| {
+            error!(err = ?err, "Unable to encode cursor");
 
-    let todos: Vec<Todo> = result
-        .items
-        .ok_or(FailureResponse {
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            body: "Got no items from DynamoDB".into(),
-        })?
-        .into_iter()
-        .flat_map(Todo::try_from)
-        .collect();
+            FailureResponse::new(ErrorCode::InternalError, "Unable to encode cursor")
+        })?;
 
     debug!("Item retrieved in {:.2?}", start.elapsed());
 
-    let todos = serde_json::to_value(todos).map_err(|err| {
+    let page = serde_json::to_value(Page {
+        items: page.todos,
+        next_cursor,
+    })
+    .map_err(|err| {
         error!(err = ?err, "Unable to serialize todo");
 
-        FailureResponse {
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            body: "Unable to serialize todo".into(),
-        }
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
     })?;
 
-    Ok((StatusCode::OK, todos))
+    Ok((StatusCode::OK, page))
+}
+
+/// The list a `LastEvaluatedKey` belongs to. A page may end on the list counter.
+fn key_list_id(key: &HashMap<String, AttributeValue>) -> Option<String> {
+    TodoKey::from_key(key)
+        .map(|key| key.list_id)
+        .or_else(|_| ListCounterKey::from_key(key).map(|key| key.list_id))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, member, todo, ApiGatewayRequest},
+        InMemoryTodoRepository, Todo,
+    };
+
+    fn get_request(raw_query_string: &str) -> Request {
+        get_request_as(raw_query_string, "arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(raw_query_string: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("GET")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .query(raw_query_string)
+            .build()
+    }
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        repository
+    }
+
+    async fn create_todo(repository: &InMemoryTodoRepository, todo_id: &str) {
+        repository
+            .seed_todo(&Todo {
+                owner: Some(iam_id("arn:aws:iam::123456789012:user/toto")),
+                ..todo("toto", todo_id)
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_handler_paginates() {
+        let repository = repository_with_list().await;
+
+        for todo_id in ["tata", "titi", "tutu"] {
+            create_todo(&repository, todo_id).await;
+        }
+
+        let (status, res) = handler(
+            get_request("limit=2"),
+            &repository,
+            Some(b"secret"),
+            ListLimits::default(),
+        )
+        .await
+        .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let page: Page<Todo> = serde_json::from_value(res).unwrap();
+
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items[0].id, "tata");
+
+        let next_cursor = page.next_cursor.expect("missing next cursor");
+
+        let (status, res) = handler(
+            get_request(&format!("limit=2&cursor={next_cursor}")),
+            &repository,
+            Some(b"secret"),
+            ListLimits::default(),
+        )
+        .await
+        .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let page: Page<Todo> = serde_json::from_value(res).unwrap();
+
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, "tutu");
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_list().await;
+
+        create_todo(&repository, "tata").await;
+
+        let err = handler(
+            get_request_as("", "arn:aws:iam::123456789012:user/tata"),
+            &repository,
+            None,
+            ListLimits::default(),
+        )
+        .await
+        .expect_err("list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_list().await;
+
+        create_todo(&repository, "tata").await;
+
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let (status, res) = handler(
+            get_request_as("", "arn:aws:iam::123456789012:user/tata"),
+            &repository,
+            None,
+            ListLimits::default(),
+        )
+        .await
+        .expect("a viewer should list todos");
+
+        assert_eq!(status, 200);
+
+        let page: Page<Todo> = serde_json::from_value(res).unwrap();
+
+        assert_eq!(page.items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_cursor() {
+        let repository = InMemoryTodoRepository::new();
+
+        // cursor issued for another list
+        let cursor = encode_cursor(
+            &HashMap::from([
+                ("PK".into(), AttributeValue::S("TODO#other".into())),
+                ("SK".into(), AttributeValue::S("ID#tata".into())),
+            ]),
+            Some(b"secret"),
+        )
+        .unwrap();
+
+        for cursor in [cursor.as_str(), "tampered"] {
+            let err = handler(
+                get_request(&format!("cursor={cursor}")),
+                &repository,
+                Some(b"secret"),
+                ListLimits::default(),
+            )
+            .await
+            .expect_err("cursor should be rejected");
+
+            assert_eq!(err.status_code, 400);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_limit() {
+        let repository = InMemoryTodoRepository::new();
+
+        for limit in ["0", "11", "ten"] {
+            let err = handler(
+                get_request(&format!("limit={limit}")),
+                &repository,
+                None,
+                ListLimits {
+                    default: 5,
+                    max: 10,
+                },
+            )
+            .await
+            .expect_err("limit should be rejected");
+
+            assert_eq!(err.status_code, 400);
+        }
+    }
 }
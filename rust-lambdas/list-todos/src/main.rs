@@ -1,36 +1,25 @@
-mod handler;
+use lambda_http::Error;
 
-use std::env;
-use std::time::Instant;
-
-use lambda_http::{
-    service_fn,
-    tower::ServiceExt,
-    tracing::{self, debug},
-    Error,
-};
-
-use handler::handler;
-use shared::get_dynamodb_client;
+use list_todos::handler;
+use shared::{http::HttpLambda, DynamoTodoRepository};
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let start = Instant::now();
-
-    tracing::init_default_subscriber();
-
-    let dynamodb_client = get_dynamodb_client().await;
-
-    let todos_table_name = env::var("TODOS_TABLE_NAME").expect("Missing TODOS_TABLE_NAME env var");
-
-    debug!("DynamoDB client initialized in {:.2?}", start.elapsed());
-
-    let func = service_fn(|request| handler(request, &dynamodb_client, &todos_table_name))
-        .map_result::<_, _, Error>(|res| match res {
-            Ok(res) => Ok(res),
-            Err(err) => Ok((err.status_code, err.body.into())),
-        });
-    lambda_http::run(func).await?;
-
-    Ok(())
+    HttpLambda::new()
+        .dynamodb()
+        .run(|request, resources| async move {
+            let repository = DynamoTodoRepository::new(
+                resources.dynamodb_client(),
+                resources.config().todos_table_name(),
+            );
+
+            handler(
+                request,
+                &repository,
+                resources.config().cursor_signing_key(),
+                resources.config().list_limits(),
+            )
+            .await
+        })
+        .await
 }
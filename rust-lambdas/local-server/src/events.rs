@@ -0,0 +1,52 @@
+use std::future::Future;
+
+use aws_lambda_events::eventbridge::EventBridgeEvent;
+use in_memory_aws::{EventPattern, InMemoryEventBus};
+use lambda_runtime::{Error, LambdaEvent};
+use shared::{EventEnvelope, TodoRepository, EVENT_SOURCE};
+
+/// Subscribe the consumers to the bus, like the rules of the stack.
+pub(crate) fn subscribe_consumers<R>(bus: &InMemoryEventBus, repository: &R)
+where
+    R: TodoRepository + Clone + Send + 'static,
+{
+    subscribe(
+        bus,
+        &["TODO_CREATED"],
+        repository,
+        |event, repository| async move { on_todo_created::handler(event, &repository).await },
+    );
+    subscribe(
+        bus,
+        &["TODO_DELETED"],
+        repository,
+        |event, repository| async move { on_todo_deleted::handler(event, &repository).await },
+    );
+    subscribe(
+        bus,
+        &["TODO_COMPLETED", "TODO_REOPENED"],
+        repository,
+        |event, repository| async move { on_todo_completion_changed::handler(event, &repository).await },
+    );
+    subscribe(
+        bus,
+        &["LIST_DELETION_REQUESTED"],
+        repository,
+        |event, repository| async move { on_list_deletion_requested::handler(event, &repository).await },
+    );
+}
+
+/// Deliver the events of the detail types to a consumer, with its own copy of the repository.
+fn subscribe<R, F, Fut>(bus: &InMemoryEventBus, detail_types: &[&str], repository: &R, handler: F)
+where
+    R: TodoRepository + Clone + Send + 'static,
+    F: Fn(LambdaEvent<EventBridgeEvent<EventEnvelope>>, R) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    let repository = repository.clone();
+
+    bus.subscribe(
+        EventPattern::new(EVENT_SOURCE, detail_types),
+        move |event| handler(event, repository.clone()),
+    );
+}
@@ -0,0 +1,614 @@
+//! Serve the HTTP lambdas locally, against DynamoDB Local.
+//!
+//! The events are dispatched to the consumers in-process instead of EventBridge.
+
+mod events;
+mod outbox;
+mod request;
+
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::{
+    config::{Credentials, Region},
+    types::{AttributeDefinition, BillingMode, KeySchemaElement, KeyType, ScalarAttributeType},
+};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Response, StatusCode},
+    Router,
+};
+use in_memory_aws::InMemoryEventBus;
+use lambda_http::{
+    tracing::{error, info},
+    Error, Request,
+};
+use shared::{
+    config::{init_subscriber, Config, ConfigLoader, ListLimits},
+    respond, ClientBuilder, DynamoTodoRepository, ErrorCode, FailureResponse, TodoRepository,
+};
+use todos_api::{router, Api};
+use tokio::net::TcpListener;
+
+use events::subscribe_consumers;
+use outbox::{Outbox, TableOutbox};
+use request::{to_axum_response, to_lambda_request};
+
+const EVENT_BUS_NAME: &str = "local";
+
+struct LocalServer<R, O> {
+    repository: R,
+    outbox: O,
+    eventbridge_client: aws_sdk_eventbridge::Client,
+    cursor_signing_key: Option<&'static [u8]>,
+    list_limits: ListLimits,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // the repository and the consumers borrow the config and the client until the process exits
+    let config: &'static Config =
+        Box::leak(Box::new(ConfigLoader::new().local_server().load_env()?));
+
+    init_subscriber(config);
+
+    let dynamodb_endpoint_url = config
+        .dynamodb_endpoint_url()
+        .expect("DYNAMODB_ENDPOINT_URL not declared");
+
+    // DynamoDB Local accepts any credentials
+    let dynamodb_client = ClientBuilder::from_config(config)
+        .endpoint_url(dynamodb_endpoint_url)
+        .region(Region::new("local"))
+        .credentials_provider(Credentials::new("local", "local", None, None, "local"))
+        .dynamodb()
+        .await;
+
+    let dynamodb_client: &'static aws_sdk_dynamodb::Client = Box::leak(Box::new(dynamodb_client));
+    let todos_table_name = config.todos_table_name();
+
+    create_table_if_missing(dynamodb_client, todos_table_name).await?;
+
+    let repository = DynamoTodoRepository::new(dynamodb_client, todos_table_name);
+    let event_bus = InMemoryEventBus::new();
+
+    subscribe_consumers(&event_bus, &repository);
+    tokio::spawn(event_bus.clone().run());
+
+    let state = Arc::new(LocalServer {
+        repository,
+        outbox: TableOutbox {
+            dynamodb_client,
+            todos_table_name,
+        },
+        eventbridge_client: event_bus.client(),
+        cursor_signing_key: config.cursor_signing_key(),
+        list_limits: config.list_limits(),
+    });
+
+    let app = Router::new().fallback(serve).with_state(state);
+
+    let listener = TcpListener::bind(("127.0.0.1", config.port())).await?;
+
+    info!(
+        port = config.port(),
+        dynamodb_endpoint_url = dynamodb_endpoint_url,
+        "Local server listening",
+    );
+
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn serve<R, O>(
+    State(server): State<Arc<LocalServer<R, O>>>,
+    request: axum::extract::Request,
+) -> Response<Body>
+where
+    R: TodoRepository + Clone,
+    O: Outbox,
+{
+    let request = match to_lambda_request(request).await {
+        Ok(request) => request,
+        Err(err) => {
+            error!(err = ?err, "Unable to read request");
+
+            return failure_response(FailureResponse::new(
+                ErrorCode::InvalidRequest,
+                "Invalid request",
+            ))
+            .await;
+        }
+    };
+
+    match server.handle(request).await {
+        Ok(response) => to_axum_response(response).await,
+        Err(err) => {
+            error!(err = ?err, "Unable to handle request");
+
+            failure_response(FailureResponse::new(
+                ErrorCode::InternalError,
+                "Unable to handle request",
+            ))
+            .await
+        }
+    }
+}
+
+/// Answer with the error body of the lambdas, for the failures outside of their handlers.
+async fn failure_response(failure: FailureResponse) -> Response<Body> {
+    let response = respond(Request::default(), |_| async { Err(failure) })
+        .await
+        // the failures of the handler are always turned into a response
+        .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, serde_json::Value::Null));
+
+    to_axum_response(response).await
+}
+
+impl<R, O> LocalServer<R, O>
+where
+    R: TodoRepository + Clone,
+    O: Outbox,
+{
+    async fn handle(&self, request: Request) -> Result<(StatusCode, serde_json::Value), Error> {
+        let api = Api {
+            repository: self.repository.clone(),
+            cursor_signing_key: self.cursor_signing_key,
+            list_limits: self.list_limits,
+        };
+
+        let response = respond(request, |request| router(request, api)).await?;
+
+        // the lambdas rely on the table stream, which is not available locally
+        if let Err(err) = self
+            .outbox
+            .relay_pending(&self.eventbridge_client, EVENT_BUS_NAME)
+            .await
+        {
+            error!(err = ?err, "Unable to relay outbox records");
+        }
+
+        Ok(response)
+    }
+}
+
+/// Create the table of the stack, so that a fresh DynamoDB Local works out of the box.
+async fn create_table_if_missing(
+    dynamodb_client: &aws_sdk_dynamodb::Client,
+    todos_table_name: &str,
+) -> Result<(), Error> {
+    let res = dynamodb_client
+        .describe_table()
+        .table_name(todos_table_name)
+        .send()
+        .await;
+
+    match res {
+        Ok(_) => return Ok(()),
+        Err(err)
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_resource_not_found_exception()) => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    let key_attributes = [("PK", KeyType::Hash), ("SK", KeyType::Range)];
+
+    let mut create_table = dynamodb_client
+        .create_table()
+        .table_name(todos_table_name)
+        .billing_mode(BillingMode::PayPerRequest);
+
+    for (name, key_type) in key_attributes {
+        create_table = create_table
+            .attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name(name)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build()?,
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(name)
+                    .key_type(key_type)
+                    .build()?,
+            );
+    }
+
+    create_table.send().await?;
+
+    info!(table_name = todos_table_name, "Created table");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use shared::{publish, InMemoryTodoRepository};
+    use std::sync::Mutex;
+
+    type TestServer = LocalServer<InMemoryTodoRepository, InMemoryOutbox>;
+
+    /// The outbox records of the repository, relayed in the order they were stored.
+    struct InMemoryOutbox {
+        repository: InMemoryTodoRepository,
+        relayed: Mutex<usize>,
+    }
+
+    impl Outbox for InMemoryOutbox {
+        async fn relay_pending(
+            &self,
+            eventbridge_client: &aws_sdk_eventbridge::Client,
+            event_bus_name: &str,
+        ) -> Result<(), Error> {
+            let relayed = *self.relayed.lock().unwrap();
+
+            for outbox_record in &self.repository.outbox_records()[relayed..] {
+                publish(
+                    eventbridge_client,
+                    event_bus_name,
+                    &outbox_record.envelope()?,
+                )
+                .await?;
+
+                *self.relayed.lock().unwrap() += 1;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn local_server(
+        repository: &InMemoryTodoRepository,
+        event_bus: &InMemoryEventBus,
+    ) -> TestServer {
+        subscribe_consumers(event_bus, repository);
+
+        LocalServer {
+            repository: repository.clone(),
+            outbox: InMemoryOutbox {
+                repository: repository.clone(),
+                relayed: Mutex::new(0),
+            },
+            eventbridge_client: event_bus.client(),
+            cursor_signing_key: None,
+            list_limits: ListLimits::default(),
+        }
+    }
+
+    async fn send(
+        server: &TestServer,
+        method: &str,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        send_as(server, request::LOCAL_USER_ARN, method, uri, body).await
+    }
+
+    async fn send_as(
+        server: &TestServer,
+        user_arn: &str,
+        method: &str,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        let request = axum::extract::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(request::LOCAL_USER_HEADER, user_arn)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        server
+            .handle(to_lambda_request(request).await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    /// Create a list as the local user, returning its id.
+    async fn create_list(server: &TestServer, event_bus: &InMemoryEventBus) -> String {
+        let (status_code, list) = send(server, "POST", "/lists", "{\"name\": \"Toto list\"}").await;
+
+        assert_eq!(status_code, StatusCode::CREATED);
+        assert_eq!(event_bus.dispatch_pending().await.unwrap(), 1);
+
+        list["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_delete_todos() {
+        let repository = InMemoryTodoRepository::new();
+        let event_bus = InMemoryEventBus::new();
+        let server = local_server(&repository, &event_bus);
+
+        let list_id = create_list(&server, &event_bus).await;
+
+        let mut todo_ids = Vec::new();
+
+        for title in ["Toto todo", "Tata todo"] {
+            let body = json!({ "title": title, "description": "" }).to_string();
+            let (status_code, todo) =
+                send(&server, "POST", &format!("/todos/{list_id}"), &body).await;
+
+            assert_eq!(status_code, StatusCode::CREATED);
+            assert_eq!(event_bus.dispatch_pending().await.unwrap(), 1);
+
+            todo_ids.push(todo["id"].as_str().unwrap().to_string());
+        }
+
+        let (status_code, _) = send(
+            &server,
+            "DELETE",
+            &format!("/todos/{list_id}/{}", todo_ids[0]),
+            "",
+        )
+        .await;
+
+        assert_eq!(status_code, StatusCode::NO_CONTENT);
+        assert_eq!(event_bus.dispatch_pending().await.unwrap(), 1);
+
+        let (status_code, list) = send(&server, "GET", &format!("/lists/{list_id}"), "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(list["todos_count"], 1);
+        assert_eq!(list["completed_count"], 0);
+
+        let counter = repository.get_counter(&list_id).await.unwrap();
+
+        assert_eq!(counter.todos_count, 1);
+        assert_eq!(counter.completed_count, 0);
+
+        let detail_types: Vec<_> = event_bus
+            .published()
+            .iter()
+            .map(|entry| entry.detail_type().unwrap_or_default().to_string())
+            .collect();
+
+        assert_eq!(
+            detail_types,
+            [
+                "LIST_CREATED",
+                "TODO_CREATED",
+                "TODO_CREATED",
+                "TODO_DELETED"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_share_list() {
+        let repository = InMemoryTodoRepository::new();
+        let event_bus = InMemoryEventBus::new();
+        let server = local_server(&repository, &event_bus);
+
+        let member = "arn:aws:iam::000000000000:user/tata";
+        let member_id = format!("iam:{member}");
+        let body = json!({ "title": "Toto todo", "description": "" }).to_string();
+
+        let list_id = create_list(&server, &event_bus).await;
+        let todos_uri = format!("/todos/{list_id}");
+        let members_uri = format!("/lists/{list_id}/members");
+
+        let (status_code, _) = send(&server, "POST", &todos_uri, &body).await;
+
+        assert_eq!(status_code, StatusCode::CREATED);
+
+        let (status_code, _) = send_as(&server, member, "GET", &todos_uri, "").await;
+
+        assert_eq!(status_code, StatusCode::FORBIDDEN);
+
+        let invitation = json!({ "user_id": member_id, "role": "viewer" }).to_string();
+        let (status_code, _) = send(&server, "POST", &members_uri, &invitation).await;
+
+        assert_eq!(status_code, StatusCode::CREATED);
+
+        let (status_code, page) = send_as(&server, member, "GET", &todos_uri, "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+
+        let (status_code, _) = send_as(&server, member, "POST", &todos_uri, &body).await;
+
+        assert_eq!(status_code, StatusCode::FORBIDDEN);
+
+        let uri = format!("{members_uri}?userId={member_id}");
+        let (status_code, _) = send(&server, "PATCH", &uri, "{\"role\": \"editor\"}").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+
+        let (status_code, _) = send_as(&server, member, "POST", &todos_uri, &body).await;
+
+        assert_eq!(status_code, StatusCode::CREATED);
+
+        // the member leaves the list
+        let (status_code, _) = send_as(&server, member, "DELETE", &uri, "").await;
+
+        assert_eq!(status_code, StatusCode::NO_CONTENT);
+
+        let (status_code, _) = send_as(&server, member, "GET", &todos_uri, "").await;
+
+        assert_eq!(status_code, StatusCode::FORBIDDEN);
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let detail_types: Vec<_> = event_bus
+            .published()
+            .iter()
+            .map(|entry| entry.detail_type().unwrap_or_default().to_string())
+            .collect();
+
+        assert_eq!(
+            detail_types,
+            [
+                "LIST_CREATED",
+                "TODO_CREATED",
+                "LIST_SHARED",
+                "LIST_SHARED",
+                "TODO_CREATED",
+                "MEMBER_REMOVED"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_lifecycle() {
+        let repository = InMemoryTodoRepository::new();
+        let event_bus = InMemoryEventBus::new();
+        let server = local_server(&repository, &event_bus);
+
+        let body = json!({ "title": "Toto todo", "description": "" }).to_string();
+        let (status_code, _) = send(&server, "POST", "/todos/toto", &body).await;
+
+        assert_eq!(status_code, StatusCode::NOT_FOUND);
+
+        let list_id = create_list(&server, &event_bus).await;
+        let list_uri = format!("/lists/{list_id}");
+
+        let (status_code, list) =
+            send(&server, "PATCH", &list_uri, "{\"name\": \"Tata list\"}").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(list["name"], "Tata list");
+
+        let (status_code, page) = send(&server, "GET", "/lists", "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(page["items"][0]["name"], "Tata list");
+
+        let (status_code, _) = send(
+            &server,
+            "PUT",
+            &format!("{list_uri}/archive"),
+            "{\"archived\": true}",
+        )
+        .await;
+
+        assert_eq!(status_code, StatusCode::OK);
+
+        let (status_code, _) = send(&server, "POST", &format!("/todos/{list_id}"), &body).await;
+
+        assert_eq!(status_code, StatusCode::CONFLICT);
+
+        let (status_code, deletion) = send(&server, "DELETE", &list_uri, "").await;
+
+        assert_eq!(status_code, StatusCode::ACCEPTED);
+        assert_eq!(deletion["status"], "in_progress");
+
+        let (status_code, _) = send(&server, "DELETE", &list_uri, "").await;
+
+        assert_eq!(status_code, StatusCode::CONFLICT);
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let (status_code, deletion) =
+            send(&server, "GET", &format!("{list_uri}/deletion"), "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(deletion["status"], "completed");
+
+        let (status_code, page) = send(&server, "GET", "/lists", "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(page["items"].as_array().unwrap().len(), 0);
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let detail_types: Vec<_> = event_bus
+            .published()
+            .iter()
+            .map(|entry| entry.detail_type().unwrap_or_default().to_string())
+            .collect();
+
+        assert_eq!(
+            detail_types,
+            [
+                "LIST_CREATED",
+                "LIST_UPDATED",
+                "LIST_UPDATED",
+                "LIST_DELETION_REQUESTED",
+                "LIST_DELETED"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_list_with_todos() {
+        let repository = InMemoryTodoRepository::new();
+        let event_bus = InMemoryEventBus::new();
+        let server = local_server(&repository, &event_bus);
+
+        let list_id = create_list(&server, &event_bus).await;
+        let body = json!({ "title": "Toto todo", "description": "" }).to_string();
+
+        for _ in 0..30 {
+            let (status_code, _) = send(&server, "POST", &format!("/todos/{list_id}"), &body).await;
+
+            assert_eq!(status_code, StatusCode::CREATED);
+        }
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let (status_code, _) = send(&server, "DELETE", &format!("/lists/{list_id}"), "").await;
+
+        assert_eq!(status_code, StatusCode::ACCEPTED);
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let (status_code, deletion) =
+            send(&server, "GET", &format!("/lists/{list_id}/deletion"), "").await;
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(deletion["status"], "completed");
+        assert_eq!(deletion["deleted_todos"], 30);
+
+        // only the deletion is left
+        assert!(repository
+            .list(&list_id, 100, None)
+            .await
+            .unwrap()
+            .todos
+            .is_empty());
+        assert_eq!(repository.get_list(&list_id).await.unwrap(), None);
+        assert_eq!(
+            repository.get_counter(&list_id).await.unwrap().todos_count,
+            0
+        );
+
+        event_bus.dispatch_pending().await.unwrap();
+
+        let deleted_count = event_bus
+            .published()
+            .iter()
+            .filter(|entry| {
+                entry
+                    .detail_type()
+                    .is_some_and(|detail_type| detail_type.ends_with("_DELETED"))
+            })
+            .count();
+
+        // a single event for the list, none for its todos
+        assert_eq!(deleted_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failure_response() {
+        let response = failure_response(FailureResponse::new(
+            ErrorCode::InvalidRequest,
+            "Invalid request",
+        ))
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["code"], "INVALID_REQUEST");
+        assert_eq!(body["message"], "Invalid request");
+    }
+}
@@ -0,0 +1,80 @@
+use std::future::Future;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use lambda_runtime::{
+    tracing::{debug, error},
+    Error,
+};
+use outbox_relay::relay;
+use shared::{keys::OutboxKey, DynamoItem, OutboxRecord};
+
+/// The outbox records of the repository served locally, relayed after each request.
+pub(crate) trait Outbox: Send + Sync {
+    /// Relay the records that were not sent yet.
+    fn relay_pending(
+        &self,
+        eventbridge_client: &aws_sdk_eventbridge::Client,
+        event_bus_name: &str,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// The outbox records of the table.
+///
+/// DynamoDB Local streams are not wired to the relay, the table is scanned instead.
+pub(crate) struct TableOutbox<'a> {
+    pub dynamodb_client: &'a aws_sdk_dynamodb::Client,
+    pub todos_table_name: &'a str,
+}
+
+impl Outbox for TableOutbox<'_> {
+    async fn relay_pending(
+        &self,
+        eventbridge_client: &aws_sdk_eventbridge::Client,
+        event_bus_name: &str,
+    ) -> Result<(), Error> {
+        let items = self
+            .dynamodb_client
+            .scan()
+            .table_name(self.todos_table_name)
+            .filter_expression("begins_with(PK, :outbox) AND attribute_not_exists(sent_at)")
+            .expression_attribute_values(
+                ":outbox",
+                AttributeValue::S(OutboxKey::PARTITION_KEY_PREFIX.into()),
+            )
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await?;
+
+        let mut outbox_records = items
+            .into_iter()
+            .filter_map(|item| {
+                OutboxRecord::from_item(item)
+                    .map_err(|err| error!(err = ?err, "Unable to deserialize outbox record"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        // ulids keep the events in the order they were written
+        outbox_records.sort_by(|a, b| a.id.cmp(&b.id));
+
+        for outbox_record in outbox_records {
+            let envelope = outbox_record.envelope()?;
+
+            relay(
+                &outbox_record,
+                &envelope,
+                self.dynamodb_client,
+                eventbridge_client,
+                self.todos_table_name,
+                event_bus_name,
+            )
+            .await?;
+
+            debug!(record_id = outbox_record.id, "Relayed outbox record");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use aws_lambda_events::apigw::{
+    ApiGatewayRequestAuthorizer, ApiGatewayRequestAuthorizerIamDescription,
+    ApiGatewayV2httpRequestContext,
+};
+use axum::{
+    body::{to_bytes, Body},
+    extract::Query,
+    http::Response,
+};
+use lambda_http::{request::RequestContext, IntoResponse, Request, RequestExt};
+use ulid::Ulid;
+
+/// Same as the API Gateway payload limit.
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// The caller of the local requests, API Gateway would authenticate it with SigV4.
+pub(crate) const LOCAL_USER_ARN: &str = "arn:aws:iam::000000000000:user/local";
+
+/// Header impersonating another caller, to try out the shared lists.
+pub(crate) const LOCAL_USER_HEADER: &str = "x-local-user";
+
+/// Convert a plain HTTP request to the request received by the lambdas.
+///
+/// The path parameters are set by the router, like API Gateway does. The caller is the
+/// [`LOCAL_USER_HEADER`] when set, [`LOCAL_USER_ARN`] otherwise.
+pub(crate) async fn to_lambda_request(
+    request: axum::extract::Request,
+) -> Result<Request, axum::Error> {
+    let (parts, body) = request.into_parts();
+
+    let user_arn = parts
+        .headers
+        .get(LOCAL_USER_HEADER)
+        .and_then(|user_arn| user_arn.to_str().ok())
+        .unwrap_or(LOCAL_USER_ARN)
+        .to_string();
+
+    let body = to_bytes(body, MAX_BODY_SIZE).await?;
+    let body = match String::from_utf8(body.to_vec()) {
+        Ok(body) if body.is_empty() => lambda_http::Body::Empty,
+        Ok(body) => lambda_http::Body::Text(body),
+        Err(err) => lambda_http::Body::Binary(err.into_bytes()),
+    };
+
+    let query_string_parameters = Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+        .map(|Query(parameters)| parameters)
+        .unwrap_or_default();
+
+    let mut request_context = ApiGatewayV2httpRequestContext::default();
+    request_context.request_id = Some(Ulid::new().to_string());
+
+    let mut iam = ApiGatewayRequestAuthorizerIamDescription::default();
+    iam.user_arn = Some(user_arn);
+
+    let mut authorizer = ApiGatewayRequestAuthorizer::default();
+    authorizer.iam = Some(iam);
+    request_context.authorizer = Some(authorizer);
+
+    Ok(Request::from_parts(parts, body)
+        .with_query_string_parameters(query_string_parameters)
+        .with_request_context(RequestContext::ApiGatewayV2(request_context)))
+}
+
+/// Convert a lambda response the same way `lambda_http` does.
+pub(crate) async fn to_axum_response(response: impl IntoResponse) -> Response<Body> {
+    let (parts, body) = response.into_response().await.into_parts();
+
+    let body = match body {
+        lambda_http::Body::Text(body) => Body::from(body),
+        lambda_http::Body::Binary(body) => Body::from(body),
+        _ => Body::empty(),
+    };
+
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_to_lambda_request() {
+        let request = axum::extract::Request::builder()
+            .method("POST")
+            .uri("/todos/toto?limit=10")
+            .body(Body::from("{\"title\": \"Toto todo\"}"))
+            .unwrap();
+
+        let request = to_lambda_request(request).await.unwrap();
+
+        assert_eq!(request.uri().path(), "/todos/toto");
+        assert_eq!(request.query_string_parameters().first("limit"), Some("10"));
+        assert!(
+            matches!(request.body(), lambda_http::Body::Text(body) if body.contains("Toto todo"))
+        );
+        assert!(matches!(
+            request.request_context_ref(),
+            Some(RequestContext::ApiGatewayV2(context)) if context.request_id.is_some()
+        ));
+        assert_eq!(
+            shared::auth::Caller::from_request(&request).unwrap().id(),
+            format!("iam:{LOCAL_USER_ARN}")
+        );
+
+        let request = axum::extract::Request::builder()
+            .uri("/todos/toto")
+            .header(LOCAL_USER_HEADER, "arn:aws:iam::000000000000:user/tata")
+            .body(Body::empty())
+            .unwrap();
+
+        let request = to_lambda_request(request).await.unwrap();
+
+        assert_eq!(
+            shared::auth::Caller::from_request(&request).unwrap().id(),
+            "iam:arn:aws:iam::000000000000:user/tata"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_axum_response() {
+        let response = to_axum_response((StatusCode::CREATED, json!({ "id": "tata" }))).await;
+
+        assert_eq!(response.status(), 201);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+
+        let body = to_bytes(response.into_body(), MAX_BODY_SIZE).await.unwrap();
+
+        assert_eq!(&body[..], b"{\"id\":\"tata\"}");
+    }
+}
@@ -0,0 +1,279 @@
+use aws_lambda_events::eventbridge::EventBridgeEvent;
+use chrono::Utc;
+use lambda_runtime::{
+    tracing::{self, error, info},
+    Error, LambdaEvent,
+};
+use shared::{
+    DeletionStatus, EventEnvelope, ListDeletion, OutboxRecord, TodoEvent, TodoRepository,
+};
+
+/// The todos deleted between two updates of the deletion progress.
+const PAGE_SIZE: i32 = 100;
+
+/// Deletes the todos and members of a list, then the list itself.
+///
+/// The progress is stored after each page, a redelivered event resumes the deletion.
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    event: LambdaEvent<EventBridgeEvent<EventEnvelope>>,
+    repository: &impl TodoRepository,
+) -> Result<(), Error> {
+    let envelope = event.payload.detail;
+
+    let TodoEvent::ListDeletionRequested(requested) = &envelope.event else {
+        let detail_type = envelope.detail_type();
+
+        error!(detail_type = detail_type, "Unexpected detail type");
+
+        return Err(format!("Unexpected detail type {detail_type}").into());
+    };
+
+    info!(
+        list_id = requested.list_id,
+        "Received list.deletion_requested event",
+    );
+
+    let Some(mut deletion) = repository
+        .get_list_deletion(&requested.list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list deletion");
+
+            err
+        })?
+    else {
+        error!(list_id = requested.list_id, "List deletion not found");
+
+        return Err(format!("List deletion not found for {}", requested.list_id).into());
+    };
+
+    if deletion.status == DeletionStatus::Completed {
+        info!(
+            list_id = deletion.list_id,
+            "List deletion already completed"
+        );
+
+        return Ok(());
+    }
+
+    let Some(list) = repository.get_list(&deletion.list_id).await? else {
+        error!(list_id = deletion.list_id, "List not found");
+
+        return Err(format!("List not found {}", deletion.list_id).into());
+    };
+
+    let mut exclusive_start_key = None;
+
+    loop {
+        let deleted = repository
+            .delete_todos(&list.id, PAGE_SIZE, exclusive_start_key)
+            .await
+            .map_err(|err| {
+                error!(err = ?err, "Unable to delete todos");
+
+                err
+            })?;
+
+        deletion.deleted_todos += deleted.count as i64;
+
+        repository.update_list_deletion(&deletion).await?;
+
+        info!(
+            deleted_todos = deletion.deleted_todos,
+            "Deleted page of todos"
+        );
+
+        exclusive_start_key = deleted.last_evaluated_key;
+
+        if exclusive_start_key.is_none() {
+            break;
+        }
+    }
+
+    let members_count = repository.delete_members(&list.id).await.map_err(|err| {
+        error!(err = ?err, "Unable to delete members");
+
+        err
+    })?;
+
+    info!(members_count = members_count, "Deleted members");
+
+    let deletion = ListDeletion {
+        status: DeletionStatus::Completed,
+        completed_at: Some(Utc::now()),
+        ..deletion
+    };
+
+    let outbox_record =
+        OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListDeleted(list.clone())))?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .delete_list(&list, &deletion, &outbox_record)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to delete list");
+
+            err
+        })?;
+
+    info!(
+        list_id = list.id,
+        deleted_todos = deletion.deleted_todos,
+        "Successfully deleted list"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lambda_runtime::Context;
+    use serde_json::json;
+    use shared::{
+        testing::{list, member, todo},
+        InMemoryTodoRepository, List, Role, Todo,
+    };
+
+    const DETAIL_TYPE: &str = "LIST_DELETION_REQUESTED";
+
+    fn get_event() -> LambdaEvent<EventBridgeEvent<EventEnvelope>> {
+        let event = json!({
+          "version": "0",
+          "id": "7bf73129-1428-4cd3-a780-95db273d1602",
+          "detail-type": DETAIL_TYPE,
+          "source": "api.todos",
+          "account": "123456789012",
+          "time": "2024-01-01T00:00:00Z",
+          "region": "us-east-1",
+          "resources": [],
+          "detail": {
+            "id": "event-1",
+            "version": 1,
+            "type": DETAIL_TYPE,
+            "data": {
+              "list_id": "toto",
+              "status": "in_progress",
+              "requested_by": "tata",
+              "requested_at": "2024-01-01T00:00:00Z",
+              "deleted_todos": 0,
+              "completed_at": null
+            }
+          }
+        });
+
+        LambdaEvent::new(serde_json::from_value(event).unwrap(), Context::default())
+    }
+
+    async fn repository_with_deletion(todos_count: usize) -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        let list = list("toto", "tata");
+        repository.seed_list(&list).await;
+
+        for i in 0..todos_count {
+            repository
+                .seed_todo(&Todo {
+                    owner: Some("tata".into()),
+                    ..todo("toto", &format!("tata{i:03}"))
+                })
+                .await;
+        }
+
+        repository
+            .seed_member(&member("toto", "titi", Role::Viewer))
+            .await;
+
+        let deletion = ListDeletion {
+            list_id: "toto".into(),
+            status: DeletionStatus::InProgress,
+            requested_by: "tata".into(),
+            requested_at: Utc::now(),
+            deleted_todos: 0,
+            completed_at: None,
+        };
+        let list = List {
+            archived: true,
+            ..list
+        };
+        let outbox_record = OutboxRecord::new(&EventEnvelope::new(
+            TodoEvent::ListDeletionRequested(deletion.clone()),
+        ))
+        .unwrap();
+
+        repository
+            .request_list_deletion(&list, &deletion, &outbox_record)
+            .await
+            .unwrap();
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_deletion(250).await;
+
+        handler(get_event(), &repository)
+            .await
+            .expect("failed to handle event");
+
+        let deletion = repository.get_list_deletion("toto").await.unwrap().unwrap();
+
+        assert_eq!(deletion.status, DeletionStatus::Completed);
+        assert_eq!(deletion.deleted_todos, 250);
+        assert!(deletion.completed_at.is_some());
+
+        assert_eq!(repository.get_list("toto").await.unwrap(), None);
+        assert!(repository
+            .list("toto", 10, None)
+            .await
+            .unwrap()
+            .todos
+            .is_empty());
+        assert_eq!(repository.delete_members("toto").await.unwrap(), 0);
+
+        let detail_types: Vec<_> = repository
+            .outbox_records()
+            .into_iter()
+            .map(|record| record.detail_type)
+            .filter(|detail_type| detail_type != "TODO_CREATED")
+            .collect();
+
+        // a single event for the whole list, none per todo
+        assert_eq!(
+            detail_types,
+            [
+                "LIST_CREATED",
+                "LIST_SHARED",
+                "LIST_DELETION_REQUESTED",
+                "LIST_DELETED"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_redelivered_event() {
+        let repository = repository_with_deletion(3).await;
+
+        for _ in 0..2 {
+            handler(get_event(), &repository)
+                .await
+                .expect("failed to handle event");
+        }
+
+        let deletion = repository.get_list_deletion("toto").await.unwrap().unwrap();
+
+        assert_eq!(deletion.status, DeletionStatus::Completed);
+        assert_eq!(deletion.deleted_todos, 3);
+    }
+
+    #[tokio::test]
+    async fn test_handler_missing_deletion() {
+        let repository = InMemoryTodoRepository::new();
+
+        handler(get_event(), &repository)
+            .await
+            .expect_err("deletion should not be found");
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Instant;
+
+use lambda_runtime::{service_fn, tracing::debug, Error};
+
+use on_list_deletion_requested::handler;
+use shared::{
+    config::{init_subscriber, ConfigLoader},
+    get_dynamodb_client, DynamoTodoRepository,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let start = Instant::now();
+
+    let config = ConfigLoader::new().load_env()?;
+
+    init_subscriber(&config);
+
+    let dynamodb_client = get_dynamodb_client(&config).await;
+
+    debug!("DynamoDB client initialized in {:.2?}", start.elapsed());
+
+    let repository = DynamoTodoRepository::new(&dynamodb_client, config.todos_table_name());
+
+    let func = service_fn(|request| handler(request, &repository));
+    lambda_runtime::run(func).await?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use aws_lambda_events::eventbridge::EventBridgeEvent;
+use lambda_runtime::{
+    tracing::{self, error, info},
+    Error, LambdaEvent,
+};
+use shared::{CounterIncrement, EventEnvelope, TodoEvent, TodoRepository};
+
+const CONSUMER: &str = "on-todo-completion-changed";
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    event: LambdaEvent<EventBridgeEvent<EventEnvelope>>,
+    repository: &impl TodoRepository,
+) -> Result<(), Error> {
+    let envelope = event.payload.detail;
+
+    let (todo, completed_count) = match &envelope.event {
+        TodoEvent::TodoCompleted(todo) => (todo, 1),
+        TodoEvent::TodoReopened(todo) => (todo, -1),
+        _ => {
+            let detail_type = envelope.detail_type();
+
+            error!(detail_type = detail_type, "Unexpected detail type");
+
+            return Err(format!("Unexpected detail type {detail_type}").into());
+        }
+    };
+
+    info!(
+        todo_id = todo.id,
+        list_id = todo.list_id,
+        detail_type = envelope.detail_type(),
+        "Received todo completion event",
+    );
+
+    let increment = CounterIncrement {
+        todos_count: 0,
+        completed_count,
+    };
+
+    repository
+        .increment_counter(&todo.list_id, increment, CONSUMER, &envelope.id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to set counter");
+
+            err
+        })?;
+
+    Ok(())
+}
@@ -1,34 +1,44 @@
 use aws_lambda_events::eventbridge::EventBridgeEvent;
-use aws_sdk_dynamodb::types::AttributeValue;
 use lambda_runtime::{
     tracing::{self, error, info},
     Error, LambdaEvent,
 };
-use shared::Todo;
+use shared::{CounterIncrement, EventEnvelope, TodoEvent, TodoRepository};
+
+const CONSUMER: &str = "on-todo-deleted";
 
 #[tracing::instrument(skip_all)]
-pub(crate) async fn handler(
-    event: LambdaEvent<EventBridgeEvent<Todo>>,
-    dynamodb_client: &aws_sdk_dynamodb::Client,
-    todos_table_name: &str,
+pub async fn handler(
+    event: LambdaEvent<EventBridgeEvent<EventEnvelope>>,
+    repository: &impl TodoRepository,
 ) -> Result<(), Error> {
+    let envelope = event.payload.detail;
+
+    let TodoEvent::TodoDeleted(todo) = &envelope.event else {
+        let detail_type = envelope.detail_type();
+
+        error!(detail_type = detail_type, "Unexpected detail type");
+
+        return Err(format!("Unexpected detail type {detail_type}").into());
+    };
+
     info!(
-        todo_id = event.payload.detail.id,
-        list_id = event.payload.detail.list_id,
+        todo_id = todo.id,
+        list_id = todo.list_id,
         "Received todo.deleted event",
     );
 
-    dynamodb_client
-        .update_item()
-        .table_name(todos_table_name)
-        .key(
-            "PK",
-            AttributeValue::S(format!("TODO#{}", event.payload.detail.list_id)),
-        )
-        .key("SK", AttributeValue::S("COUNTER".into()))
-        .update_expression("ADD todosCount :increment")
-        .expression_attribute_values(":increment", AttributeValue::N("-1".into()))
-        .send()
+    // completed todos are also counted separately
+    let increment = CounterIncrement {
+        todos_count: -1,
+        completed_count: match todo.completed {
+            true => -1,
+            false => 0,
+        },
+    };
+
+    repository
+        .increment_counter(&todo.list_id, increment, CONSUMER, &envelope.id)
         .await
         .map_err(|err| {
             error!(err = ?err, "Unable to set counter");
@@ -38,3 +48,74 @@ pub(crate) async fn handler(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lambda_runtime::Context;
+    use serde_json::json;
+    use shared::{testing::list, InMemoryTodoRepository};
+
+    const DETAIL_TYPE: &str = "TODO_DELETED";
+
+    fn get_event(event_id: &str) -> LambdaEvent<EventBridgeEvent<EventEnvelope>> {
+        let event = json!({
+          "version": "0",
+          // deduplication relies on the envelope id, not on the EventBridge one
+          "id": "7bf73129-1428-4cd3-a780-95db273d1602",
+          "detail-type": DETAIL_TYPE,
+          "source": "api.todos",
+          "account": "123456789012",
+          "time": "2024-01-01T00:00:00Z",
+          "region": "us-east-1",
+          "resources": [],
+          "detail": {
+            "id": event_id,
+            "version": 1,
+            "type": DETAIL_TYPE,
+            "data": {
+              "id": "tata",
+              "list_id": "toto",
+              "title": "Toto todo",
+              "description": "This is a great description",
+              "completed": true,
+              "completed_at": "2024-01-01T00:00:00Z"
+            }
+          }
+        });
+
+        LambdaEvent::new(serde_json::from_value(event).unwrap(), Context::default())
+    }
+
+    #[tokio::test]
+    async fn test_handler_replayed_event() {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", "toto")).await;
+
+        for _ in 0..2 {
+            handler(get_event("event-1"), &repository)
+                .await
+                .expect("failed to handle event");
+        }
+
+        let counter = repository.get_counter("toto").await.unwrap();
+
+        // the replayed event is only counted once, along with the completion
+        assert_eq!(counter.todos_count, -1);
+        assert_eq!(counter.completed_count, -1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_unexpected_event() {
+        let repository = InMemoryTodoRepository::new();
+        let mut event = get_event("event-1");
+        event.payload.detail.event = TodoEvent::TodoCreated(match event.payload.detail.event {
+            TodoEvent::TodoDeleted(todo) => todo,
+            _ => unreachable!(),
+        });
+
+        handler(event, &repository)
+            .await
+            .expect_err("event should be rejected");
+    }
+}
@@ -1,30 +1,28 @@
-mod handler;
-
-use std::env;
 use std::time::Instant;
 
-use lambda_runtime::{
-    service_fn,
-    tracing::{self, debug},
-    Error,
-};
+use lambda_runtime::{service_fn, tracing::debug, Error};
 
-use handler::handler;
-use shared::get_dynamodb_client;
+use on_todo_deleted::handler;
+use shared::{
+    config::{init_subscriber, ConfigLoader},
+    get_dynamodb_client, DynamoTodoRepository,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let start = Instant::now();
 
-    tracing::init_default_subscriber();
+    let config = ConfigLoader::new().load_env()?;
 
-    let dynamodb_client = get_dynamodb_client().await;
+    init_subscriber(&config);
 
-    let todos_table_name = env::var("TODOS_TABLE_NAME").expect("Missing TODOS_TABLE_NAME env var");
+    let dynamodb_client = get_dynamodb_client(&config).await;
 
     debug!("DynamoDB client initialized in {:.2?}", start.elapsed());
 
-    let func = service_fn(|request| handler(request, &dynamodb_client, &todos_table_name));
+    let repository = DynamoTodoRepository::new(&dynamodb_client, config.todos_table_name());
+
+    let func = service_fn(|request| handler(request, &repository));
     lambda_runtime::run(func).await?;
 
     Ok(())
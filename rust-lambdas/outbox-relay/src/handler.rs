@@ -0,0 +1,285 @@
+use std::{collections::HashMap, time::Duration};
+
+use aws_lambda_events::{
+    dynamodb::Event,
+    streams::{DynamoDbBatchItemFailure, DynamoDbEventResponse},
+};
+use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::Utc;
+use lambda_runtime::{
+    tracing::{self, error, info, warn},
+    Error, LambdaEvent,
+};
+use shared::{
+    keys::{OutboxKey, TableKey},
+    publish, DynamoItem, EventEnvelope, OutboxRecord, EXPIRES_AT_ATTRIBUTE, SENT_OUTBOX_RECORD_TTL,
+};
+use tokio::time::sleep;
+
+const MAX_PUBLISH_ATTEMPTS: u32 = 3;
+const PUBLISH_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    event: LambdaEvent<Event>,
+    dynamodb_client: &aws_sdk_dynamodb::Client,
+    eventbridge_client: &aws_sdk_eventbridge::Client,
+    todos_table_name: &str,
+    event_bus_name: &str,
+) -> Result<DynamoDbEventResponse, Error> {
+    let mut response = DynamoDbEventResponse::default();
+
+    for record in event.payload.records {
+        // marking a record as sent modifies it, only new records are published
+        if record.event_name != "INSERT" {
+            continue;
+        }
+
+        let item: HashMap<String, AttributeValue> = record.change.new_image.into();
+
+        if OutboxKey::from_key(&item).is_err() {
+            continue;
+        }
+
+        let outbox_record = match OutboxRecord::from_item(item) {
+            Ok(outbox_record) => outbox_record,
+            Err(err) => {
+                // retrying would not help, do not block the stream
+                error!(err = ?err, "Unable to deserialize outbox record");
+
+                continue;
+            }
+        };
+
+        let envelope = match outbox_record.envelope() {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                error!(err = ?err, record_id = outbox_record.id, "Unable to deserialize event");
+
+                continue;
+            }
+        };
+
+        if let Err(err) = relay(
+            &outbox_record,
+            &envelope,
+            dynamodb_client,
+            eventbridge_client,
+            todos_table_name,
+            event_bus_name,
+        )
+        .await
+        {
+            error!(err = ?err, record_id = outbox_record.id, "Unable to relay outbox record");
+
+            // the stream is retried from the first failed record, keeping events in order
+            let mut failure = DynamoDbBatchItemFailure::default();
+            failure.item_identifier = record.change.sequence_number;
+            response.batch_item_failures.push(failure);
+
+            break;
+        }
+
+        info!(
+            record_id = outbox_record.id,
+            detail_type = outbox_record.detail_type,
+            "Relayed outbox record",
+        );
+    }
+
+    Ok(response)
+}
+
+/// Publish an outbox record, then mark it as sent.
+pub async fn relay(
+    outbox_record: &OutboxRecord,
+    envelope: &EventEnvelope,
+    dynamodb_client: &aws_sdk_dynamodb::Client,
+    eventbridge_client: &aws_sdk_eventbridge::Client,
+    todos_table_name: &str,
+    event_bus_name: &str,
+) -> Result<(), Error> {
+    publish_with_retries(envelope, eventbridge_client, event_bus_name).await?;
+
+    // the record is published at least once: if this fails, it is published again
+    let sent_at = Utc::now();
+
+    dynamodb_client
+        .update_item()
+        .table_name(todos_table_name)
+        .set_key(Some(outbox_record.key().to_key()))
+        .update_expression("SET sent_at = :sent_at, #expires_at = :expires_at")
+        .condition_expression("attribute_exists(PK)")
+        .expression_attribute_names("#expires_at", EXPIRES_AT_ATTRIBUTE)
+        .expression_attribute_values(":sent_at", AttributeValue::S(sent_at.to_rfc3339()))
+        .expression_attribute_values(
+            ":expires_at",
+            AttributeValue::N((sent_at + SENT_OUTBOX_RECORD_TTL).timestamp().to_string()),
+        )
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+async fn publish_with_retries(
+    envelope: &EventEnvelope,
+    eventbridge_client: &aws_sdk_eventbridge::Client,
+    event_bus_name: &str,
+) -> Result<(), Error> {
+    let mut attempt = 1;
+
+    loop {
+        let err = match publish(eventbridge_client, event_bus_name, envelope).await {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+
+        if attempt >= MAX_PUBLISH_ATTEMPTS {
+            return Err(err.into());
+        }
+
+        warn!(err = ?err, attempt = attempt, "Unable to publish event, retrying");
+
+        sleep(PUBLISH_RETRY_DELAY * 2u32.pow(attempt - 1)).await;
+
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_mocks::{mock, mock_client};
+
+    use aws_sdk_dynamodb::operation::update_item::UpdateItemOutput;
+    use aws_sdk_eventbridge::{
+        operation::put_events::{PutEventsError, PutEventsOutput},
+        types::error::InternalException,
+    };
+    use lambda_runtime::Context;
+    use serde_json::json;
+
+    fn stream_record(sequence_number: &str, keys: serde_json::Value) -> serde_json::Value {
+        let mut new_image = json!({
+          "detail_type": { "S": "TODO_CREATED" },
+          "detail": { "S": json!({
+            "id": sequence_number,
+            "version": 1,
+            "type": "TODO_CREATED",
+            "data": {
+              "id": "tata",
+              "list_id": "toto",
+              "title": "Toto todo",
+              "description": "This is a great description",
+              "completed": false,
+              "completed_at": null
+            }
+          }).to_string() },
+          "created_at": { "S": "2024-01-01T00:00:00+00:00" }
+        });
+        new_image
+            .as_object_mut()
+            .unwrap()
+            .extend(keys.as_object().unwrap().clone());
+        new_image["id"] = json!({ "S": sequence_number });
+
+        json!({
+          "eventID": sequence_number,
+          "eventName": "INSERT",
+          "eventVersion": "1.1",
+          "eventSource": "aws:dynamodb",
+          "awsRegion": "us-east-1",
+          "dynamodb": {
+            "ApproximateCreationDateTime": 1704067200,
+            "Keys": keys,
+            "NewImage": new_image,
+            "SequenceNumber": sequence_number,
+            "SizeBytes": 26,
+            "StreamViewType": "NEW_IMAGE"
+          },
+          "eventSourceARN": "arn:aws:dynamodb:us-east-1:123456789012:table/toto/stream/2024"
+        })
+    }
+
+    fn get_event(records: Vec<serde_json::Value>) -> LambdaEvent<Event> {
+        LambdaEvent::new(
+            serde_json::from_value(json!({ "Records": records })).unwrap(),
+            Context::default(),
+        )
+    }
+
+    fn outbox_keys(record_id: &str) -> serde_json::Value {
+        json!({
+          "PK": { "S": format!("OUTBOX#{record_id}") },
+          "SK": { "S": "RECORD" }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_handler_retries() {
+        let mock_put_events = mock!(aws_sdk_eventbridge::Client::put_events)
+            .match_requests(|req| {
+                req.entries()[0].detail_type() == Some("TODO_CREATED")
+                    && req.entries()[0].source() == Some("api.todos")
+            })
+            .sequence()
+            .error(|| PutEventsError::InternalException(InternalException::builder().build()))
+            .output(|| PutEventsOutput::builder().failed_entry_count(0).build())
+            .build();
+        let eventbridge_client = mock_client!(aws_sdk_eventbridge, &[&mock_put_events]);
+
+        let mock_update_item = mock!(aws_sdk_dynamodb::Client::update_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("PK"))
+                    == Some(&AttributeValue::S("OUTBOX#1".into()))
+            })
+            .then_output(|| UpdateItemOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_update_item]);
+
+        let event = get_event(vec![
+            // todos are written to the same table, they are skipped
+            stream_record(
+                "0",
+                json!({ "PK": { "S": "TODO#toto" }, "SK": { "S": "ID#tata" } }),
+            ),
+            stream_record("1", outbox_keys("1")),
+        ]);
+
+        let res = handler(event, &dynamodb_client, &eventbridge_client, "toto", "tata")
+            .await
+            .expect("failed to handle event");
+
+        assert!(res.batch_item_failures.is_empty());
+        assert_eq!(mock_put_events.num_calls(), 2);
+        assert_eq!(mock_update_item.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_reports_failure() {
+        let mock_put_events = mock!(aws_sdk_eventbridge::Client::put_events)
+            .sequence()
+            .error(|| PutEventsError::InternalException(InternalException::builder().build()))
+            .times(3)
+            .build();
+        let eventbridge_client = mock_client!(aws_sdk_eventbridge, &[&mock_put_events]);
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[]);
+
+        let event = get_event(vec![
+            stream_record("1", outbox_keys("1")),
+            stream_record("2", outbox_keys("2")),
+        ]);
+
+        let res = handler(event, &dynamodb_client, &eventbridge_client, "toto", "tata")
+            .await
+            .expect("failed to handle event");
+
+        // the following records are not published before the failed one
+        assert_eq!(res.batch_item_failures.len(), 1);
+        assert_eq!(
+            res.batch_item_failures[0].item_identifier.as_deref(),
+            Some("1")
+        );
+        assert_eq!(mock_put_events.num_calls(), 3);
+    }
+}
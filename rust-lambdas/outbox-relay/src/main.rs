@@ -0,0 +1,36 @@
+use std::time::Instant;
+
+use lambda_runtime::{service_fn, tracing::debug, Error};
+
+use outbox_relay::handler;
+use shared::{
+    config::{init_subscriber, ConfigLoader},
+    get_dynamodb_client, get_event_bridge_client,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let start = Instant::now();
+
+    let config = ConfigLoader::new().event_bus().load_env()?;
+
+    init_subscriber(&config);
+
+    let dynamodb_client = get_dynamodb_client(&config).await;
+    let eventbridge_client = get_event_bridge_client(&config).await;
+
+    debug!("Clients initialized in {:.2?}", start.elapsed());
+
+    let func = service_fn(|request| {
+        handler(
+            request,
+            &dynamodb_client,
+            &eventbridge_client,
+            config.todos_table_name(),
+            config.event_bus_name(),
+        )
+    });
+    lambda_runtime::run(func).await?;
+
+    Ok(())
+}
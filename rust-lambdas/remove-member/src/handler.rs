@@ -0,0 +1,180 @@
+use aws_lambda_events::http::StatusCode;
+use shared::{
+    auth::Caller, keys::ListMemberKey, ErrorCode, EventEnvelope, FailureResponse, OutboxRecord,
+    RepositoryError, Role, TodoEvent, TodoRepository,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    // the user ids are ARNs, which contain slashes
+    let query_string_parameters = request.query_string_parameters();
+
+    let user_id = query_string_parameters
+        .first("userId")
+        .ok_or(FailureResponse::new(
+            ErrorCode::MissingParameter,
+            "Missing user id",
+        ))?;
+
+    let key = ListMemberKey::new(list_id, user_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    // members may leave the list, only the owner removes the others
+    if caller.id() != user_id {
+        caller
+            .list_access(repository, list_id)
+            .await
+            .map_err(|err| {
+                error!(err = ?err, "Unable to get list access");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to remove member")
+            })?
+            .require(Role::Owner)?;
+    }
+
+    // the event carries the removed member
+    let member = repository
+        .get_member(&key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get member");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to remove member")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::MemberNotFound,
+            "Member not found",
+        ))?;
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::MemberRemoved(
+        member.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize member");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize member")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .remove_member(&member, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::MemberNotFound, "Member not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to remove member");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to remove member")
+            }
+        })?;
+
+    debug!("Item deleted in {:.2?}", start.elapsed());
+
+    info!(list_id = list_id, "Successfully removed member");
+
+    Ok((StatusCode::NO_CONTENT, "".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, member, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+    const MEMBER: &str = "arn:aws:iam::123456789012:user/tata";
+
+    fn get_request_as(member_arn: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("DELETE")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .query(&format!("userId={}", iam_id(member_arn)))
+            .build()
+    }
+
+    async fn repository_with_members() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", &iam_id(OWNER))).await;
+
+        for user_arn in [MEMBER, "arn:aws:iam::123456789012:user/titi"] {
+            repository
+                .seed_member(&member("toto", &iam_id(user_arn), Role::Editor))
+                .await;
+        }
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_members().await;
+
+        let (status, _) = handler(get_request_as(MEMBER, OWNER), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 204);
+
+        let key = ListMemberKey::new("toto", &iam_id(MEMBER)).unwrap();
+
+        assert!(repository.get_member(&key).await.unwrap().is_none());
+
+        let outbox_records = repository.outbox_records();
+        let envelope = outbox_records[3].envelope().unwrap();
+
+        assert!(
+            matches!(envelope.event, TodoEvent::MemberRemoved(member) if member.user_id == iam_id(MEMBER))
+        );
+
+        let err = handler(get_request_as(MEMBER, OWNER), &repository)
+            .await
+            .expect_err("member should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_leave() {
+        let repository = repository_with_members().await;
+
+        let (status, _) = handler(get_request_as(MEMBER, MEMBER), &repository)
+            .await
+            .expect("a member should leave the list");
+
+        assert_eq!(status, 204);
+
+        let err = handler(
+            get_request_as("arn:aws:iam::123456789012:user/titi", MEMBER),
+            &repository,
+        )
+        .await
+        .expect_err("a former member should not remove the others");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
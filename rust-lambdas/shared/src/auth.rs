@@ -0,0 +1,288 @@
+//! The caller of the HTTP lambdas, as authenticated by the API Gateway authorizers.
+
+use lambda_http::{request::RequestContext, Request, RequestExt};
+
+use crate::{
+    keys::ListMemberKey, ErrorCode, FailureResponse, List, RepositoryError, Role, TodoRepository,
+};
+
+/// The authenticated principal of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    /// A request signed with SigV4, identified by the ARN of the IAM user or role session.
+    Iam { user_arn: String },
+    /// A request bearing a JWT, identified by its `iss` and `sub` claims.
+    ///
+    /// The claims come from an API Gateway JWT authorizer, or from the context of the
+    /// `jwt-authorizer` lambda.
+    Jwt { issuer: String, subject: String },
+}
+
+impl Caller {
+    /// Read the caller from the authorizer of the request context.
+    ///
+    /// Fails with a 401 when no authorizer identified the caller, which API Gateway prevents.
+    pub fn from_request(request: &Request) -> Result<Caller, FailureResponse> {
+        let authorizer = match request.request_context_ref() {
+            Some(RequestContext::ApiGatewayV2(context)) => context.authorizer.as_ref(),
+            _ => None,
+        };
+
+        let caller = authorizer.and_then(|authorizer| {
+            let iam = authorizer
+                .iam
+                .as_ref()
+                .and_then(|iam| iam.user_arn.clone())
+                .map(|user_arn| Caller::Iam { user_arn });
+
+            let claim = |name: &str| {
+                authorizer
+                    .jwt
+                    .as_ref()
+                    .and_then(|jwt| jwt.claims.get(name).cloned())
+                    .or_else(|| {
+                        authorizer
+                            .fields
+                            .get(name)
+                            .and_then(|claim| claim.as_str())
+                            .map(Into::into)
+                    })
+            };
+
+            let jwt = || {
+                Some(Caller::Jwt {
+                    issuer: claim("iss")?,
+                    subject: claim("sub")?,
+                })
+            };
+
+            iam.or_else(jwt)
+        });
+
+        caller.ok_or(FailureResponse::new(
+            ErrorCode::Unauthorized,
+            "Missing caller identity",
+        ))
+    }
+
+    /// The id stored as the owner of the items created by the caller.
+    ///
+    /// It is namespaced by the kind of principal, so that a `sub` claim can't take over the
+    /// lists of an IAM user, nor the subject of another issuer.
+    pub fn id(&self) -> String {
+        match self {
+            Caller::Iam { user_arn } => format!("iam:{user_arn}"),
+            Caller::Jwt { issuer, subject } => format!("jwt:{issuer}|{subject}"),
+        }
+    }
+
+    /// The access of the caller to a list: the list, its owner, and the role of the caller.
+    ///
+    /// The lists that were never created are not accessible.
+    pub async fn list_access(
+        &self,
+        repository: &impl TodoRepository,
+        list_id: &str,
+    ) -> Result<ListAccess, RepositoryError> {
+        let id = self.id();
+        let list = repository.get_list(list_id).await?;
+
+        let owner = list.as_ref().map(|list| list.owner.clone());
+
+        let role = match owner.as_deref() {
+            None => None,
+            Some(owner) if owner == id => Some(Role::Owner),
+            Some(_) => repository
+                .get_member(&ListMemberKey::new(list_id, &id)?)
+                .await?
+                .map(|member| member.role),
+        };
+
+        Ok(ListAccess { list, owner, role })
+    }
+}
+
+/// The access of a caller to a list, see [`Caller::list_access`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListAccess {
+    /// `None` for the lists that were never created.
+    pub list: Option<List>,
+    /// `None` for the lists that were never created.
+    pub owner: Option<String>,
+    /// `None` when the list was never created or is not shared with the caller.
+    pub role: Option<Role>,
+}
+
+impl ListAccess {
+    /// The list, a 404 when it was never created.
+    pub fn existing_list(&self) -> Result<&List, FailureResponse> {
+        self.list.as_ref().ok_or(FailureResponse::new(
+            ErrorCode::ListNotFound,
+            "List not found",
+        ))
+    }
+
+    /// A 403 unless the caller has at least the role.
+    pub fn require(&self, role: Role) -> Result<(), FailureResponse> {
+        match self.role {
+            Some(caller_role) if caller_role >= role => Ok(()),
+            Some(_) => Err(FailureResponse::new(
+                ErrorCode::Forbidden,
+                "Insufficient role on the list",
+            )),
+            None => Err(FailureResponse::new(
+                ErrorCode::Forbidden,
+                "The list belongs to another user",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        testing::{list, member, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    fn get_request(authorizer: Value) -> Request {
+        ApiGatewayRequest::new("GET").authorizer(authorizer).build()
+    }
+
+    fn jwt(subject: &str) -> Caller {
+        Caller::Jwt {
+            issuer: "https://toto.com".into(),
+            subject: subject.into(),
+        }
+    }
+
+    #[test]
+    fn test_from_request() {
+        let request = get_request(json!({
+            "iam": { "userArn": "arn:aws:iam::123456789012:user/toto" }
+        }));
+
+        assert_eq!(
+            Caller::from_request(&request).unwrap(),
+            Caller::Iam {
+                user_arn: "arn:aws:iam::123456789012:user/toto".into()
+            }
+        );
+
+        let request = get_request(json!({
+            "jwt": { "claims": { "sub": "tata", "iss": "https://toto.com" }, "scopes": null }
+        }));
+
+        assert_eq!(Caller::from_request(&request).unwrap(), jwt("tata"));
+
+        let request = get_request(json!({
+            "lambda": { "sub": "titi", "iss": "https://toto.com", "exp": 1583348638 }
+        }));
+
+        assert_eq!(Caller::from_request(&request).unwrap(), jwt("titi"));
+
+        let err = Caller::from_request(&get_request(Value::Null)).unwrap_err();
+
+        assert_eq!(err.status_code, 401);
+
+        // a token without issuer can't be told apart from the ones of other issuers
+        let request = get_request(json!({
+            "lambda": { "sub": "titi", "exp": 1583348638 }
+        }));
+
+        assert_eq!(Caller::from_request(&request).unwrap_err().status_code, 401);
+    }
+
+    #[test]
+    fn test_id() {
+        let iam = Caller::Iam {
+            user_arn: "arn:aws:iam::123456789012:user/toto".into(),
+        };
+
+        assert_eq!(iam.id(), "iam:arn:aws:iam::123456789012:user/toto");
+        assert_eq!(jwt("tata").id(), "jwt:https://toto.com|tata");
+
+        // a subject equal to the ARN of an IAM user, or to a subject of another issuer
+        let colliding = [
+            Caller::Jwt {
+                issuer: "https://toto.com".into(),
+                subject: "arn:aws:iam::123456789012:user/toto".into(),
+            },
+            Caller::Jwt {
+                issuer: "https://tata.com".into(),
+                subject: "tata".into(),
+            },
+        ];
+
+        assert_ne!(colliding[0].id(), iam.id());
+        assert_ne!(colliding[1].id(), jwt("tata").id());
+    }
+
+    #[tokio::test]
+    async fn test_list_access() {
+        let repository = InMemoryTodoRepository::new();
+
+        // the lists that were never created are not accessible
+        for caller in ["tata", "titi"] {
+            let access = jwt(caller).list_access(&repository, "toto").await.unwrap();
+
+            assert_eq!(access.owner, None);
+            assert_eq!(access.require(Role::Viewer).unwrap_err().status_code, 403);
+        }
+
+        repository.seed_list(&list("toto", &jwt("tata").id())).await;
+        repository
+            .seed_member(&member("toto", &jwt("titi").id(), Role::Viewer))
+            .await;
+
+        let access = jwt("tata").list_access(&repository, "toto").await.unwrap();
+
+        assert_eq!(access.owner, Some(jwt("tata").id()));
+        assert!(access.require(Role::Owner).is_ok());
+
+        let access = jwt("titi").list_access(&repository, "toto").await.unwrap();
+
+        assert!(access.require(Role::Viewer).is_ok());
+        assert_eq!(
+            access.require(Role::Editor).unwrap_err().message,
+            "Insufficient role on the list"
+        );
+
+        let access = jwt("tutu").list_access(&repository, "toto").await.unwrap();
+
+        assert_eq!(access.role, None);
+        assert_eq!(access.require(Role::Viewer).unwrap_err().status_code, 403);
+
+        // the same subject from another issuer is a stranger
+        let access = Caller::Jwt {
+            issuer: "https://tata.com".into(),
+            subject: "tata".into(),
+        }
+        .list_access(&repository, "toto")
+        .await
+        .unwrap();
+
+        assert_eq!(access.role, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_access_created_list() {
+        let repository = InMemoryTodoRepository::new();
+        let list = list("toto", &jwt("tata").id());
+        repository.seed_list(&list).await;
+
+        let caller = jwt("tata");
+
+        let access = caller.list_access(&repository, "toto").await.unwrap();
+
+        assert_eq!(access.role, Some(Role::Owner));
+        assert_eq!(access.existing_list().unwrap(), &list);
+
+        let access = caller.list_access(&repository, "tutu").await.unwrap();
+
+        assert_eq!(access.existing_list().unwrap_err().status_code, 404);
+    }
+}
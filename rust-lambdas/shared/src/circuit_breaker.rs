@@ -0,0 +1,248 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use aws_sdk_dynamodb::{
+    config::{
+        interceptors::{BeforeSerializationInterceptorContextRef, FinalizerInterceptorContextRef},
+        ConfigBag, Intercept, RuntimeComponents,
+    },
+    error::BoxError,
+};
+use tracing::warn;
+
+use crate::{ErrorCode, FailureResponse};
+
+/// When a [`CircuitBreaker`] opens, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive failed calls opening the breaker.
+    pub failure_threshold: u32,
+    /// How long the calls fail fast once open.
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        CircuitBreakerPolicy {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Fails fast once the AWS calls failed repeatedly, instead of waiting for their timeouts.
+///
+/// A call fails when it times out, cannot connect or gets a 5xx response, the other errors
+/// come from a healthy service. Once open for the policy duration, the next call is let
+/// through: the breaker closes when it succeeds, and opens again when it fails.
+///
+/// The state is per execution environment, attach the same breaker to all the clients with
+/// [`crate::ClientBuilder::circuit_breaker`].
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        CircuitBreaker {
+            policy,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap();
+
+        state
+            .opened_at
+            .is_some_and(|opened_at| opened_at.elapsed() < self.policy.open_duration)
+    }
+
+    /// A 503 while the breaker is open.
+    pub fn check(&self) -> Result<(), FailureResponse> {
+        match self.is_open() {
+            true => Err(FailureResponse::new(
+                ErrorCode::ServiceUnavailable,
+                "Service unavailable, retry later",
+            )),
+            false => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        state.consecutive_failures += 1;
+
+        if state.consecutive_failures >= self.policy.failure_threshold {
+            if state.opened_at.is_none() {
+                warn!(
+                    consecutive_failures = state.consecutive_failures,
+                    "Circuit breaker opened"
+                );
+            }
+
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("policy", &self.policy)
+            .field("open", &self.is_open())
+            .finish()
+    }
+}
+
+impl Intercept for CircuitBreaker {
+    fn name(&self) -> &'static str {
+        "CircuitBreaker"
+    }
+
+    fn read_before_execution(
+        &self,
+        _context: &BeforeSerializationInterceptorContextRef<'_>,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        match self.is_open() {
+            true => Err("circuit breaker is open".into()),
+            false => Ok(()),
+        }
+    }
+
+    fn read_after_execution(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        match context.output_or_error() {
+            // failed fast, the service was not called
+            Some(Err(err)) if err.is_interceptor_error() => {}
+            Some(Err(err))
+                if err.is_timeout_error()
+                    || err.is_connector_error()
+                    || context
+                        .response()
+                        .is_some_and(|response| response.status().is_server_error()) =>
+            {
+                self.record_failure()
+            }
+            Some(_) => self.record_success(),
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::{
+        config::retry::RetryConfig,
+        operation::get_item::GetItemError,
+        types::{error::ResourceNotFoundException, AttributeValue},
+    };
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+
+    fn circuit_breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 2,
+            open_duration: Duration::from_millis(50),
+        })
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let circuit_breaker = circuit_breaker();
+
+        circuit_breaker.record_failure();
+        circuit_breaker.record_success();
+        circuit_breaker.record_failure();
+
+        assert!(circuit_breaker.check().is_ok());
+
+        circuit_breaker.record_failure();
+
+        let err = circuit_breaker.check().unwrap_err();
+
+        assert_eq!(err.status_code, 503);
+
+        // half open after the open duration, until the next outcome
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert!(!circuit_breaker.is_open());
+
+        circuit_breaker.record_failure();
+
+        assert!(circuit_breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_intercept() {
+        let circuit_breaker = circuit_breaker();
+
+        let get_item_rule = mock!(aws_sdk_dynamodb::Client::get_item)
+            .sequence()
+            .error(|| {
+                GetItemError::ResourceNotFoundException(
+                    ResourceNotFoundException::builder().build(),
+                )
+            })
+            .http_status(500, None)
+            .times(2)
+            .build();
+
+        let client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::Sequential,
+            [&get_item_rule],
+            // one response per call, the retries would consume the sequence
+            |config| config
+                .interceptor(circuit_breaker.clone())
+                .retry_config(RetryConfig::disabled())
+        );
+
+        let get_item = || {
+            client
+                .get_item()
+                .table_name("toto")
+                .key("PK", AttributeValue::S("tata".into()))
+                .send()
+        };
+
+        // a client error does not count
+        get_item().await.unwrap_err();
+        assert!(!circuit_breaker.is_open());
+
+        get_item().await.unwrap_err();
+        get_item().await.unwrap_err();
+        assert!(circuit_breaker.is_open());
+
+        // failed fast, without calling the service
+        get_item().await.unwrap_err();
+        assert_eq!(get_item_rule.num_calls(), 3);
+    }
+}
@@ -1,29 +1,340 @@
-use aws_config::{BehaviorVersion, SdkConfig};
+use std::time::Duration;
+
+use aws_config::{
+    retry::{RetryConfig, RetryMode},
+    timeout::TimeoutConfig,
+    BehaviorVersion, Region, SdkConfig,
+};
 use aws_runtime::recursion_detection::RecursionDetectionInterceptor;
+use aws_sdk_dynamodb::{
+    client::customize::CustomizableOperation,
+    config::{ProvideCredentials, SharedCredentialsProvider, SharedInterceptor},
+};
 use tokio::sync::OnceCell;
 
+use crate::{config::Config, CircuitBreaker};
+
+/// How the failed AWS calls are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// The adaptive mode also rate limits the client once it gets throttled.
+    pub mode: RetryMode,
+    /// Attempts per call, including the first one.
+    pub max_attempts: u32,
+    /// The first backoff, doubled with jitter on every retry.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            mode: RetryMode::Standard,
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl From<RetryPolicy> for RetryConfig {
+    fn from(policy: RetryPolicy) -> Self {
+        RetryConfig::standard()
+            .with_retry_mode(policy.mode)
+            .with_max_attempts(policy.max_attempts)
+            .with_initial_backoff(policy.initial_backoff)
+            .with_max_backoff(policy.max_backoff)
+    }
+}
+
+/// How long the AWS calls may take, well below the 3 seconds of the lambdas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeoutPolicy {
+    pub connect: Duration,
+    /// A single attempt.
+    pub operation_attempt: Duration,
+    /// All the attempts of a call, backoffs included.
+    pub operation: Duration,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        TimeoutPolicy {
+            connect: Duration::from_millis(500),
+            operation_attempt: Duration::from_secs(1),
+            operation: Duration::from_secs(2),
+        }
+    }
+}
+
+impl From<TimeoutPolicy> for TimeoutConfig {
+    fn from(policy: TimeoutPolicy) -> Self {
+        TimeoutConfig::builder()
+            .connect_timeout(policy.connect)
+            .operation_attempt_timeout(policy.operation_attempt)
+            .operation_timeout(policy.operation)
+            .build()
+    }
+}
+
+/// Override the policies of the client for a single DynamoDB call.
+///
+/// ```ignore
+/// dynamodb_client
+///     .batch_write_item()
+///     .customize()
+///     .with_policies(retry_policy, timeout_policy)
+///     .send()
+///     .await
+/// ```
+pub trait WithPolicies {
+    fn with_policies(self, retry_policy: RetryPolicy, timeout_policy: TimeoutPolicy) -> Self;
+}
+
+impl<T, E, B> WithPolicies for CustomizableOperation<T, E, B> {
+    fn with_policies(self, retry_policy: RetryPolicy, timeout_policy: TimeoutPolicy) -> Self {
+        self.config_override(
+            aws_sdk_dynamodb::Config::builder()
+                .retry_config(retry_policy.into())
+                .timeout_config(timeout_policy.into()),
+        )
+    }
+}
+
 static AWS_CONFIG: OnceCell<SdkConfig> = OnceCell::const_new();
 
 async fn build_aws_config() -> aws_config::SdkConfig {
     aws_config::load_defaults(BehaviorVersion::latest()).await
 }
 
-pub async fn get_dynamodb_client() -> aws_sdk_dynamodb::Client {
-    let config = AWS_CONFIG.get_or_init(build_aws_config).await;
+/// Builder of the AWS clients, all of them carrying the [`RecursionDetectionInterceptor`].
+///
+/// The settings left unset are loaded from the environment, once per execution environment.
+/// When both the region and the credentials are set, nothing is resolved from the instance
+/// metadata or the profiles, so that local and test clients are the same on every machine.
+///
+/// ```ignore
+/// let dynamodb_client = ClientBuilder::new()
+///     .endpoint_url("http://localhost:8000")
+///     .region(Region::new("local"))
+///     .credentials_provider(Credentials::new("local", "local", None, None, "local"))
+///     .dynamodb()
+///     .await;
+/// ```
+///
+/// The policies apply to every call of the clients, see [`WithPolicies`] to override them
+/// for a single call.
+#[derive(Clone, Default)]
+pub struct ClientBuilder {
+    endpoint_url: Option<String>,
+    dynamodb_endpoint_url: Option<String>,
+    eventbridge_endpoint_url: Option<String>,
+    region: Option<Region>,
+    credentials_provider: Option<SharedCredentialsProvider>,
+    retry_config: Option<RetryConfig>,
+    timeout_config: Option<TimeoutConfig>,
+    circuit_breaker: Option<CircuitBreaker>,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        ClientBuilder::default()
+    }
+
+    /// The endpoint overrides and the policies of the config.
+    pub fn from_config(config: &Config) -> Self {
+        ClientBuilder {
+            dynamodb_endpoint_url: config.dynamodb_endpoint_url().map(Into::into),
+            eventbridge_endpoint_url: config.eventbridge_endpoint_url().map(Into::into),
+            ..ClientBuilder::new()
+        }
+        .retry_config(config.retry_policy().into())
+        .timeout_config(config.timeout_policy().into())
+    }
+
+    /// The endpoint of all the clients, taking precedence over the ones of the config.
+    pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn credentials_provider(
+        mut self,
+        credentials_provider: impl ProvideCredentials + 'static,
+    ) -> Self {
+        self.credentials_provider = Some(SharedCredentialsProvider::new(credentials_provider));
+        self
+    }
+
+    pub fn retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = Some(retry_config);
+        self
+    }
+
+    pub fn timeout_config(mut self, timeout_config: TimeoutConfig) -> Self {
+        self.timeout_config = Some(timeout_config);
+        self
+    }
+
+    /// Record the outcome of the calls, and fail them fast while the breaker is open.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
 
-    let dynamodb_config = aws_sdk_dynamodb::config::Builder::from(config)
-        .interceptor(RecursionDetectionInterceptor::new())
-        .build();
+    pub async fn dynamodb(&self) -> aws_sdk_dynamodb::Client {
+        let sdk_config = self.sdk_config(self.dynamodb_endpoint_url.as_ref()).await;
 
-    aws_sdk_dynamodb::Client::from_conf(dynamodb_config)
+        let mut dynamodb_config = aws_sdk_dynamodb::config::Builder::from(&sdk_config)
+            .interceptor(RecursionDetectionInterceptor::new());
+
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            dynamodb_config.push_interceptor(SharedInterceptor::new(circuit_breaker.clone()));
+        }
+
+        aws_sdk_dynamodb::Client::from_conf(dynamodb_config.build())
+    }
+
+    pub async fn eventbridge(&self) -> aws_sdk_eventbridge::Client {
+        let sdk_config = self
+            .sdk_config(self.eventbridge_endpoint_url.as_ref())
+            .await;
+
+        let mut eventbridge_config = aws_sdk_eventbridge::config::Builder::from(&sdk_config)
+            .interceptor(RecursionDetectionInterceptor::new());
+
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            eventbridge_config.push_interceptor(SharedInterceptor::new(circuit_breaker.clone()));
+        }
+
+        aws_sdk_eventbridge::Client::from_conf(eventbridge_config.build())
+    }
+
+    async fn sdk_config(&self, service_endpoint_url: Option<&String>) -> SdkConfig {
+        let mut sdk_config = match (&self.region, &self.credentials_provider) {
+            (Some(region), Some(credentials_provider)) => {
+                aws_config::defaults(BehaviorVersion::latest())
+                    .region(region.clone())
+                    .credentials_provider(credentials_provider.clone())
+                    .load()
+                    .await
+            }
+            _ => AWS_CONFIG.get_or_init(build_aws_config).await.clone(),
+        }
+        .into_builder();
+
+        if let Some(endpoint_url) = self.endpoint_url.as_ref().or(service_endpoint_url) {
+            sdk_config.set_endpoint_url(Some(endpoint_url.clone()));
+        }
+        if let Some(region) = &self.region {
+            sdk_config.set_region(region.clone());
+        }
+        if let Some(credentials_provider) = &self.credentials_provider {
+            sdk_config.set_credentials_provider(Some(credentials_provider.clone()));
+        }
+        if let Some(retry_config) = &self.retry_config {
+            sdk_config.set_retry_config(Some(retry_config.clone()));
+        }
+        if let Some(timeout_config) = &self.timeout_config {
+            sdk_config.set_timeout_config(Some(timeout_config.clone()));
+        }
+
+        sdk_config.build()
+    }
+}
+
+pub async fn get_dynamodb_client(config: &Config) -> aws_sdk_dynamodb::Client {
+    ClientBuilder::from_config(config).dynamodb().await
+}
+
+pub async fn get_event_bridge_client(config: &Config) -> aws_sdk_eventbridge::Client {
+    ClientBuilder::from_config(config).eventbridge().await
 }
 
-pub async fn get_event_bridge_client() -> aws_sdk_eventbridge::Client {
-    let config = AWS_CONFIG.get_or_init(build_aws_config).await;
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    use aws_sdk_dynamodb::config::{Credentials, Intercept};
+
+    use super::*;
+
+    fn local_builder() -> ClientBuilder {
+        ClientBuilder::new()
+            .region(Region::new("local"))
+            .credentials_provider(Credentials::new("toto", "tata", None, None, "test"))
+    }
+
+    #[tokio::test]
+    async fn test_dynamodb() {
+        let client = local_builder()
+            .retry_config(RetryConfig::standard().with_max_attempts(2))
+            .timeout_config(
+                TimeoutConfig::builder()
+                    .operation_timeout(Duration::from_secs(3))
+                    .build(),
+            )
+            .dynamodb()
+            .await;
+
+        let config = client.config();
+
+        assert_eq!(config.region(), Some(&Region::new("local")));
+        assert_eq!(config.retry_config().unwrap().max_attempts(), 2);
+        assert_eq!(
+            config.timeout_config().unwrap().operation_timeout(),
+            Some(Duration::from_secs(3))
+        );
+        assert!(config
+            .interceptors()
+            .any(|interceptor| interceptor.name() == "RecursionDetectionInterceptor"));
+    }
+
+    #[tokio::test]
+    async fn test_eventbridge_endpoint_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint_url = format!("http://{}", listener.local_addr().unwrap());
+
+        // answer a single request, returning its head
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut head = String::new();
+
+            // the head ends with an empty line
+            while !head.ends_with("\r\n\r\n") && reader.read_line(&mut head).unwrap() > 0 {}
+
+            (&stream)
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\n\
+                      Content-Type: application/x-amz-json-1.1\r\n\
+                      Content-Length: 2\r\n\r\n{}",
+                )
+                .unwrap();
+
+            head
+        });
+
+        let client = local_builder()
+            .endpoint_url(endpoint_url)
+            .eventbridge()
+            .await;
+
+        client.list_event_buses().send().await.unwrap();
 
-    let eventbridge_config = aws_sdk_eventbridge::config::Builder::from(config)
-        .interceptor(RecursionDetectionInterceptor::new())
-        .build();
+        let head = server.join().unwrap().to_lowercase();
 
-    aws_sdk_eventbridge::Client::from_conf(eventbridge_config)
+        assert!(head.starts_with("post / http/1.1"));
+        assert!(head.contains("x-amz-target: awsevents.listeventbuses"));
+    }
 }
@@ -0,0 +1,610 @@
+//! Settings of the lambdas, loaded from the environment once per execution environment.
+//!
+//! ```ignore
+//! let config = ConfigLoader::new().event_bus().load_env()?;
+//!
+//! init_subscriber(&config);
+//! ```
+
+use std::{env, str::FromStr, time::Duration};
+
+use aws_config::retry::RetryMode;
+use lambda_http::tracing::subscriber::{self, EnvFilter};
+use tracing::level_filters::LevelFilter;
+
+use crate::{CircuitBreakerPolicy, ConfigError, ConfigIssue, RetryPolicy, TimeoutPolicy};
+
+/// Bounds of the `limit` query parameter of the list endpoints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListLimits {
+    /// The page size when no limit is requested.
+    pub default: i32,
+    /// The largest page size a client may request.
+    pub max: i32,
+}
+
+impl Default for ListLimits {
+    fn default() -> Self {
+        ListLimits {
+            default: 50,
+            max: 100,
+        }
+    }
+}
+
+/// The validated settings of a lambda.
+#[derive(Debug, Clone)]
+pub struct Config {
+    todos_table_name: String,
+    event_bus_name: Option<String>,
+    log_level: LevelFilter,
+    list_limits: ListLimits,
+    cursor_signing_key: Option<String>,
+    dynamodb_endpoint_url: Option<String>,
+    eventbridge_endpoint_url: Option<String>,
+    retry_policy: RetryPolicy,
+    timeout_policy: TimeoutPolicy,
+    circuit_breaker_policy: Option<CircuitBreakerPolicy>,
+    port: Option<u16>,
+}
+
+impl Config {
+    pub fn todos_table_name(&self) -> &str {
+        &self.todos_table_name
+    }
+
+    /// Only loaded by the lambdas declaring it, see [`ConfigLoader::event_bus`].
+    pub fn event_bus_name(&self) -> &str {
+        self.event_bus_name
+            .as_deref()
+            .expect("EVENT_BUS_NAME not declared")
+    }
+
+    /// The level set by `AWS_LAMBDA_LOG_LEVEL` or `RUST_LOG`, `INFO` by default.
+    pub fn log_level(&self) -> LevelFilter {
+        self.log_level
+    }
+
+    pub fn list_limits(&self) -> ListLimits {
+        self.list_limits
+    }
+
+    /// Pagination cursors are only signed when a key is configured.
+    pub fn cursor_signing_key(&self) -> Option<&[u8]> {
+        self.cursor_signing_key.as_deref().map(str::as_bytes)
+    }
+
+    /// Overrides the DynamoDB endpoint, to run against a local stand-in.
+    pub fn dynamodb_endpoint_url(&self) -> Option<&str> {
+        self.dynamodb_endpoint_url.as_deref()
+    }
+
+    /// Overrides the EventBridge endpoint, to run against a local stand-in.
+    pub fn eventbridge_endpoint_url(&self) -> Option<&str> {
+        self.eventbridge_endpoint_url.as_deref()
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    pub fn timeout_policy(&self) -> TimeoutPolicy {
+        self.timeout_policy
+    }
+
+    /// Only set when `CIRCUIT_BREAKER_FAILURE_THRESHOLD` is.
+    pub fn circuit_breaker_policy(&self) -> Option<CircuitBreakerPolicy> {
+        self.circuit_breaker_policy
+    }
+
+    /// Only loaded by the local server, see [`ConfigLoader::local_server`].
+    pub fn port(&self) -> u16 {
+        self.port.expect("PORT not declared")
+    }
+}
+
+/// Initialize the tracing subscriber at the level of the config.
+///
+/// As with the lambda runtime subscriber, `RUST_LOG` may add per target directives and the
+/// logs are formatted as JSON when `AWS_LAMBDA_LOG_FORMAT` is `JSON`.
+pub fn init_subscriber(config: &Config) {
+    let collector = subscriber::fmt()
+        .with_target(false)
+        .without_time()
+        .with_env_filter(
+            EnvFilter::builder()
+                .with_default_directive(config.log_level().into())
+                .from_env_lossy(),
+        );
+
+    match env::var("AWS_LAMBDA_LOG_FORMAT") {
+        Ok(log_format) if log_format.eq_ignore_ascii_case("json") => collector.json().init(),
+        _ => collector.init(),
+    }
+}
+
+/// Loader of the [`Config`], declaring the settings a lambda requires.
+#[derive(Default)]
+pub struct ConfigLoader {
+    event_bus: bool,
+    local_server: bool,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        ConfigLoader::default()
+    }
+
+    /// Require `EVENT_BUS_NAME`.
+    pub fn event_bus(mut self) -> Self {
+        self.event_bus = true;
+        self
+    }
+
+    /// Require `PORT` and `DYNAMODB_ENDPOINT_URL`, to serve the lambdas locally.
+    pub fn local_server(mut self) -> Self {
+        self.local_server = true;
+        self
+    }
+
+    /// Load the config from the process environment.
+    pub fn load_env(self) -> Result<Config, ConfigError> {
+        self.load(|name| env::var(name).ok())
+    }
+
+    /// Load the config from the variables returned by `lookup`, reporting all the issues at once.
+    pub fn load(self, lookup: impl Fn(&str) -> Option<String>) -> Result<Config, ConfigError> {
+        let mut issues = Vec::new();
+
+        // an empty variable is as good as a missing one
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+
+        let todos_table_name = var("TODOS_TABLE_NAME");
+        if todos_table_name.is_none() {
+            issues.push(ConfigIssue::Missing {
+                name: "TODOS_TABLE_NAME",
+            });
+        }
+
+        let event_bus_name = match self.event_bus {
+            true => {
+                let event_bus_name = var("EVENT_BUS_NAME");
+                if event_bus_name.is_none() {
+                    issues.push(ConfigIssue::Missing {
+                        name: "EVENT_BUS_NAME",
+                    });
+                }
+                event_bus_name
+            }
+            false => None,
+        };
+
+        // the same precedence as the lambda runtime subscriber
+        let log_level = match var("AWS_LAMBDA_LOG_LEVEL") {
+            Some(level) => parse_log_level("AWS_LAMBDA_LOG_LEVEL", &level),
+            None => var("RUST_LOG")
+                .map(|level| parse_log_level("RUST_LOG", &level))
+                .unwrap_or(Ok(LevelFilter::INFO)),
+        }
+        .unwrap_or_else(|issue| {
+            issues.push(issue);
+            LevelFilter::INFO
+        });
+
+        let default_limits = ListLimits::default();
+        let list_limits = ListLimits {
+            default: positive(
+                &var,
+                &mut issues,
+                "LIST_DEFAULT_LIMIT",
+                default_limits.default,
+            ),
+            max: positive(&var, &mut issues, "LIST_MAX_LIMIT", default_limits.max),
+        };
+        if list_limits.default > list_limits.max {
+            issues.push(ConfigIssue::Invalid {
+                name: "LIST_DEFAULT_LIMIT",
+                reason: format!("greater than the max limit {}", list_limits.max),
+            });
+        }
+
+        let default_retry = RetryPolicy::default();
+        let retry_policy = RetryPolicy {
+            mode: var("AWS_RETRY_MODE")
+                .map(|mode| match mode.as_str() {
+                    "standard" => Ok(RetryMode::Standard),
+                    "adaptive" => Ok(RetryMode::Adaptive),
+                    _ => Err(ConfigIssue::Invalid {
+                        name: "AWS_RETRY_MODE",
+                        reason: "expected standard or adaptive".into(),
+                    }),
+                })
+                .unwrap_or(Ok(default_retry.mode))
+                .unwrap_or_else(|issue| {
+                    issues.push(issue);
+                    default_retry.mode
+                }),
+            max_attempts: positive(
+                &var,
+                &mut issues,
+                "AWS_MAX_ATTEMPTS",
+                default_retry.max_attempts,
+            ),
+            ..default_retry
+        };
+
+        let default_timeout = TimeoutPolicy::default();
+        let timeout_policy = TimeoutPolicy {
+            operation: millis(
+                &var,
+                &mut issues,
+                "AWS_OPERATION_TIMEOUT_MS",
+                default_timeout.operation,
+            ),
+            operation_attempt: millis(
+                &var,
+                &mut issues,
+                "AWS_OPERATION_ATTEMPT_TIMEOUT_MS",
+                default_timeout.operation_attempt,
+            ),
+            ..default_timeout
+        };
+
+        // the circuit breaker is enabled by its threshold
+        let circuit_breaker_policy = var("CIRCUIT_BREAKER_FAILURE_THRESHOLD").map(|_| {
+            let default_policy = CircuitBreakerPolicy::default();
+
+            CircuitBreakerPolicy {
+                failure_threshold: positive(
+                    &var,
+                    &mut issues,
+                    "CIRCUIT_BREAKER_FAILURE_THRESHOLD",
+                    default_policy.failure_threshold,
+                ),
+                open_duration: millis(
+                    &var,
+                    &mut issues,
+                    "CIRCUIT_BREAKER_OPEN_MS",
+                    default_policy.open_duration,
+                ),
+            }
+        });
+
+        let mut endpoint_url = |name| {
+            var(name).filter(|url| match is_http_url(url) {
+                true => true,
+                false => {
+                    issues.push(ConfigIssue::Invalid {
+                        name,
+                        reason: "expected an http(s) URL".into(),
+                    });
+                    false
+                }
+            })
+        };
+        let dynamodb_endpoint_url = endpoint_url("DYNAMODB_ENDPOINT_URL");
+        let eventbridge_endpoint_url = endpoint_url("EVENTBRIDGE_ENDPOINT_URL");
+
+        let port = match self.local_server {
+            true => {
+                for name in ["PORT", "DYNAMODB_ENDPOINT_URL"] {
+                    if var(name).is_none() {
+                        issues.push(ConfigIssue::Missing { name });
+                    }
+                }
+
+                Some(positive(&var, &mut issues, "PORT", 0))
+            }
+            false => None,
+        };
+
+        match (todos_table_name, issues.is_empty()) {
+            (Some(todos_table_name), true) => Ok(Config {
+                todos_table_name,
+                event_bus_name,
+                log_level,
+                list_limits,
+                cursor_signing_key: var("CURSOR_SIGNING_KEY"),
+                dynamodb_endpoint_url,
+                eventbridge_endpoint_url,
+                retry_policy,
+                timeout_policy,
+                circuit_breaker_policy,
+                port,
+            }),
+            _ => Err(ConfigError { issues }),
+        }
+    }
+}
+
+/// The level of an env filter, such as `info` or `debug,hyper=info`.
+///
+/// Only the global level is validated, the per target directives are left to the subscriber.
+fn parse_log_level(name: &'static str, value: &str) -> Result<LevelFilter, ConfigIssue> {
+    value
+        .split(',')
+        .filter(|directive| !directive.contains('='))
+        .try_fold(LevelFilter::INFO, |_, level| {
+            LevelFilter::from_str(level.trim()).map_err(|_| ConfigIssue::Invalid {
+                name,
+                reason: format!("unknown log level {level}"),
+            })
+        })
+}
+
+/// A positive number, or the default when unset.
+fn positive<T>(
+    var: &impl Fn(&str) -> Option<String>,
+    issues: &mut Vec<ConfigIssue>,
+    name: &'static str,
+    default: T,
+) -> T
+where
+    T: FromStr + Default + PartialOrd,
+{
+    match var(name) {
+        Some(value) => match value
+            .parse::<T>()
+            .ok()
+            .filter(|value| *value > T::default())
+        {
+            Some(value) => value,
+            None => {
+                issues.push(ConfigIssue::Invalid {
+                    name,
+                    reason: "expected a positive number".into(),
+                });
+                default
+            }
+        },
+        None => default,
+    }
+}
+
+/// A positive duration in milliseconds, or the default when unset.
+fn millis(
+    var: &impl Fn(&str) -> Option<String>,
+    issues: &mut Vec<ConfigIssue>,
+    name: &'static str,
+    default: Duration,
+) -> Duration {
+    let default_millis = default.as_millis() as u64;
+
+    Duration::from_millis(positive(var, issues, name, default_millis))
+}
+
+fn is_http_url(url: &str) -> bool {
+    ["http://", "https://"]
+        .iter()
+        .any(|scheme| url.len() > scheme.len() && url.starts_with(scheme))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn load(loader: ConfigLoader, vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        loader.load(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_load_defaults() {
+        let config = load(ConfigLoader::new(), &[("TODOS_TABLE_NAME", "toto")]).unwrap();
+
+        assert_eq!(config.todos_table_name(), "toto");
+        assert_eq!(config.log_level(), LevelFilter::INFO);
+        assert_eq!(config.list_limits(), ListLimits::default());
+        assert_eq!(config.cursor_signing_key(), None);
+        assert_eq!(config.dynamodb_endpoint_url(), None);
+        assert_eq!(config.eventbridge_endpoint_url(), None);
+        assert_eq!(config.retry_policy(), RetryPolicy::default());
+        assert_eq!(config.timeout_policy(), TimeoutPolicy::default());
+        assert_eq!(config.circuit_breaker_policy(), None);
+    }
+
+    #[test]
+    fn test_load() {
+        let config = load(
+            ConfigLoader::new().event_bus(),
+            &[
+                ("TODOS_TABLE_NAME", "toto"),
+                ("EVENT_BUS_NAME", "tata"),
+                ("AWS_LAMBDA_LOG_LEVEL", "debug"),
+                ("RUST_LOG", "error"),
+                ("LIST_DEFAULT_LIMIT", "10"),
+                ("LIST_MAX_LIMIT", "20"),
+                ("CURSOR_SIGNING_KEY", "secret"),
+                ("DYNAMODB_ENDPOINT_URL", "http://localhost:8000"),
+                ("EVENTBRIDGE_ENDPOINT_URL", "https://localhost:4566"),
+                ("AWS_RETRY_MODE", "adaptive"),
+                ("AWS_MAX_ATTEMPTS", "5"),
+                ("AWS_OPERATION_TIMEOUT_MS", "1500"),
+                ("AWS_OPERATION_ATTEMPT_TIMEOUT_MS", "500"),
+                ("CIRCUIT_BREAKER_FAILURE_THRESHOLD", "3"),
+                ("CIRCUIT_BREAKER_OPEN_MS", "2000"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.event_bus_name(), "tata");
+        assert_eq!(config.log_level(), LevelFilter::DEBUG);
+        assert_eq!(
+            config.list_limits(),
+            ListLimits {
+                default: 10,
+                max: 20
+            }
+        );
+        assert_eq!(config.cursor_signing_key(), Some(b"secret".as_slice()));
+        assert_eq!(
+            config.dynamodb_endpoint_url(),
+            Some("http://localhost:8000")
+        );
+        assert_eq!(
+            config.eventbridge_endpoint_url(),
+            Some("https://localhost:4566")
+        );
+        assert_eq!(
+            config.retry_policy(),
+            RetryPolicy {
+                mode: RetryMode::Adaptive,
+                max_attempts: 5,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            config.timeout_policy(),
+            TimeoutPolicy {
+                operation: Duration::from_millis(1500),
+                operation_attempt: Duration::from_millis(500),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            config.circuit_breaker_policy(),
+            Some(CircuitBreakerPolicy {
+                failure_threshold: 3,
+                open_duration: Duration::from_secs(2),
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_local_server() {
+        let config = load(
+            ConfigLoader::new().local_server(),
+            &[
+                ("TODOS_TABLE_NAME", "toto"),
+                ("PORT", "3000"),
+                ("DYNAMODB_ENDPOINT_URL", "http://localhost:8000"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.port(), 3000);
+        assert_eq!(
+            config.dynamodb_endpoint_url(),
+            Some("http://localhost:8000")
+        );
+
+        let err = load(
+            ConfigLoader::new().local_server(),
+            &[("TODOS_TABLE_NAME", "toto"), ("PORT", "65536")],
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.issues,
+            vec![
+                ConfigIssue::Missing {
+                    name: "DYNAMODB_ENDPOINT_URL"
+                },
+                ConfigIssue::Invalid {
+                    name: "PORT",
+                    reason: "expected a positive number".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_env_filter() {
+        let config = load(
+            ConfigLoader::new(),
+            &[
+                ("TODOS_TABLE_NAME", "toto"),
+                ("RUST_LOG", "warn,hyper=info"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.log_level(), LevelFilter::WARN);
+    }
+
+    #[test]
+    fn test_load_reports_all_issues() {
+        let err = load(
+            ConfigLoader::new().event_bus(),
+            &[
+                ("TODOS_TABLE_NAME", ""),
+                ("RUST_LOG", "loud"),
+                ("LIST_DEFAULT_LIMIT", "0"),
+                ("LIST_MAX_LIMIT", "many"),
+                ("DYNAMODB_ENDPOINT_URL", "localhost:8000"),
+                ("AWS_RETRY_MODE", "legacy"),
+                ("AWS_OPERATION_TIMEOUT_MS", "-1"),
+            ],
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.issues,
+            vec![
+                ConfigIssue::Missing {
+                    name: "TODOS_TABLE_NAME"
+                },
+                ConfigIssue::Missing {
+                    name: "EVENT_BUS_NAME"
+                },
+                ConfigIssue::Invalid {
+                    name: "RUST_LOG",
+                    reason: "unknown log level loud".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "LIST_DEFAULT_LIMIT",
+                    reason: "expected a positive number".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "LIST_MAX_LIMIT",
+                    reason: "expected a positive number".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "AWS_RETRY_MODE",
+                    reason: "expected standard or adaptive".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "AWS_OPERATION_TIMEOUT_MS",
+                    reason: "expected a positive number".into()
+                },
+                ConfigIssue::Invalid {
+                    name: "DYNAMODB_ENDPOINT_URL",
+                    reason: "expected an http(s) URL".into()
+                },
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid config: missing TODOS_TABLE_NAME, missing EVENT_BUS_NAME, \
+             invalid RUST_LOG: unknown log level loud, \
+             invalid LIST_DEFAULT_LIMIT: expected a positive number, \
+             invalid LIST_MAX_LIMIT: expected a positive number, \
+             invalid AWS_RETRY_MODE: expected standard or adaptive, \
+             invalid AWS_OPERATION_TIMEOUT_MS: expected a positive number, \
+             invalid DYNAMODB_ENDPOINT_URL: expected an http(s) URL"
+        );
+    }
+
+    #[test]
+    fn test_load_default_limit_above_max() {
+        let err = load(
+            ConfigLoader::new(),
+            &[("TODOS_TABLE_NAME", "toto"), ("LIST_MAX_LIMIT", "20")],
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.issues,
+            vec![ConfigIssue::Invalid {
+                name: "LIST_DEFAULT_LIMIT",
+                reason: "greater than the max limit 20".into()
+            }]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+
+use crate::CursorError;
+
+/// Encode a DynamoDB `LastEvaluatedKey` into an opaque pagination cursor.
+///
+/// The cursor is the base64 encoded JSON of the key's string attributes. When a
+/// signing key is given, an HMAC-SHA256 signature is appended after a `.` so that
+/// clients cannot forge cursors.
+pub fn encode_cursor(
+    key: &HashMap<String, AttributeValue>,
+    signing_key: Option<&[u8]>,
+) -> Result<String, CursorError> {
+    let key = key
+        .iter()
+        .map(|(attribute, value)| match value {
+            AttributeValue::S(value) => Ok((attribute.as_str(), value.as_str())),
+            _ => Err(CursorError::UnsupportedAttribute {
+                attribute: attribute.clone(),
+            }),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let payload = serde_json::to_vec(&key).map_err(|_| CursorError::InvalidEncoding)?;
+    let payload = URL_SAFE_NO_PAD.encode(payload);
+
+    match signing_key {
+        Some(signing_key) => {
+            let signature = sign(&payload, signing_key)?.finalize().into_bytes();
+
+            Ok(format!("{payload}.{}", URL_SAFE_NO_PAD.encode(signature)))
+        }
+        None => Ok(payload),
+    }
+}
+
+/// Decode a cursor produced by [`encode_cursor`] back into an `ExclusiveStartKey`.
+///
+/// When a signing key is given, the signature is required and verified.
+pub fn decode_cursor(
+    cursor: &str,
+    signing_key: Option<&[u8]>,
+) -> Result<HashMap<String, AttributeValue>, CursorError> {
+    let payload = match (signing_key, cursor.split_once('.')) {
+        (Some(signing_key), Some((payload, signature))) => {
+            let signature = URL_SAFE_NO_PAD
+                .decode(signature)
+                .map_err(|_| CursorError::InvalidSignature)?;
+
+            sign(payload, signing_key)?
+                .verify_slice(&signature)
+                .map_err(|_| CursorError::InvalidSignature)?;
+
+            payload
+        }
+        (Some(_), None) => return Err(CursorError::InvalidSignature),
+        (None, Some(_)) => return Err(CursorError::InvalidEncoding),
+        (None, None) => cursor,
+    };
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| CursorError::InvalidEncoding)?;
+
+    let key: HashMap<String, String> =
+        serde_json::from_slice(&payload).map_err(|_| CursorError::InvalidEncoding)?;
+
+    Ok(key
+        .into_iter()
+        .map(|(attribute, value)| (attribute, AttributeValue::S(value)))
+        .collect())
+}
+
+fn sign(payload: &str, signing_key: &[u8]) -> Result<Hmac<Sha256>, CursorError> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(signing_key).map_err(|_| CursorError::InvalidSignature)?;
+    mac.update(payload.as_bytes());
+
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_evaluated_key() -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("PK".into(), AttributeValue::S("TODO#toto".into())),
+            ("SK".into(), AttributeValue::S("ID#tata".into())),
+        ])
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let cursor = encode_cursor(&last_evaluated_key(), None).unwrap();
+
+        assert_eq!(decode_cursor(&cursor, None).unwrap(), last_evaluated_key());
+    }
+
+    #[test]
+    fn test_signed_roundtrip() {
+        let cursor = encode_cursor(&last_evaluated_key(), Some(b"secret")).unwrap();
+
+        assert_eq!(
+            decode_cursor(&cursor, Some(b"secret")).unwrap(),
+            last_evaluated_key()
+        );
+    }
+
+    #[test]
+    fn test_tampered_cursor() {
+        let cursor = encode_cursor(&last_evaluated_key(), Some(b"secret")).unwrap();
+        let (_, signature) = cursor.split_once('.').unwrap();
+
+        let forged_key = HashMap::from([
+            ("PK".into(), AttributeValue::S("TODO#other".into())),
+            ("SK".into(), AttributeValue::S("ID#tata".into())),
+        ]);
+        let forged_payload = encode_cursor(&forged_key, None).unwrap();
+
+        assert!(matches!(
+            decode_cursor(&format!("{forged_payload}.{signature}"), Some(b"secret")),
+            Err(CursorError::InvalidSignature)
+        ));
+        assert!(matches!(
+            decode_cursor(&forged_payload, Some(b"secret")),
+            Err(CursorError::InvalidSignature)
+        ));
+        assert!(matches!(
+            decode_cursor(&cursor, Some(b"other secret")),
+            Err(CursorError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        assert!(matches!(
+            decode_cursor("not a cursor!", None),
+            Err(CursorError::InvalidEncoding)
+        ));
+    }
+}
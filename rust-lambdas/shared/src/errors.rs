@@ -1,15 +1,36 @@
 use aws_lambda_events::http::StatusCode;
 
+use crate::ErrorCode;
+
+/// An HTTP handler failure, sent to the client as an [`crate::ErrorResponse`].
 #[derive(Debug)]
 pub struct FailureResponse {
     pub status_code: StatusCode,
-    pub body: String,
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl FailureResponse {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        FailureResponse {
+            status_code: code.status_code(),
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
 }
 
 // Implement Display for the Failure response so that we can then implement Error.
 impl std::fmt::Display for FailureResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.body)
+        write!(f, "{}", self.message)
     }
 }
 
@@ -26,3 +47,100 @@ pub enum DynamoDBError {
     #[error("invalid attribute {attribute}")]
     InvalidAttribute { attribute: String },
 }
+
+impl DynamoDBError {
+    /// Prefix the attribute of an error raised while reading a nested item.
+    pub fn nested(attribute: &str, err: DynamoDBError) -> DynamoDBError {
+        match err {
+            DynamoDBError::MissingAttribute { attribute: nested } => {
+                DynamoDBError::MissingAttribute {
+                    attribute: format!("{attribute}.{nested}"),
+                }
+            }
+            DynamoDBError::InvalidAttribute { attribute: nested } => {
+                DynamoDBError::InvalidAttribute {
+                    attribute: format!("{attribute}.{nested}"),
+                }
+            }
+            err => err,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CursorError {
+    #[error("invalid cursor encoding")]
+    InvalidEncoding,
+    #[error("invalid cursor signature")]
+    InvalidSignature,
+    #[error("unsupported key attribute {attribute}")]
+    UnsupportedAttribute { attribute: String },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum KeyError {
+    #[error("invalid id {id}")]
+    InvalidId { id: String },
+    #[error("missing key attribute {attribute}")]
+    MissingAttribute { attribute: String },
+    #[error("invalid key attribute {attribute}")]
+    InvalidAttribute { attribute: String },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum IdempotencyError {
+    #[error(transparent)]
+    Key(#[from] KeyError),
+    #[error(transparent)]
+    Build(#[from] aws_sdk_dynamodb::error::BuildError),
+    #[error(transparent)]
+    DynamoDB(#[from] aws_sdk_dynamodb::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EventError {
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    EventBridge(#[from] aws_sdk_eventbridge::Error),
+    #[error("failed entry {code}: {message}")]
+    FailedEntry { code: String, message: String },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RepositoryError {
+    #[error("item not found")]
+    NotFound,
+    #[error("conflicting write")]
+    Conflict,
+    /// The list of the todo is archived, or was deleted, when the todo was written.
+    #[error("list archived or deleted")]
+    ListClosed,
+    #[error("{count} items left unprocessed")]
+    Unprocessed { count: usize },
+    #[error(transparent)]
+    Key(#[from] KeyError),
+    #[error(transparent)]
+    Item(#[from] DynamoDBError),
+    #[error(transparent)]
+    Idempotency(#[from] IdempotencyError),
+    #[error(transparent)]
+    Build(#[from] aws_sdk_dynamodb::error::BuildError),
+    #[error(transparent)]
+    DynamoDB(#[from] aws_sdk_dynamodb::Error),
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ConfigIssue {
+    #[error("missing {name}")]
+    Missing { name: &'static str },
+    #[error("invalid {name}: {reason}")]
+    Invalid { name: &'static str, reason: String },
+}
+
+/// Every issue found while loading the [`crate::config::Config`].
+#[derive(thiserror::Error, Debug)]
+#[error("invalid config: {}", .issues.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct ConfigError {
+    pub issues: Vec<ConfigIssue>,
+}
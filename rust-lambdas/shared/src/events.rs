@@ -0,0 +1,133 @@
+use aws_sdk_eventbridge::types::PutEventsRequestEntry;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use crate::{EventError, List, ListDeletion, ListMember, Todo, TodoUpdate};
+
+/// Source of the events published to EventBridge.
+pub const EVENT_SOURCE: &str = "api.todos";
+
+/// Version of the event data, bumped on breaking changes.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// The catalog of the events published by the API.
+///
+/// The tag is also used as the EventBridge `detail-type`.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TodoEvent {
+    TodoCreated(Todo),
+    TodoUpdated(TodoUpdate),
+    TodoCompleted(Todo),
+    TodoReopened(Todo),
+    TodoDeleted(Todo),
+    ListCreated(List),
+    /// The list was renamed, archived or unarchived.
+    ListUpdated(List),
+    /// The deletion of the list and its todos was requested, see [`ListDeletion`].
+    ListDeletionRequested(ListDeletion),
+    /// The list was deleted along with its todos, published once per list.
+    ListDeleted(List),
+    /// A user was invited to the list, or their role changed.
+    ListShared(ListMember),
+    MemberRemoved(ListMember),
+}
+
+impl TodoEvent {
+    pub fn detail_type(&self) -> &'static str {
+        match self {
+            TodoEvent::TodoCreated(_) => "TODO_CREATED",
+            TodoEvent::TodoUpdated(_) => "TODO_UPDATED",
+            TodoEvent::TodoCompleted(_) => "TODO_COMPLETED",
+            TodoEvent::TodoReopened(_) => "TODO_REOPENED",
+            TodoEvent::TodoDeleted(_) => "TODO_DELETED",
+            TodoEvent::ListCreated(_) => "LIST_CREATED",
+            TodoEvent::ListUpdated(_) => "LIST_UPDATED",
+            TodoEvent::ListDeletionRequested(_) => "LIST_DELETION_REQUESTED",
+            TodoEvent::ListDeleted(_) => "LIST_DELETED",
+            TodoEvent::ListShared(_) => "LIST_SHARED",
+            TodoEvent::MemberRemoved(_) => "MEMBER_REMOVED",
+        }
+    }
+}
+
+/// The `detail` of the published events.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Unique id of the event, kept when the event is delivered more than once.
+    pub id: String,
+    pub version: u32,
+    #[serde(flatten)]
+    pub event: TodoEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(event: TodoEvent) -> Self {
+        EventEnvelope {
+            id: Ulid::new().to_string(),
+            version: EVENT_SCHEMA_VERSION,
+            event,
+        }
+    }
+
+    pub fn detail_type(&self) -> &'static str {
+        self.event.detail_type()
+    }
+}
+
+/// Publish a single event to the bus.
+pub async fn publish(
+    eventbridge_client: &aws_sdk_eventbridge::Client,
+    event_bus_name: &str,
+    envelope: &EventEnvelope,
+) -> Result<(), EventError> {
+    let entry = PutEventsRequestEntry::builder()
+        .event_bus_name(event_bus_name)
+        .source(EVENT_SOURCE)
+        .detail_type(envelope.detail_type())
+        .detail(serde_json::to_string(envelope)?)
+        .build();
+
+    let res = eventbridge_client
+        .put_events()
+        .entries(entry)
+        .send()
+        .await
+        .map_err(aws_sdk_eventbridge::Error::from)?;
+
+    match res.entries().first() {
+        Some(entry) if res.failed_entry_count > 0 => Err(EventError::FailedEntry {
+            code: entry.error_code().unwrap_or_default().into(),
+            message: entry.error_message().unwrap_or_default().into(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::todo;
+    use serde_json::json;
+
+    #[test]
+    fn test_envelope() {
+        let envelope = EventEnvelope::new(TodoEvent::TodoCreated(todo("toto", "tata")));
+
+        let detail = serde_json::to_value(&envelope).unwrap();
+
+        assert_eq!(envelope.detail_type(), "TODO_CREATED");
+        assert_eq!(detail["version"], json!(1));
+        assert_eq!(detail["type"], json!("TODO_CREATED"));
+        assert_eq!(detail["data"]["list_id"], json!("toto"));
+
+        let envelope: EventEnvelope = serde_json::from_value(detail).unwrap();
+
+        assert!(matches!(envelope.event, TodoEvent::TodoCreated(todo) if todo.id == "tata"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Runtime shared by the HTTP lambdas.
+//!
+//! ```ignore
+//! HttpLambda::new()
+//!     .dynamodb()
+//!     .run(|request, resources| {
+//!         handler(
+//!             request,
+//!             resources.dynamodb_client(),
+//!             resources.config().todos_table_name(),
+//!         )
+//!     })
+//!     .await
+//! ```
+
+use std::{
+    env,
+    future::Future,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use aws_lambda_events::http::StatusCode;
+use chrono::Utc;
+use lambda_http::{service_fn, Error, Request};
+use serde_json::json;
+use tracing::{debug, error, info};
+
+use crate::{
+    config::{init_subscriber, Config, ConfigLoader},
+    respond, CircuitBreaker, ClientBuilder, ErrorCode, FailureResponse,
+};
+
+/// CloudWatch namespace of the metrics emitted by the lambdas.
+pub const METRICS_NAMESPACE: &str = "TodosApi";
+
+/// The clients and config declared by a lambda, initialized once per execution environment.
+pub struct Resources {
+    dynamodb_client: Option<aws_sdk_dynamodb::Client>,
+    eventbridge_client: Option<aws_sdk_eventbridge::Client>,
+    config: Config,
+    circuit_breaker: Option<CircuitBreaker>,
+}
+
+impl Resources {
+    pub fn dynamodb_client(&self) -> &aws_sdk_dynamodb::Client {
+        self.dynamodb_client
+            .as_ref()
+            .expect("DynamoDB client not declared")
+    }
+
+    pub fn eventbridge_client(&self) -> &aws_sdk_eventbridge::Client {
+        self.eventbridge_client
+            .as_ref()
+            .expect("EventBridge client not declared")
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+/// Builder of an HTTP lambda.
+pub struct HttpLambda {
+    start: Instant,
+    dynamodb: bool,
+    eventbridge: bool,
+}
+
+impl Default for HttpLambda {
+    fn default() -> Self {
+        HttpLambda::new()
+    }
+}
+
+impl HttpLambda {
+    /// Create the builder first thing in `main`, the init duration is measured from here.
+    pub fn new() -> Self {
+        HttpLambda {
+            start: Instant::now(),
+            dynamodb: false,
+            eventbridge: false,
+        }
+    }
+
+    pub fn dynamodb(mut self) -> Self {
+        self.dynamodb = true;
+        self
+    }
+
+    /// Declare the EventBridge client, `EVENT_BUS_NAME` is then required.
+    pub fn eventbridge(mut self) -> Self {
+        self.eventbridge = true;
+        self
+    }
+
+    /// Initialize the declared resources and serve the requests with the handler.
+    ///
+    /// Failures are converted by [`respond`], and panics are reported as a 500 instead
+    /// of crashing the execution environment.
+    pub async fn run<F, Fut>(self, handler: F) -> Result<(), Error>
+    where
+        F: Fn(Request, &'static Resources) -> Fut + Sync,
+        Fut: Future<Output = Result<(StatusCode, serde_json::Value), FailureResponse>>
+            + Send
+            + 'static,
+    {
+        let config = match self.eventbridge {
+            true => ConfigLoader::new().event_bus(),
+            false => ConfigLoader::new(),
+        }
+        .load_env()?;
+
+        init_subscriber(&config);
+
+        let circuit_breaker = config.circuit_breaker_policy().map(CircuitBreaker::new);
+
+        let client_builder = match &circuit_breaker {
+            Some(circuit_breaker) => {
+                ClientBuilder::from_config(&config).circuit_breaker(circuit_breaker.clone())
+            }
+            None => ClientBuilder::from_config(&config),
+        };
+
+        let resources = Resources {
+            dynamodb_client: match self.dynamodb {
+                true => Some(client_builder.dynamodb().await),
+                false => None,
+            },
+            eventbridge_client: match self.eventbridge {
+                true => Some(client_builder.eventbridge().await),
+                false => None,
+            },
+            config,
+            circuit_breaker,
+        };
+
+        // the resources live as long as the execution environment
+        let resources: &'static Resources = Box::leak(Box::new(resources));
+
+        let init_duration = self.start.elapsed();
+
+        debug!("Lambda initialized in {:.2?}", init_duration);
+
+        let cold_start = AtomicBool::new(true);
+        let handler = &handler;
+        let cold_start = &cold_start;
+
+        lambda_http::run(service_fn(|request| async move {
+            if cold_start.swap(false, Ordering::Relaxed) {
+                emit_cold_start_metrics(init_duration);
+            }
+
+            invoke(request, resources, handler).await
+        }))
+        .await
+    }
+}
+
+async fn invoke<F, Fut>(
+    request: Request,
+    resources: &'static Resources,
+    handler: &F,
+) -> Result<(StatusCode, serde_json::Value), Error>
+where
+    F: Fn(Request, &'static Resources) -> Fut + Sync,
+    Fut: Future<Output = Result<(StatusCode, serde_json::Value), FailureResponse>> + Send + 'static,
+{
+    let start = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let res = respond(request, |request| async move {
+        // no need to wait for the timeouts of a failing downstream service
+        if let Some(circuit_breaker) = &resources.circuit_breaker {
+            circuit_breaker.check()?;
+        }
+
+        // a panicking task does not take the runtime down with it
+        tokio::spawn(handler(request, resources))
+            .await
+            .unwrap_or_else(|err| {
+                error!(err = ?err, "Handler panicked");
+
+                Err(FailureResponse::new(
+                    ErrorCode::InternalError,
+                    "Internal error",
+                ))
+            })
+    })
+    .await?;
+
+    info!(
+        method = %method,
+        path = path,
+        status = res.0.as_u16(),
+        duration = ?start.elapsed(),
+        "Handled request",
+    );
+
+    Ok(res)
+}
+
+/// Log the cold start in the CloudWatch embedded metric format.
+fn emit_cold_start_metrics(init_duration: Duration) {
+    let function_name = env::var("AWS_LAMBDA_FUNCTION_NAME").unwrap_or_default();
+
+    println!(
+        "{}",
+        json!({
+            "_aws": {
+                "Timestamp": Utc::now().timestamp_millis(),
+                "CloudWatchMetrics": [{
+                    "Namespace": METRICS_NAMESPACE,
+                    "Dimensions": [["FunctionName"]],
+                    "Metrics": [
+                        { "Name": "ColdStart", "Unit": "Count" },
+                        { "Name": "InitDuration", "Unit": "Milliseconds" },
+                    ],
+                }],
+            },
+            "FunctionName": function_name,
+            "ColdStart": 1,
+            "InitDuration": init_duration.as_secs_f64() * 1000.0,
+        })
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::ApiGatewayRequest, CircuitBreakerPolicy};
+
+    fn get_request() -> Request {
+        ApiGatewayRequest::new("GET").build()
+    }
+
+    fn resources(circuit_breaker: Option<CircuitBreaker>) -> &'static Resources {
+        let config = ConfigLoader::new()
+            .load(|name| (name == "TODOS_TABLE_NAME").then(|| "toto".into()))
+            .unwrap();
+
+        Box::leak(Box::new(Resources {
+            dynamodb_client: None,
+            eventbridge_client: None,
+            config,
+            circuit_breaker,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_invoke() {
+        let handler = |_, resources: &'static Resources| async move {
+            Ok((StatusCode::OK, json!(resources.config().todos_table_name())))
+        };
+
+        let (status, body) = invoke(get_request(), resources(None), &handler)
+            .await
+            .unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(body, json!("toto"));
+    }
+
+    #[tokio::test]
+    async fn test_invoke_panic() {
+        let handler = |_, resources: &'static Resources| async move {
+            // the client was not declared
+            resources.dynamodb_client();
+
+            Ok((StatusCode::OK, json!({})))
+        };
+
+        let (status, body) = invoke(get_request(), resources(None), &handler)
+            .await
+            .unwrap();
+
+        assert_eq!(status, 500);
+        assert_eq!(body["code"], json!("INTERNAL_ERROR"));
+        assert_eq!(body["request_id"], json!("request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_invoke_circuit_breaker_open() {
+        let circuit_breaker = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            ..Default::default()
+        });
+        circuit_breaker.record_failure();
+
+        let handler = |_, _: &'static Resources| async move {
+            panic!("the handler should not be called");
+        };
+
+        let (status, body) = invoke(get_request(), resources(Some(circuit_breaker)), &handler)
+            .await
+            .unwrap();
+
+        assert_eq!(status, 503);
+        assert_eq!(body["code"], json!("SERVICE_UNAVAILABLE"));
+    }
+}
@@ -0,0 +1,238 @@
+use aws_sdk_dynamodb::{
+    operation::transact_write_items::TransactWriteItemsError,
+    types::{AttributeValue, ConditionCheck, Put, TransactWriteItem, Update},
+};
+use chrono::{TimeDelta, Utc};
+use tracing::info;
+
+use crate::{
+    keys::{ProcessedEventKey, TableKey},
+    IdempotencyError,
+};
+
+/// How long processed events are remembered. EventBridge retries deliveries for up to 24 hours.
+pub const PROCESSED_EVENT_TTL: TimeDelta = TimeDelta::days(2);
+
+/// Name of the TTL attribute of the processed event records.
+pub const EXPIRES_AT_ATTRIBUTE: &str = "expiresAt";
+
+#[derive(Debug, PartialEq)]
+pub enum Processing {
+    Processed,
+    Duplicate,
+    /// The condition check of the update failed, nothing was applied nor recorded.
+    Skipped,
+}
+
+/// Apply `update` at most once per event and consumer.
+///
+/// The event id is recorded with a conditional put in the same transaction as the
+/// update, so that a duplicate delivery is a no-op while a failed update can still
+/// be retried. The update is skipped when `condition_check`, on another item, fails.
+pub async fn update_once(
+    dynamodb_client: &aws_sdk_dynamodb::Client,
+    table_name: &str,
+    consumer: &str,
+    event_id: &str,
+    update: Update,
+    condition_check: Option<ConditionCheck>,
+) -> Result<Processing, IdempotencyError> {
+    let key = ProcessedEventKey::new(event_id, consumer)?;
+
+    let expires_at = (Utc::now() + PROCESSED_EVENT_TTL).timestamp();
+
+    let record = Put::builder()
+        .table_name(table_name)
+        .set_item(Some(key.to_key()))
+        .item(
+            EXPIRES_AT_ATTRIBUTE,
+            AttributeValue::N(expires_at.to_string()),
+        )
+        .condition_expression("attribute_not_exists(PK)")
+        .build()?;
+
+    let mut transact_items = vec![
+        TransactWriteItem::builder().put(record).build(),
+        TransactWriteItem::builder().update(update).build(),
+    ];
+
+    if let Some(condition_check) = condition_check {
+        transact_items.push(
+            TransactWriteItem::builder()
+                .condition_check(condition_check)
+                .build(),
+        );
+    }
+
+    let res = dynamodb_client
+        .transact_write_items()
+        .set_transact_items(Some(transact_items))
+        .send()
+        .await;
+
+    let Err(err) = res else {
+        return Ok(Processing::Processed);
+    };
+
+    let cancelled_by_condition = |index: usize| match err.as_service_error() {
+        Some(TransactWriteItemsError::TransactionCanceledException(exception)) => {
+            exception
+                .cancellation_reasons()
+                .get(index)
+                .and_then(|reason| reason.code())
+                == Some("ConditionalCheckFailed")
+        }
+        _ => false,
+    };
+
+    // the record put comes first in the transaction, the condition check last
+    if cancelled_by_condition(0) {
+        info!(
+            event_id = event_id,
+            consumer = consumer,
+            "Event already processed"
+        );
+
+        Ok(Processing::Duplicate)
+    } else if cancelled_by_condition(2) {
+        info!(
+            event_id = event_id,
+            consumer = consumer,
+            "Event skipped by the condition check"
+        );
+
+        Ok(Processing::Skipped)
+    } else {
+        Err(aws_sdk_dynamodb::Error::from(err).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_dynamodb::{
+        operation::transact_write_items::TransactWriteItemsOutput,
+        types::{error::TransactionCanceledException, CancellationReason},
+    };
+    use aws_smithy_mocks::{mock, mock_client};
+
+    fn update() -> Update {
+        Update::builder()
+            .table_name("toto")
+            .key("PK", AttributeValue::S("TODO#toto".into()))
+            .key("SK", AttributeValue::S("COUNTER".into()))
+            .update_expression("ADD todosCount :todos_count")
+            .expression_attribute_values(":todos_count", AttributeValue::N("1".into()))
+            .build()
+            .unwrap()
+    }
+
+    fn cancelled(codes: &[&'static str]) -> TransactWriteItemsError {
+        TransactWriteItemsError::TransactionCanceledException(
+            TransactionCanceledException::builder()
+                .set_cancellation_reasons(Some(
+                    codes
+                        .iter()
+                        .map(|code| CancellationReason::builder().code(*code).build())
+                        .collect(),
+                ))
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_update_once() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let expires_at = (Utc::now() + PROCESSED_EVENT_TTL).timestamp();
+
+                matches!(req.transact_items(), [record, update]
+                if record.put().is_some_and(|put| {
+                    put.item().get("PK") == Some(&AttributeValue::S("EVENT#tata".into()))
+                        && put.item().get("SK")
+                            == Some(&AttributeValue::S("CONSUMER#toto".into()))
+                        && put.condition_expression() == Some("attribute_not_exists(PK)")
+                        && put
+                            .item()
+                            .get(EXPIRES_AT_ATTRIBUTE)
+                            .and_then(|expires| expires.as_n().ok())
+                            .and_then(|expires| expires.parse::<i64>().ok())
+                            .is_some_and(|expires| (expires_at - expires).abs() < 60)
+                })
+                && update.update().is_some_and(|update| {
+                    update.update_expression() == "ADD todosCount :todos_count"
+                }))
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+
+        let processing = update_once(&dynamodb_client, "toto", "toto", "tata", update(), None)
+            .await
+            .expect("failed to update");
+
+        assert_eq!(processing, Processing::Processed);
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_once_duplicate() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| cancelled(&["ConditionalCheckFailed", "None"]));
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+
+        let processing = update_once(&dynamodb_client, "toto", "toto", "tata", update(), None)
+            .await
+            .expect("a duplicate should not fail");
+
+        assert_eq!(processing, Processing::Duplicate);
+    }
+
+    #[tokio::test]
+    async fn test_update_once_update_failure() {
+        // the update was cancelled, not the record, so the event must be retried
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| cancelled(&["None", "ConditionalCheckFailed"]));
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+
+        let err = update_once(&dynamodb_client, "toto", "toto", "tata", update(), None)
+            .await
+            .expect_err("the cancelled update should fail");
+
+        assert!(matches!(err, IdempotencyError::DynamoDB(_)));
+    }
+
+    #[tokio::test]
+    async fn test_update_once_skipped() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                matches!(req.transact_items(), [_, _, check]
+                if check.condition_check().is_some_and(|check| {
+                    check.condition_expression() == "attribute_exists(PK)"
+                }))
+            })
+            .then_error(|| cancelled(&["None", "None", "ConditionalCheckFailed"]));
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+
+        let condition_check = ConditionCheck::builder()
+            .table_name("toto")
+            .key("PK", AttributeValue::S("LIST#toto".into()))
+            .key("SK", AttributeValue::S("LIST".into()))
+            .condition_expression("attribute_exists(PK)")
+            .build()
+            .unwrap();
+
+        let processing = update_once(
+            &dynamodb_client,
+            "toto",
+            "toto",
+            "tata",
+            update(),
+            Some(condition_check),
+        )
+        .await
+        .expect("a failed condition check should not fail");
+
+        assert_eq!(processing, Processing::Skipped);
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+}
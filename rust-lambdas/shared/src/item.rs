@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+pub use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{DateTime, Utc};
+pub use dynamo_item_derive::DynamoItem;
+
+use crate::DynamoDBError;
+
+/// An entity that is stored as a DynamoDB item.
+///
+/// Use `#[derive(DynamoItem)]` rather than implementing it by hand.
+pub trait DynamoItem: Sized {
+    fn to_item(&self) -> HashMap<String, AttributeValue>;
+
+    fn from_item(item: HashMap<String, AttributeValue>) -> Result<Self, DynamoDBError>;
+}
+
+/// A value that is stored as a single DynamoDB attribute.
+pub trait DynamoAttribute: Sized {
+    /// Returns `None` when the attribute should not be written at all.
+    fn to_attribute(&self) -> Option<AttributeValue>;
+
+    /// `value` is `None` when the attribute is missing from the item.
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError>;
+}
+
+fn required<'a>(
+    value: Option<&'a AttributeValue>,
+    attribute: &str,
+) -> Result<&'a AttributeValue, DynamoDBError> {
+    value.ok_or_else(|| DynamoDBError::MissingAttribute {
+        attribute: attribute.into(),
+    })
+}
+
+fn invalid(attribute: &str) -> DynamoDBError {
+    DynamoDBError::InvalidAttribute {
+        attribute: attribute.into(),
+    }
+}
+
+impl DynamoAttribute for AttributeValue {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(self.clone())
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute).cloned()
+    }
+}
+
+impl DynamoAttribute for String {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::S(self.clone()))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute)?
+            .as_s()
+            .cloned()
+            .map_err(|_| invalid(attribute))
+    }
+}
+
+impl DynamoAttribute for bool {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::Bool(*self))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute)?
+            .as_bool()
+            .copied()
+            .map_err(|_| invalid(attribute))
+    }
+}
+
+macro_rules! impl_number_attribute {
+    ($($number:ty),*) => {
+        $(
+            impl DynamoAttribute for $number {
+                fn to_attribute(&self) -> Option<AttributeValue> {
+                    Some(AttributeValue::N(self.to_string()))
+                }
+
+                fn from_attribute(
+                    value: Option<&AttributeValue>,
+                    attribute: &str,
+                ) -> Result<Self, DynamoDBError> {
+                    required(value, attribute)?
+                        .as_n()
+                        .ok()
+                        .and_then(|number| number.parse().ok())
+                        .ok_or_else(|| invalid(attribute))
+                }
+            }
+        )*
+    };
+}
+
+impl_number_attribute!(i8, i16, i32, i64, u8, u16, u32, u64, usize, f32, f64);
+
+impl DynamoAttribute for DateTime<Utc> {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::S(self.to_rfc3339()))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute)?
+            .as_s()
+            .ok()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc))
+            .ok_or_else(|| invalid(attribute))
+    }
+}
+
+impl<T: DynamoAttribute> DynamoAttribute for Option<T> {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        self.as_ref().and_then(T::to_attribute)
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        match value {
+            None | Some(AttributeValue::Null(_)) => Ok(None),
+            value => T::from_attribute(value, attribute).map(Some),
+        }
+    }
+}
+
+impl<T: DynamoAttribute> DynamoAttribute for Vec<T> {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::L(
+            self.iter()
+                .map(|value| value.to_attribute().unwrap_or(AttributeValue::Null(true)))
+                .collect(),
+        ))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute)?
+            .as_l()
+            .map_err(|_| invalid(attribute))?
+            .iter()
+            .enumerate()
+            .map(|(index, value)| T::from_attribute(Some(value), &format!("{attribute}[{index}]")))
+            .collect()
+    }
+}
+
+impl<T: DynamoAttribute> DynamoAttribute for HashMap<String, T> {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::M(
+            self.iter()
+                .map(|(key, value)| {
+                    (
+                        key.clone(),
+                        value.to_attribute().unwrap_or(AttributeValue::Null(true)),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        required(value, attribute)?
+            .as_m()
+            .map_err(|_| invalid(attribute))?
+            .iter()
+            .map(|(key, value)| {
+                T::from_attribute(Some(value), &format!("{attribute}.{key}"))
+                    .map(|value| (key.clone(), value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(DynamoItem, Debug, PartialEq)]
+    struct Address {
+        city: String,
+        zip_code: Option<String>,
+    }
+
+    #[derive(DynamoItem, Debug, PartialEq)]
+    struct Person {
+        name: String,
+        #[dynamo(rename = "personAge")]
+        age: u32,
+        score: f64,
+        admin: bool,
+        #[dynamo(default)]
+        archived: bool,
+        nickname: Option<String>,
+        tags: Vec<String>,
+        address: Address,
+        metadata: HashMap<String, i64>,
+    }
+
+    fn person() -> Person {
+        Person {
+            name: "Toto".into(),
+            age: 42,
+            score: 0.5,
+            admin: true,
+            archived: false,
+            nickname: None,
+            tags: vec!["a".into(), "b".into()],
+            address: Address {
+                city: "Paris".into(),
+                zip_code: Some("75001".into()),
+            },
+            metadata: HashMap::from([("visits".into(), 3)]),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let item = person().to_item();
+
+        assert_eq!(item.get("name"), Some(&AttributeValue::S("Toto".into())));
+        assert_eq!(item.get("personAge"), Some(&AttributeValue::N("42".into())));
+        assert_eq!(item.get("admin"), Some(&AttributeValue::Bool(true)));
+        // empty options are not written
+        assert_eq!(item.get("nickname"), None);
+        assert_eq!(
+            item.get("tags"),
+            Some(&AttributeValue::L(vec![
+                AttributeValue::S("a".into()),
+                AttributeValue::S("b".into())
+            ]))
+        );
+
+        assert_eq!(Person::from_item(item).unwrap(), person());
+    }
+
+    #[test]
+    fn test_default() {
+        let mut item = person().to_item();
+        item.remove("archived");
+
+        assert!(!Person::from_item(item).unwrap().archived);
+    }
+
+    #[test]
+    fn test_missing_attribute() {
+        let mut item = person().to_item();
+        item.remove("personAge");
+
+        assert!(matches!(
+            Person::from_item(item),
+            Err(DynamoDBError::MissingAttribute { attribute }) if attribute == "personAge"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_attribute() {
+        let mut item = person().to_item();
+        item.insert("admin".into(), AttributeValue::S("yes".into()));
+
+        assert!(matches!(
+            Person::from_item(item),
+            Err(DynamoDBError::InvalidAttribute { attribute }) if attribute == "admin"
+        ));
+    }
+
+    #[test]
+    fn test_nested_attribute() {
+        let mut item = person().to_item();
+        item.insert(
+            "address".into(),
+            AttributeValue::M(HashMap::from([(
+                "zip_code".into(),
+                AttributeValue::S("75001".into()),
+            )])),
+        );
+
+        assert!(matches!(
+            Person::from_item(item),
+            Err(DynamoDBError::MissingAttribute { attribute }) if attribute == "address.city"
+        ));
+
+        let mut item = person().to_item();
+        item.insert(
+            "tags".into(),
+            AttributeValue::L(vec![
+                AttributeValue::S("a".into()),
+                AttributeValue::N("1".into()),
+            ]),
+        );
+
+        assert!(matches!(
+            Person::from_item(item),
+            Err(DynamoDBError::InvalidAttribute { attribute }) if attribute == "tags[1]"
+        ));
+    }
+}
@@ -0,0 +1,511 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::KeyError;
+
+/// Delimiter between the entity prefix and the id in `PK` and `SK` values.
+pub const KEY_DELIMITER: char = '#';
+
+const TODO_PARTITION_PREFIX: &str = "TODO#";
+const TODO_SORT_PREFIX: &str = "ID#";
+const COUNTER_SORT_KEY: &str = "COUNTER";
+const EVENT_PARTITION_PREFIX: &str = "EVENT#";
+const CONSUMER_SORT_PREFIX: &str = "CONSUMER#";
+const OUTBOX_PARTITION_PREFIX: &str = "OUTBOX#";
+const OUTBOX_SORT_KEY: &str = "RECORD";
+const LIST_PARTITION_PREFIX: &str = "LIST#";
+const MEMBER_SORT_PREFIX: &str = "MEMBER#";
+const LIST_SORT_KEY: &str = "LIST";
+const OWNER_PARTITION_PREFIX: &str = "OWNER#";
+const LIST_SORT_PREFIX: &str = "LIST#";
+const DELETION_PARTITION_PREFIX: &str = "DELETION#";
+const DELETION_SORT_KEY: &str = "STATUS";
+
+/// A typed primary key of the single table.
+pub trait TableKey: Sized {
+    fn partition_key(&self) -> String;
+
+    fn sort_key(&self) -> String;
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError>;
+
+    /// Build the `PK`/`SK` attribute map, e.g. for `GetItem` or `DeleteItem`.
+    fn to_key(&self) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("PK".into(), AttributeValue::S(self.partition_key())),
+            ("SK".into(), AttributeValue::S(self.sort_key())),
+        ])
+    }
+
+    /// Parse a `PK`/`SK` attribute map, e.g. a `LastEvaluatedKey`. Other attributes are ignored.
+    fn from_key(key: &HashMap<String, AttributeValue>) -> Result<Self, KeyError> {
+        Self::parse(key_attribute(key, "PK")?, key_attribute(key, "SK")?)
+    }
+}
+
+fn key_attribute<'a>(
+    key: &'a HashMap<String, AttributeValue>,
+    attribute: &str,
+) -> Result<&'a str, KeyError> {
+    key.get(attribute)
+        .ok_or_else(|| KeyError::MissingAttribute {
+            attribute: attribute.into(),
+        })?
+        .as_s()
+        .map(String::as_str)
+        .map_err(|_| KeyError::InvalidAttribute {
+            attribute: attribute.into(),
+        })
+}
+
+fn validate_id(id: &str) -> Result<&str, KeyError> {
+    match id.is_empty() || id.contains(KEY_DELIMITER) {
+        true => Err(KeyError::InvalidId { id: id.into() }),
+        false => Ok(id),
+    }
+}
+
+fn strip_prefix<'a>(value: &'a str, prefix: &str, attribute: &str) -> Result<&'a str, KeyError> {
+    value
+        .strip_prefix(prefix)
+        .ok_or_else(|| KeyError::InvalidAttribute {
+            attribute: attribute.into(),
+        })
+        .and_then(validate_id)
+}
+
+/// The partition holding all the todos of a list, along with its counter.
+pub fn todo_list_partition_key(list_id: &str) -> Result<String, KeyError> {
+    Ok(format!("{TODO_PARTITION_PREFIX}{}", validate_id(list_id)?))
+}
+
+/// The partition holding a list along with its members.
+pub fn list_partition_key(list_id: &str) -> Result<String, KeyError> {
+    Ok(format!("{LIST_PARTITION_PREFIX}{}", validate_id(list_id)?))
+}
+
+/// The partition holding a copy of all the lists of an owner.
+pub fn owner_lists_partition_key(owner: &str) -> Result<String, KeyError> {
+    Ok(format!("{OWNER_PARTITION_PREFIX}{}", validate_id(owner)?))
+}
+
+/// `PK=TODO#{list_id}`, `SK=ID#{todo_id}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoKey {
+    pub list_id: String,
+    pub todo_id: String,
+}
+
+impl TodoKey {
+    /// Prefix of the sort keys of all the todos of a list.
+    pub const SORT_KEY_PREFIX: &'static str = TODO_SORT_PREFIX;
+
+    pub fn new(list_id: &str, todo_id: &str) -> Result<Self, KeyError> {
+        Ok(TodoKey {
+            list_id: validate_id(list_id)?.into(),
+            todo_id: validate_id(todo_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for TodoKey {
+    fn partition_key(&self) -> String {
+        format!("{TODO_PARTITION_PREFIX}{}", self.list_id)
+    }
+
+    fn sort_key(&self) -> String {
+        format!("{TODO_SORT_PREFIX}{}", self.todo_id)
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        Ok(TodoKey {
+            list_id: strip_prefix(partition_key, TODO_PARTITION_PREFIX, "PK")?.into(),
+            todo_id: strip_prefix(sort_key, TODO_SORT_PREFIX, "SK")?.into(),
+        })
+    }
+}
+
+/// `PK=TODO#{list_id}`, `SK=COUNTER`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListCounterKey {
+    pub list_id: String,
+}
+
+impl ListCounterKey {
+    pub fn new(list_id: &str) -> Result<Self, KeyError> {
+        Ok(ListCounterKey {
+            list_id: validate_id(list_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for ListCounterKey {
+    fn partition_key(&self) -> String {
+        format!("{TODO_PARTITION_PREFIX}{}", self.list_id)
+    }
+
+    fn sort_key(&self) -> String {
+        COUNTER_SORT_KEY.into()
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        if sort_key != COUNTER_SORT_KEY {
+            return Err(KeyError::InvalidAttribute {
+                attribute: "SK".into(),
+            });
+        }
+
+        Ok(ListCounterKey {
+            list_id: strip_prefix(partition_key, TODO_PARTITION_PREFIX, "PK")?.into(),
+        })
+    }
+}
+
+/// `PK=EVENT#{event_id}`, `SK=CONSUMER#{consumer}`
+///
+/// Records that an event was processed by a consumer, see [`crate::update_once`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedEventKey {
+    pub event_id: String,
+    pub consumer: String,
+}
+
+impl ProcessedEventKey {
+    pub fn new(event_id: &str, consumer: &str) -> Result<Self, KeyError> {
+        Ok(ProcessedEventKey {
+            event_id: validate_id(event_id)?.into(),
+            consumer: validate_id(consumer)?.into(),
+        })
+    }
+}
+
+impl TableKey for ProcessedEventKey {
+    fn partition_key(&self) -> String {
+        format!("{EVENT_PARTITION_PREFIX}{}", self.event_id)
+    }
+
+    fn sort_key(&self) -> String {
+        format!("{CONSUMER_SORT_PREFIX}{}", self.consumer)
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        Ok(ProcessedEventKey {
+            event_id: strip_prefix(partition_key, EVENT_PARTITION_PREFIX, "PK")?.into(),
+            consumer: strip_prefix(sort_key, CONSUMER_SORT_PREFIX, "SK")?.into(),
+        })
+    }
+}
+
+/// `PK=OUTBOX#{record_id}`, `SK=RECORD`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxKey {
+    pub record_id: String,
+}
+
+impl OutboxKey {
+    /// Prefix of the partition keys of all the outbox records.
+    pub const PARTITION_KEY_PREFIX: &'static str = OUTBOX_PARTITION_PREFIX;
+
+    pub fn new(record_id: &str) -> Result<Self, KeyError> {
+        Ok(OutboxKey {
+            record_id: validate_id(record_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for OutboxKey {
+    fn partition_key(&self) -> String {
+        format!("{OUTBOX_PARTITION_PREFIX}{}", self.record_id)
+    }
+
+    fn sort_key(&self) -> String {
+        OUTBOX_SORT_KEY.into()
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        if sort_key != OUTBOX_SORT_KEY {
+            return Err(KeyError::InvalidAttribute {
+                attribute: "SK".into(),
+            });
+        }
+
+        Ok(OutboxKey {
+            record_id: strip_prefix(partition_key, OUTBOX_PARTITION_PREFIX, "PK")?.into(),
+        })
+    }
+}
+
+/// `PK=LIST#{list_id}`, `SK=MEMBER#{user_id}`
+///
+/// The user id is a [`crate::auth::Caller::id`], e.g. an IAM user ARN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListMemberKey {
+    pub list_id: String,
+    pub user_id: String,
+}
+
+impl ListMemberKey {
+    /// Prefix of the sort keys of all the members of a list.
+    pub const SORT_KEY_PREFIX: &'static str = MEMBER_SORT_PREFIX;
+
+    pub fn new(list_id: &str, user_id: &str) -> Result<Self, KeyError> {
+        Ok(ListMemberKey {
+            list_id: validate_id(list_id)?.into(),
+            user_id: validate_id(user_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for ListMemberKey {
+    fn partition_key(&self) -> String {
+        format!("{LIST_PARTITION_PREFIX}{}", self.list_id)
+    }
+
+    fn sort_key(&self) -> String {
+        format!("{MEMBER_SORT_PREFIX}{}", self.user_id)
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        Ok(ListMemberKey {
+            list_id: strip_prefix(partition_key, LIST_PARTITION_PREFIX, "PK")?.into(),
+            user_id: strip_prefix(sort_key, MEMBER_SORT_PREFIX, "SK")?.into(),
+        })
+    }
+}
+
+/// `PK=LIST#{list_id}`, `SK=LIST`
+///
+/// The list shares its partition with its members.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListKey {
+    pub list_id: String,
+}
+
+impl ListKey {
+    pub fn new(list_id: &str) -> Result<Self, KeyError> {
+        Ok(ListKey {
+            list_id: validate_id(list_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for ListKey {
+    fn partition_key(&self) -> String {
+        format!("{LIST_PARTITION_PREFIX}{}", self.list_id)
+    }
+
+    fn sort_key(&self) -> String {
+        LIST_SORT_KEY.into()
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        if sort_key != LIST_SORT_KEY {
+            return Err(KeyError::InvalidAttribute {
+                attribute: "SK".into(),
+            });
+        }
+
+        Ok(ListKey {
+            list_id: strip_prefix(partition_key, LIST_PARTITION_PREFIX, "PK")?.into(),
+        })
+    }
+}
+
+/// `PK=OWNER#{owner}`, `SK=LIST#{list_id}`
+///
+/// A copy of a list, written along with it, to query the lists of an owner in creation order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnerListKey {
+    pub owner: String,
+    pub list_id: String,
+}
+
+impl OwnerListKey {
+    pub fn new(owner: &str, list_id: &str) -> Result<Self, KeyError> {
+        Ok(OwnerListKey {
+            owner: validate_id(owner)?.into(),
+            list_id: validate_id(list_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for OwnerListKey {
+    fn partition_key(&self) -> String {
+        format!("{OWNER_PARTITION_PREFIX}{}", self.owner)
+    }
+
+    fn sort_key(&self) -> String {
+        format!("{LIST_SORT_PREFIX}{}", self.list_id)
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        Ok(OwnerListKey {
+            owner: strip_prefix(partition_key, OWNER_PARTITION_PREFIX, "PK")?.into(),
+            list_id: strip_prefix(sort_key, LIST_SORT_PREFIX, "SK")?.into(),
+        })
+    }
+}
+
+/// `PK=DELETION#{list_id}`, `SK=STATUS`
+///
+/// Kept out of the list partition, so that the status outlives the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListDeletionKey {
+    pub list_id: String,
+}
+
+impl ListDeletionKey {
+    pub fn new(list_id: &str) -> Result<Self, KeyError> {
+        Ok(ListDeletionKey {
+            list_id: validate_id(list_id)?.into(),
+        })
+    }
+}
+
+impl TableKey for ListDeletionKey {
+    fn partition_key(&self) -> String {
+        format!("{DELETION_PARTITION_PREFIX}{}", self.list_id)
+    }
+
+    fn sort_key(&self) -> String {
+        DELETION_SORT_KEY.into()
+    }
+
+    fn parse(partition_key: &str, sort_key: &str) -> Result<Self, KeyError> {
+        if sort_key != DELETION_SORT_KEY {
+            return Err(KeyError::InvalidAttribute {
+                attribute: "SK".into(),
+            });
+        }
+
+        Ok(ListDeletionKey {
+            list_id: strip_prefix(partition_key, DELETION_PARTITION_PREFIX, "PK")?.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_key() {
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert_eq!(
+            key.to_key(),
+            HashMap::from([
+                ("PK".into(), AttributeValue::S("TODO#toto".into())),
+                ("SK".into(), AttributeValue::S("ID#tata".into())),
+            ])
+        );
+        assert_eq!(TodoKey::from_key(&key.to_key()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_list_counter_key() {
+        let key = ListCounterKey::new("toto").unwrap();
+
+        assert_eq!(
+            key.to_key(),
+            HashMap::from([
+                ("PK".into(), AttributeValue::S("TODO#toto".into())),
+                ("SK".into(), AttributeValue::S("COUNTER".into())),
+            ])
+        );
+        assert_eq!(ListCounterKey::from_key(&key.to_key()).unwrap(), key);
+        // the counter shares the todos partition but is not a todo
+        assert!(TodoKey::from_key(&key.to_key()).is_err());
+    }
+
+    #[test]
+    fn test_processed_event_key() {
+        let key = ProcessedEventKey::new("event-1", "on-todo-created").unwrap();
+
+        assert_eq!(key.partition_key(), "EVENT#event-1");
+        assert_eq!(key.sort_key(), "CONSUMER#on-todo-created");
+        assert_eq!(ProcessedEventKey::from_key(&key.to_key()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_outbox_key() {
+        let key = OutboxKey::new("01HZY").unwrap();
+
+        assert_eq!(key.partition_key(), "OUTBOX#01HZY");
+        assert!(key
+            .partition_key()
+            .starts_with(OutboxKey::PARTITION_KEY_PREFIX));
+        assert_eq!(OutboxKey::from_key(&key.to_key()).unwrap(), key);
+        assert!(OutboxKey::parse("OUTBOX#01HZY", "COUNTER").is_err());
+    }
+
+    #[test]
+    fn test_list_member_key() {
+        let key = ListMemberKey::new("toto", "arn:aws:iam::123456789012:user/tata").unwrap();
+
+        assert_eq!(key.partition_key(), "LIST#toto");
+        assert_eq!(key.sort_key(), "MEMBER#arn:aws:iam::123456789012:user/tata");
+        assert_eq!(ListMemberKey::from_key(&key.to_key()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_list_key() {
+        let key = ListKey::new("toto").unwrap();
+
+        assert_eq!(key.partition_key(), "LIST#toto");
+        assert_eq!(key.sort_key(), "LIST");
+        assert_eq!(ListKey::from_key(&key.to_key()).unwrap(), key);
+        // the list shares its partition with its members
+        assert!(ListKey::parse("LIST#toto", "MEMBER#tata").is_err());
+    }
+
+    #[test]
+    fn test_owner_list_key() {
+        let key = OwnerListKey::new("arn:aws:iam::123456789012:user/tata", "toto").unwrap();
+
+        assert_eq!(
+            key.partition_key(),
+            owner_lists_partition_key("arn:aws:iam::123456789012:user/tata").unwrap()
+        );
+        assert_eq!(key.sort_key(), "LIST#toto");
+        assert_eq!(OwnerListKey::from_key(&key.to_key()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_list_deletion_key() {
+        let key = ListDeletionKey::new("toto").unwrap();
+
+        assert_eq!(key.partition_key(), "DELETION#toto");
+        assert_eq!(key.sort_key(), "STATUS");
+        assert_eq!(ListDeletionKey::from_key(&key.to_key()).unwrap(), key);
+        assert!(ListDeletionKey::parse("LIST#toto", "STATUS").is_err());
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        assert!(matches!(
+            TodoKey::new("to#to", "tata"),
+            Err(KeyError::InvalidId { id }) if id == "to#to"
+        ));
+        assert!(TodoKey::new("toto", "").is_err());
+        assert!(ListCounterKey::new("TODO#toto").is_err());
+        assert!(todo_list_partition_key("to#to").is_err());
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert!(matches!(
+            TodoKey::from_key(&HashMap::from([(
+                "PK".into(),
+                AttributeValue::S("TODO#toto".into())
+            )])),
+            Err(KeyError::MissingAttribute { attribute }) if attribute == "SK"
+        ));
+        assert!(matches!(
+            TodoKey::parse("LIST#toto", "ID#tata"),
+            Err(KeyError::InvalidAttribute { attribute }) if attribute == "PK"
+        ));
+        assert!(TodoKey::parse("TODO#toto", "ID#ta#ta").is_err());
+    }
+}
@@ -1,7 +1,33 @@
+// allow the `DynamoItem` derive to refer to `::shared` from within this crate
+extern crate self as shared;
+
+pub mod auth;
+mod circuit_breaker;
 mod clients;
+pub mod config;
+mod cursor;
 mod errors;
+mod events;
+pub mod http;
+mod idempotency;
+mod item;
+pub mod keys;
 mod models;
+mod outbox;
+mod repository;
+mod response;
+pub mod testing;
+mod validation;
 
+pub use circuit_breaker::*;
 pub use clients::*;
+pub use cursor::*;
 pub use errors::*;
+pub use events::*;
+pub use idempotency::*;
+pub use item::*;
 pub use models::*;
+pub use outbox::*;
+pub use repository::*;
+pub use response::*;
+pub use validation::*;
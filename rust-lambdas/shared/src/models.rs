@@ -1,87 +1,188 @@
-use std::collections::HashMap;
-
-use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tracing::error;
 use ts_rs::TS;
 
-use crate::DynamoDBError;
+use crate::{AttributeValue, DynamoAttribute, DynamoDBError, DynamoItem};
 
 #[derive(TS)]
 #[ts(export, export_to = "../../../integration-tests/bindings/")]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, DynamoItem, Clone)]
 pub struct Todo {
     pub id: String,
     pub list_id: String,
     pub title: String,
     pub description: String,
+    // todos created before completion existed have no `completed` attribute
+    #[dynamo(default)]
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The id of the caller who created the todo, see [`crate::auth::Caller::id`].
+    // todos created before ownership have no `owner` attribute
+    pub owner: Option<String>,
+}
+
+/// A todo list, see [`crate::keys::ListKey`].
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, DynamoItem, Debug, Clone, PartialEq)]
+pub struct List {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// The id of the caller who created the list, see [`crate::auth::Caller::id`].
+    pub owner: String,
+    /// Archived lists accept no new todos.
+    pub archived: bool,
+}
+
+/// Counters maintained on the `COUNTER` item of a list by the event consumers.
+#[derive(DynamoItem, Debug, Clone, Default)]
+pub struct ListCounter {
+    #[dynamo(rename = "todosCount", default)]
+    pub todos_count: i64,
+    #[dynamo(rename = "completedCount", default)]
+    pub completed_count: i64,
+}
+
+/// What a user may do on a list, each role allowing what the previous ones do.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Lists the todos.
+    Viewer,
+    /// Creates and deletes the todos.
+    Editor,
+    /// Shares the list, only held by the user who created it.
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        }
+    }
+}
+
+impl DynamoAttribute for Role {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::S(self.as_str().into()))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        match String::from_attribute(value, attribute)?.as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "owner" => Ok(Role::Owner),
+            _ => Err(DynamoDBError::InvalidAttribute {
+                attribute: attribute.into(),
+            }),
+        }
+    }
+}
+
+/// A user the list is shared with, see [`crate::keys::ListMemberKey`].
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, DynamoItem, Debug, Clone, PartialEq)]
+pub struct ListMember {
+    pub list_id: String,
+    pub user_id: String,
+    pub role: Role,
+    pub added_at: DateTime<Utc>,
+}
+
+/// The progress of a list deletion.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionStatus {
+    /// The todos are being deleted, the list is archived meanwhile.
+    InProgress,
+    /// The list, its todos, counter and members are deleted.
+    Completed,
 }
 
-impl TryFrom<HashMap<String, AttributeValue>> for Todo {
-    type Error = DynamoDBError;
-
-    fn try_from(item: HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
-        Ok(Todo {
-            id: item
-                .get("id")
-                .ok_or(DynamoDBError::MissingAttribute {
-                    attribute: "id".into(),
-                })?
-                .to_owned()
-                .as_s()
-                .map_err(|err| {
-                    error!(err = ?err, "Unable to query table");
-
-                    DynamoDBError::InvalidAttribute {
-                        attribute: "id".into(),
-                    }
-                })?
-                .to_string(),
-            list_id: item
-                .get("list_id")
-                .ok_or(DynamoDBError::MissingAttribute {
-                    attribute: "list_id".into(),
-                })?
-                .to_owned()
-                .as_s()
-                .map_err(|err| {
-                    error!(err = ?err, "Unable to query table");
-
-                    DynamoDBError::InvalidAttribute {
-                        attribute: "list_id".into(),
-                    }
-                })?
-                .to_string(),
-            title: item
-                .get("title")
-                .ok_or(DynamoDBError::MissingAttribute {
-                    attribute: "title".into(),
-                })?
-                .to_owned()
-                .as_s()
-                .map_err(|err| {
-                    error!(err = ?err, "Unable to query table");
-
-                    DynamoDBError::InvalidAttribute {
-                        attribute: "title".into(),
-                    }
-                })?
-                .to_string(),
-            description: item
-                .get("description")
-                .ok_or(DynamoDBError::MissingAttribute {
-                    attribute: "description".into(),
-                })?
-                .to_owned()
-                .as_s()
-                .map_err(|err| {
-                    error!(err = ?err, "Unable to query table");
-
-                    DynamoDBError::InvalidAttribute {
-                        attribute: "description".into(),
-                    }
-                })?
-                .to_string(),
-        })
+impl DeletionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeletionStatus::InProgress => "in_progress",
+            DeletionStatus::Completed => "completed",
+        }
+    }
+}
+
+impl DynamoAttribute for DeletionStatus {
+    fn to_attribute(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::S(self.as_str().into()))
+    }
+
+    fn from_attribute(
+        value: Option<&AttributeValue>,
+        attribute: &str,
+    ) -> Result<Self, DynamoDBError> {
+        match String::from_attribute(value, attribute)?.as_str() {
+            "in_progress" => Ok(DeletionStatus::InProgress),
+            "completed" => Ok(DeletionStatus::Completed),
+            _ => Err(DynamoDBError::InvalidAttribute {
+                attribute: attribute.into(),
+            }),
+        }
     }
 }
+
+/// The deletion of a list with all its todos, polled by the client, see
+/// [`crate::keys::ListDeletionKey`].
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, DynamoItem, Debug, Clone, PartialEq)]
+pub struct ListDeletion {
+    pub list_id: String,
+    pub status: DeletionStatus,
+    /// The id of the owner who requested the deletion.
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    #[ts(type = "number")]
+    pub deleted_todos: i64,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+pub struct ListSummary {
+    pub list_id: String,
+    pub name: String,
+    /// See [`List::owner`].
+    pub owner: String,
+    pub created_at: DateTime<Utc>,
+    pub archived: bool,
+    #[ts(type = "number")]
+    pub todos_count: i64,
+    #[ts(type = "number")]
+    pub completed_count: i64,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+pub struct TodoUpdate {
+    pub old: Todo,
+    pub new: Todo,
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::{
+    error::BuildError,
+    types::{AttributeValue, Put},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::{
+    keys::{OutboxKey, TableKey},
+    DynamoItem, EventEnvelope,
+};
+
+/// How long sent records are kept around, e.g. to debug the relay.
+pub const SENT_OUTBOX_RECORD_TTL: TimeDelta = TimeDelta::days(2);
+
+/// An event stored in the same transaction as the change it describes.
+///
+/// The outbox relay picks new records up from the table stream and publishes them
+/// to EventBridge, so that a write and its event can't diverge.
+#[derive(DynamoItem, Debug, Clone, PartialEq)]
+pub struct OutboxRecord {
+    pub id: String,
+    pub detail_type: String,
+    /// JSON encoded [`EventEnvelope`].
+    pub detail: String,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+impl OutboxRecord {
+    pub fn new(envelope: &EventEnvelope) -> Result<Self, serde_json::Error> {
+        Ok(OutboxRecord {
+            // the event keeps the same id whatever the number of times it is relayed
+            id: envelope.id.clone(),
+            detail_type: envelope.detail_type().into(),
+            detail: serde_json::to_string(envelope)?,
+            created_at: Utc::now(),
+            sent_at: None,
+        })
+    }
+
+    pub fn envelope(&self) -> Result<EventEnvelope, serde_json::Error> {
+        serde_json::from_str(&self.detail)
+    }
+
+    pub fn key(&self) -> OutboxKey {
+        // ulids never contain the key delimiter
+        OutboxKey {
+            record_id: self.id.clone(),
+        }
+    }
+
+    /// The full item, keys included.
+    pub fn to_record_item(&self) -> HashMap<String, AttributeValue> {
+        let mut item = self.to_item();
+        item.extend(self.key().to_key());
+
+        item
+    }
+
+    /// The put to add to the `TransactWriteItems` of the change.
+    pub fn to_put(&self, table_name: &str) -> Result<Put, BuildError> {
+        Put::builder()
+            .table_name(table_name)
+            .set_item(Some(self.to_record_item()))
+            .condition_expression("attribute_not_exists(PK)")
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::todo, TodoEvent};
+
+    #[test]
+    fn test_outbox_record() {
+        let todo = todo("toto", "tata");
+
+        let envelope = EventEnvelope::new(TodoEvent::TodoCreated(todo));
+        let record = OutboxRecord::new(&envelope).unwrap();
+        let item = record.to_record_item();
+
+        assert_eq!(
+            item.get("PK"),
+            Some(&AttributeValue::S(format!("OUTBOX#{}", record.id)))
+        );
+        assert_eq!(item.get("sent_at"), None);
+        assert_eq!(record.id, envelope.id);
+        assert_eq!(record.detail_type, "TODO_CREATED");
+        assert!(matches!(
+            record.envelope().unwrap().event,
+            TodoEvent::TodoCreated(todo) if todo.id == "tata"
+        ));
+        assert_eq!(OutboxRecord::from_item(item).unwrap(), record);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, future::Future};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    keys::{ListMemberKey, TodoKey},
+    List, ListCounter, ListDeletion, ListMember, OutboxRecord, Processing, RepositoryError, Todo,
+};
+
+mod dynamodb;
+mod memory;
+
+pub use dynamodb::DynamoTodoRepository;
+pub use memory::InMemoryTodoRepository;
+
+/// A page of the todos of a list.
+pub struct TodoPage {
+    pub todos: Vec<Todo>,
+    /// The key to resume from, `None` on the last page.
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+}
+
+/// A page of the lists of an owner.
+pub struct ListPage {
+    pub lists: Vec<List>,
+    /// The key to resume from, `None` on the last page.
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+}
+
+/// A page of todos deleted along with their list.
+#[derive(Debug)]
+pub struct DeletedTodos {
+    pub count: usize,
+    /// The key to resume from, `None` once every todo is deleted.
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+}
+
+/// Deltas applied to a [`ListCounter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CounterIncrement {
+    pub todos_count: i64,
+    pub completed_count: i64,
+}
+
+/// Storage of the lists, of their todos, counters and members.
+///
+/// The writes changing a list, a todo or a member store the outbox record of their event in the
+/// same transaction, see [`OutboxRecord`]. The todo writes fail with
+/// [`RepositoryError::ListClosed`] once their list is archived or deleted.
+pub trait TodoRepository: Sync {
+    /// Store a new todo, failing with [`RepositoryError::Conflict`] if the outbox record
+    /// already exists.
+    fn create(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// A strongly consistent read of a todo.
+    fn get(
+        &self,
+        key: &TodoKey,
+    ) -> impl Future<Output = Result<Option<Todo>, RepositoryError>> + Send;
+
+    /// The todos of a list, in creation order, starting after `exclusive_start_key`.
+    fn list(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> impl Future<Output = Result<TodoPage, RepositoryError>> + Send;
+
+    /// Delete a todo, provided its completion state did not change since it was read.
+    ///
+    /// Fails with [`RepositoryError::NotFound`] or [`RepositoryError::Conflict`].
+    fn delete(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Replace the title and description of a todo, provided it did not change since `old`
+    /// was read.
+    ///
+    /// Fails with [`RepositoryError::NotFound`] or [`RepositoryError::Conflict`].
+    fn update(
+        &self,
+        old: &Todo,
+        new: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Complete or reopen a todo, provided it is still in the opposite state.
+    ///
+    /// `todo` carries the new state, a completed todo has its `completed_at`.
+    /// Fails with [`RepositoryError::NotFound`] or [`RepositoryError::Conflict`].
+    fn set_completion(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// The counter of a list, zeroed until the first todo is counted.
+    fn get_counter(
+        &self,
+        list_id: &str,
+    ) -> impl Future<Output = Result<ListCounter, RepositoryError>> + Send;
+
+    /// Apply an increment at most once per event and consumer, see [`crate::update_once`].
+    ///
+    /// The increment is [`Processing::Skipped`] once the list is deleted.
+    fn increment_counter(
+        &self,
+        list_id: &str,
+        increment: CounterIncrement,
+        consumer: &str,
+        event_id: &str,
+    ) -> impl Future<Output = Result<Processing, RepositoryError>> + Send;
+
+    /// A strongly consistent read of a list member.
+    fn get_member(
+        &self,
+        key: &ListMemberKey,
+    ) -> impl Future<Output = Result<Option<ListMember>, RepositoryError>> + Send;
+
+    /// Store a new member, failing with [`RepositoryError::Conflict`] if they already are one.
+    fn add_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Replace an existing member, failing with [`RepositoryError::NotFound`] otherwise.
+    fn update_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Delete a member, failing with [`RepositoryError::NotFound`] if they are not one.
+    fn remove_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Store a new list, failing with [`RepositoryError::Conflict`] if it already exists.
+    fn create_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// A strongly consistent read of a list.
+    fn get_list(
+        &self,
+        list_id: &str,
+    ) -> impl Future<Output = Result<Option<List>, RepositoryError>> + Send;
+
+    /// Replace an existing list, failing with [`RepositoryError::NotFound`] otherwise.
+    fn update_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Store the deletion of a list along with the list, archived meanwhile.
+    ///
+    /// Fails with [`RepositoryError::Conflict`] if the deletion was already requested, and with
+    /// [`RepositoryError::NotFound`] if the list does not exist.
+    fn request_list_deletion(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// A strongly consistent read of a list deletion.
+    fn get_list_deletion(
+        &self,
+        list_id: &str,
+    ) -> impl Future<Output = Result<Option<ListDeletion>, RepositoryError>> + Send;
+
+    /// Store the progress of a list deletion.
+    fn update_list_deletion(
+        &self,
+        deletion: &ListDeletion,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Delete a page of the todos of a list by key, without any event nor counter update, for
+    /// the deletion of the list. The todos are not read, so that malformed ones are deleted too.
+    ///
+    /// Fails with [`RepositoryError::Unprocessed`] when todos are left after the retries.
+    fn delete_todos(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> impl Future<Output = Result<DeletedTodos, RepositoryError>> + Send;
+
+    /// Delete all the members of a list, returning how many there were.
+    fn delete_members(
+        &self,
+        list_id: &str,
+    ) -> impl Future<Output = Result<usize, RepositoryError>> + Send;
+
+    /// Delete a list along with its counter, storing its completed `deletion`.
+    ///
+    /// Fails with [`RepositoryError::NotFound`] if the list does not exist.
+    fn delete_list(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// The lists of an owner, in creation order, starting after `exclusive_start_key`.
+    fn owner_lists(
+        &self,
+        owner: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> impl Future<Output = Result<ListPage, RepositoryError>> + Send;
+}
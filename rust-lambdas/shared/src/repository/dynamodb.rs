@@ -0,0 +1,1754 @@
+use std::{collections::HashMap, time::Duration};
+
+use aws_config::retry::RetryMode;
+use aws_sdk_dynamodb::{
+    error::BuildError,
+    operation::transact_write_items::TransactWriteItemsError,
+    types::{
+        AttributeValue, ConditionCheck, Delete, DeleteRequest, Put,
+        ReturnValuesOnConditionCheckFailure, TransactWriteItem, Update, WriteRequest,
+    },
+};
+use chrono::{TimeDelta, Utc};
+use tokio::time::sleep;
+
+use crate::{
+    keys::{
+        list_partition_key, owner_lists_partition_key, todo_list_partition_key, ListCounterKey,
+        ListDeletionKey, ListKey, ListMemberKey, OwnerListKey, TableKey, TodoKey,
+    },
+    update_once, CounterIncrement, DeletedTodos, DynamoItem, List, ListCounter, ListDeletion,
+    ListMember, ListPage, OutboxRecord, Processing, RepositoryError, RetryPolicy, TimeoutPolicy,
+    Todo, TodoPage, TodoRepository, WithPolicies, EXPIRES_AT_ATTRIBUTE,
+};
+
+/// The maximum number of requests of a `BatchWriteItem` call.
+const BATCH_WRITE_SIZE: usize = 25;
+const MAX_BATCH_WRITE_ATTEMPTS: u32 = 5;
+const BATCH_WRITE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// The batch deletes run in the list deletion consumer, which can wait longer than the HTTP
+/// lambdas, and back off once the table throttles them.
+const BATCH_WRITE_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    mode: RetryMode::Adaptive,
+    max_attempts: 5,
+    initial_backoff: Duration::from_millis(100),
+    max_backoff: Duration::from_secs(5),
+};
+const BATCH_WRITE_TIMEOUT_POLICY: TimeoutPolicy = TimeoutPolicy {
+    connect: Duration::from_millis(500),
+    operation_attempt: Duration::from_secs(5),
+    operation: Duration::from_secs(30),
+};
+
+/// How long the completed list deletions can be polled.
+const COMPLETED_LIST_DELETION_TTL: TimeDelta = TimeDelta::days(7);
+
+/// The todos stored in the single table, see [`crate::keys`] for the layout.
+#[derive(Clone)]
+pub struct DynamoTodoRepository<'a> {
+    dynamodb_client: &'a aws_sdk_dynamodb::Client,
+    table_name: &'a str,
+}
+
+impl<'a> DynamoTodoRepository<'a> {
+    pub fn new(dynamodb_client: &'a aws_sdk_dynamodb::Client, table_name: &'a str) -> Self {
+        DynamoTodoRepository {
+            dynamodb_client,
+            table_name,
+        }
+    }
+
+    /// Put a list and its copy in the partition of its owner.
+    ///
+    /// `condition` applies to the list.
+    fn put_list(
+        &self,
+        list: &List,
+        key: &ListKey,
+        owner_key: &OwnerListKey,
+        condition: &str,
+    ) -> Result<Vec<TransactWriteItem>, BuildError> {
+        let item = list.to_item();
+
+        let mut list_item = item.clone();
+        list_item.extend(key.to_key());
+
+        let mut owner_item = item;
+        owner_item.extend(owner_key.to_key());
+
+        Ok(vec![
+            TransactWriteItem::builder()
+                .put(
+                    Put::builder()
+                        .table_name(self.table_name)
+                        .set_item(Some(list_item))
+                        .condition_expression(condition)
+                        .build()?,
+                )
+                .build(),
+            TransactWriteItem::builder()
+                .put(
+                    Put::builder()
+                        .table_name(self.table_name)
+                        .set_item(Some(owner_item))
+                        .build()?,
+                )
+                .build(),
+        ])
+    }
+
+    /// Check that a list exists and is not archived, in the transaction writing one of its todos.
+    fn check_list_open(&self, key: &ListKey) -> Result<TransactWriteItem, BuildError> {
+        Ok(TransactWriteItem::builder()
+            .condition_check(
+                ConditionCheck::builder()
+                    .table_name(self.table_name)
+                    .set_key(Some(key.to_key()))
+                    .condition_expression("attribute_exists(PK) AND archived = :false")
+                    .expression_attribute_values(":false", AttributeValue::Bool(false))
+                    .build()?,
+            )
+            .build())
+    }
+
+    /// Write items along with the outbox record of their event.
+    ///
+    /// `condition_failure` is returned when the condition of the first write fails.
+    async fn write_items(
+        &self,
+        mut transact_items: Vec<TransactWriteItem>,
+        outbox_record: &OutboxRecord,
+        condition_failure: RepositoryError,
+    ) -> Result<(), RepositoryError> {
+        transact_items.push(
+            TransactWriteItem::builder()
+                .put(outbox_record.to_put(self.table_name)?)
+                .build(),
+        );
+
+        let res = self
+            .dynamodb_client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await;
+
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) if cancelled_by_condition(err.as_service_error(), 0) => Err(condition_failure),
+            Err(err) => Err(aws_sdk_dynamodb::Error::from(err).into()),
+        }
+    }
+
+    /// Write a todo along with the outbox record of its event.
+    ///
+    /// The condition of the todo write must fail when it changed since it was read, the
+    /// returned item then tells a [`RepositoryError::Conflict`] from a
+    /// [`RepositoryError::NotFound`]. The write fails with [`RepositoryError::ListClosed`]
+    /// when the list of the todo is archived or deleted.
+    async fn write_todo(
+        &self,
+        list_id: &str,
+        write_todo: TransactWriteItem,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let transact_items = vec![
+            write_todo,
+            TransactWriteItem::builder()
+                .put(outbox_record.to_put(self.table_name)?)
+                .build(),
+            self.check_list_open(&ListKey::new(list_id)?)?,
+        ];
+
+        let res = self
+            .dynamodb_client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await;
+
+        let Err(err) = res else {
+            return Ok(());
+        };
+
+        if cancelled_by_condition(err.as_service_error(), 2) {
+            return Err(RepositoryError::ListClosed);
+        }
+
+        let condition_check_failure = match err.as_service_error() {
+            Some(TransactWriteItemsError::TransactionCanceledException(exception)) => exception
+                .cancellation_reasons()
+                .first()
+                .filter(|reason| reason.code() == Some("ConditionalCheckFailed"))
+                .map(|reason| reason.item().is_some()),
+            _ => None,
+        };
+
+        match condition_check_failure {
+            // the todo still exists, it changed since it was read
+            Some(true) => Err(RepositoryError::Conflict),
+            Some(false) => Err(RepositoryError::NotFound),
+            None => Err(aws_sdk_dynamodb::Error::from(err).into()),
+        }
+    }
+
+    /// Delete items in `BatchWriteItem` chunks, retrying the unprocessed ones with a backoff.
+    async fn batch_delete(
+        &self,
+        keys: Vec<HashMap<String, AttributeValue>>,
+    ) -> Result<(), RepositoryError> {
+        for chunk in keys.chunks(BATCH_WRITE_SIZE) {
+            let mut requests = chunk
+                .iter()
+                .map(|key| {
+                    Ok(WriteRequest::builder()
+                        .delete_request(
+                            DeleteRequest::builder()
+                                .set_key(Some(key.clone()))
+                                .build()?,
+                        )
+                        .build())
+                })
+                .collect::<Result<Vec<_>, BuildError>>()?;
+
+            let mut attempt = 1;
+
+            loop {
+                let res = self
+                    .dynamodb_client
+                    .batch_write_item()
+                    .request_items(self.table_name, requests)
+                    .customize()
+                    .with_policies(BATCH_WRITE_RETRY_POLICY, BATCH_WRITE_TIMEOUT_POLICY)
+                    .send()
+                    .await
+                    .map_err(aws_sdk_dynamodb::Error::from)?;
+
+                // throttled requests are returned rather than failing the whole batch
+                requests = res
+                    .unprocessed_items
+                    .and_then(|mut unprocessed_items| unprocessed_items.remove(self.table_name))
+                    .unwrap_or_default();
+
+                if requests.is_empty() {
+                    break;
+                }
+
+                if attempt >= MAX_BATCH_WRITE_ATTEMPTS {
+                    return Err(RepositoryError::Unprocessed {
+                        count: requests.len(),
+                    });
+                }
+
+                sleep(BATCH_WRITE_RETRY_DELAY * 2u32.pow(attempt - 1)).await;
+
+                attempt += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a transaction was cancelled by the condition of its item at `index`.
+fn cancelled_by_condition(err: Option<&TransactWriteItemsError>, index: usize) -> bool {
+    match err {
+        Some(TransactWriteItemsError::TransactionCanceledException(exception)) => exception
+            .cancellation_reasons()
+            .get(index)
+            .is_some_and(|reason| reason.code() == Some("ConditionalCheckFailed")),
+        _ => false,
+    }
+}
+
+impl TodoRepository for DynamoTodoRepository<'_> {
+    async fn create(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = TodoKey::new(&todo.list_id, &todo.id)?;
+
+        let mut item = todo.to_item();
+        item.extend(key.to_key());
+
+        let put_todo = Put::builder()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .build()?;
+
+        // the event is published by the outbox relay once the transaction is committed
+        let res = self
+            .dynamodb_client
+            .transact_write_items()
+            .transact_items(TransactWriteItem::builder().put(put_todo).build())
+            .transact_items(
+                TransactWriteItem::builder()
+                    .put(outbox_record.to_put(self.table_name)?)
+                    .build(),
+            )
+            .transact_items(self.check_list_open(&ListKey::new(&todo.list_id)?)?)
+            .send()
+            .await;
+
+        match res {
+            Ok(_) => Ok(()),
+            // the outbox record already exists
+            Err(err) if cancelled_by_condition(err.as_service_error(), 1) => {
+                Err(RepositoryError::Conflict)
+            }
+            Err(err) if cancelled_by_condition(err.as_service_error(), 2) => {
+                Err(RepositoryError::ListClosed)
+            }
+            Err(err) => Err(aws_sdk_dynamodb::Error::from(err).into()),
+        }
+    }
+
+    async fn get(&self, key: &TodoKey) -> Result<Option<Todo>, RepositoryError> {
+        let res = self
+            .dynamodb_client
+            .get_item()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        Ok(res.item.map(Todo::from_item).transpose()?)
+    }
+
+    async fn list(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<TodoPage, RepositoryError> {
+        let partition_key = todo_list_partition_key(list_id)?;
+
+        let res = self
+            .dynamodb_client
+            .query()
+            .table_name(self.table_name)
+            // the list counter lives in the same partition as the todos, it must not use up
+            // the limit
+            .key_condition_expression("PK = :PK AND begins_with(SK, :SK)")
+            .expression_attribute_values(":PK", AttributeValue::S(partition_key))
+            .expression_attribute_values(":SK", AttributeValue::S(TodoKey::SORT_KEY_PREFIX.into()))
+            .limit(limit)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        let todos = res
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(Todo::from_item)
+            .collect();
+
+        Ok(TodoPage {
+            todos,
+            last_evaluated_key: res.last_evaluated_key,
+        })
+    }
+
+    async fn delete(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = TodoKey::new(&todo.list_id, &todo.id)?;
+
+        // the event carries the completion state used by the counters, make sure it
+        // did not change since the todo was read
+        let delete_todo = Delete::builder()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .expression_attribute_values(":completed", AttributeValue::Bool(todo.completed))
+            .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld);
+
+        let delete_todo = if todo.completed {
+            delete_todo.condition_expression("completed = :completed")
+        } else {
+            delete_todo.condition_expression(
+                "attribute_exists(PK) AND (attribute_not_exists(completed) OR completed = :completed)",
+            )
+        };
+
+        self.write_todo(
+            &todo.list_id,
+            TransactWriteItem::builder()
+                .delete(delete_todo.build()?)
+                .build(),
+            outbox_record,
+        )
+        .await
+    }
+
+    async fn update(
+        &self,
+        old: &Todo,
+        new: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = TodoKey::new(&old.list_id, &old.id)?;
+
+        // the event carries the todo that was read, make sure it did not change since. Todos
+        // created before completion existed have no `completed` attribute and count as open.
+        let condition_expression = match old.completed {
+            true => "#title = :old_title AND #description = :old_description AND completed = :old_completed",
+            false => "#title = :old_title AND #description = :old_description AND (attribute_not_exists(completed) OR completed = :old_completed)",
+        };
+
+        let update_todo = Update::builder()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .update_expression("SET #title = :title, #description = :description")
+            .condition_expression(condition_expression)
+            .expression_attribute_names("#title", "title")
+            .expression_attribute_names("#description", "description")
+            .expression_attribute_values(":title", AttributeValue::S(new.title.clone()))
+            .expression_attribute_values(":description", AttributeValue::S(new.description.clone()))
+            .expression_attribute_values(":old_title", AttributeValue::S(old.title.clone()))
+            .expression_attribute_values(
+                ":old_description",
+                AttributeValue::S(old.description.clone()),
+            )
+            .expression_attribute_values(":old_completed", AttributeValue::Bool(old.completed))
+            .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld)
+            .build()?;
+
+        self.write_todo(
+            &old.list_id,
+            TransactWriteItem::builder().update(update_todo).build(),
+            outbox_record,
+        )
+        .await
+    }
+
+    async fn set_completion(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = TodoKey::new(&todo.list_id, &todo.id)?;
+
+        let update_todo = Update::builder()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .expression_attribute_values(":completed", AttributeValue::Bool(todo.completed))
+            .expression_attribute_values(":previous", AttributeValue::Bool(!todo.completed))
+            .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld);
+
+        // Todos created before completion existed have no `completed` attribute and count as open.
+        let update_todo = match todo.completed_at {
+            Some(completed_at) => update_todo
+                .update_expression("SET completed = :completed, completed_at = :completed_at")
+                .condition_expression(
+                    "attribute_exists(PK) AND (attribute_not_exists(completed) OR completed = :previous)",
+                )
+                .expression_attribute_values(
+                    ":completed_at",
+                    AttributeValue::S(completed_at.to_rfc3339()),
+                ),
+            None => update_todo
+                .update_expression("SET completed = :completed REMOVE completed_at")
+                .condition_expression("completed = :previous"),
+        };
+
+        self.write_todo(
+            &todo.list_id,
+            TransactWriteItem::builder()
+                .update(update_todo.build()?)
+                .build(),
+            outbox_record,
+        )
+        .await
+    }
+
+    async fn get_counter(&self, list_id: &str) -> Result<ListCounter, RepositoryError> {
+        let key = ListCounterKey::new(list_id)?;
+
+        let res = self
+            .dynamodb_client
+            .get_item()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        // the counter is only created along with the first todo of the list
+        Ok(res
+            .item
+            .map(ListCounter::from_item)
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    async fn increment_counter(
+        &self,
+        list_id: &str,
+        increment: CounterIncrement,
+        consumer: &str,
+        event_id: &str,
+    ) -> Result<Processing, RepositoryError> {
+        let key = ListCounterKey::new(list_id)?;
+
+        let update = Update::builder()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .update_expression("ADD todosCount :todos_count, completedCount :completed_count")
+            .expression_attribute_values(
+                ":todos_count",
+                AttributeValue::N(increment.todos_count.to_string()),
+            )
+            .expression_attribute_values(
+                ":completed_count",
+                AttributeValue::N(increment.completed_count.to_string()),
+            )
+            .build()?;
+
+        // late events of a deleted list must not recreate its counter
+        let list_exists = ConditionCheck::builder()
+            .table_name(self.table_name)
+            .set_key(Some(ListKey::new(list_id)?.to_key()))
+            .condition_expression("attribute_exists(PK)")
+            .build()?;
+
+        Ok(update_once(
+            self.dynamodb_client,
+            self.table_name,
+            consumer,
+            event_id,
+            update,
+            Some(list_exists),
+        )
+        .await?)
+    }
+
+    async fn get_member(&self, key: &ListMemberKey) -> Result<Option<ListMember>, RepositoryError> {
+        let res = self
+            .dynamodb_client
+            .get_item()
+            .table_name(self.table_name)
+            .set_key(Some(key.to_key()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        Ok(res.item.map(ListMember::from_item).transpose()?)
+    }
+
+    async fn add_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut item = member.to_item();
+        item.extend(ListMemberKey::new(&member.list_id, &member.user_id)?.to_key());
+
+        let put_member = Put::builder()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(PK)")
+            .build()?;
+
+        self.write_items(
+            vec![TransactWriteItem::builder().put(put_member).build()],
+            outbox_record,
+            RepositoryError::Conflict,
+        )
+        .await
+    }
+
+    async fn update_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut item = member.to_item();
+        item.extend(ListMemberKey::new(&member.list_id, &member.user_id)?.to_key());
+
+        let put_member = Put::builder()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_exists(PK)")
+            .build()?;
+
+        self.write_items(
+            vec![TransactWriteItem::builder().put(put_member).build()],
+            outbox_record,
+            RepositoryError::NotFound,
+        )
+        .await
+    }
+
+    async fn remove_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let delete_member = Delete::builder()
+            .table_name(self.table_name)
+            .set_key(Some(
+                ListMemberKey::new(&member.list_id, &member.user_id)?.to_key(),
+            ))
+            .condition_expression("attribute_exists(PK)")
+            .build()?;
+
+        self.write_items(
+            vec![TransactWriteItem::builder().delete(delete_member).build()],
+            outbox_record,
+            RepositoryError::NotFound,
+        )
+        .await
+    }
+
+    async fn create_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        self.write_items(
+            self.put_list(
+                list,
+                &ListKey::new(&list.id)?,
+                &OwnerListKey::new(&list.owner, &list.id)?,
+                "attribute_not_exists(PK)",
+            )?,
+            outbox_record,
+            RepositoryError::Conflict,
+        )
+        .await
+    }
+
+    async fn get_list(&self, list_id: &str) -> Result<Option<List>, RepositoryError> {
+        let res = self
+            .dynamodb_client
+            .get_item()
+            .table_name(self.table_name)
+            .set_key(Some(ListKey::new(list_id)?.to_key()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        Ok(res.item.map(List::from_item).transpose()?)
+    }
+
+    async fn update_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        self.write_items(
+            self.put_list(
+                list,
+                &ListKey::new(&list.id)?,
+                &OwnerListKey::new(&list.owner, &list.id)?,
+                "attribute_exists(PK)",
+            )?,
+            outbox_record,
+            RepositoryError::NotFound,
+        )
+        .await
+    }
+
+    async fn request_list_deletion(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut item = deletion.to_item();
+        item.extend(ListDeletionKey::new(&deletion.list_id)?.to_key());
+
+        let put_deletion = Put::builder()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(PK)")
+            .build()?;
+
+        let mut transact_items = vec![TransactWriteItem::builder().put(put_deletion).build()];
+
+        transact_items.extend(self.put_list(
+            list,
+            &ListKey::new(&list.id)?,
+            &OwnerListKey::new(&list.owner, &list.id)?,
+            "attribute_exists(PK)",
+        )?);
+
+        transact_items.push(
+            TransactWriteItem::builder()
+                .put(outbox_record.to_put(self.table_name)?)
+                .build(),
+        );
+
+        let res = self
+            .dynamodb_client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await;
+
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) if cancelled_by_condition(err.as_service_error(), 0) => {
+                Err(RepositoryError::Conflict)
+            }
+            Err(err) if cancelled_by_condition(err.as_service_error(), 1) => {
+                Err(RepositoryError::NotFound)
+            }
+            Err(err) => Err(aws_sdk_dynamodb::Error::from(err).into()),
+        }
+    }
+
+    async fn get_list_deletion(
+        &self,
+        list_id: &str,
+    ) -> Result<Option<ListDeletion>, RepositoryError> {
+        let res = self
+            .dynamodb_client
+            .get_item()
+            .table_name(self.table_name)
+            .set_key(Some(ListDeletionKey::new(list_id)?.to_key()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        Ok(res.item.map(ListDeletion::from_item).transpose()?)
+    }
+
+    async fn update_list_deletion(&self, deletion: &ListDeletion) -> Result<(), RepositoryError> {
+        let mut item = deletion.to_item();
+        item.extend(ListDeletionKey::new(&deletion.list_id)?.to_key());
+
+        self.dynamodb_client
+            .put_item()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        Ok(())
+    }
+
+    async fn delete_todos(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<DeletedTodos, RepositoryError> {
+        let partition_key = todo_list_partition_key(list_id)?;
+
+        // only the keys are read, a todo that can't be parsed must not be left behind
+        let res = self
+            .dynamodb_client
+            .query()
+            .table_name(self.table_name)
+            .key_condition_expression("PK = :PK AND begins_with(SK, :SK)")
+            .projection_expression("PK, SK")
+            .expression_attribute_values(":PK", AttributeValue::S(partition_key))
+            .expression_attribute_values(":SK", AttributeValue::S(TodoKey::SORT_KEY_PREFIX.into()))
+            .limit(limit)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        let keys = res.items.unwrap_or_default();
+        let count = keys.len();
+
+        self.batch_delete(keys).await?;
+
+        Ok(DeletedTodos {
+            count,
+            last_evaluated_key: res.last_evaluated_key,
+        })
+    }
+
+    async fn delete_members(&self, list_id: &str) -> Result<usize, RepositoryError> {
+        let partition_key = list_partition_key(list_id)?;
+
+        let mut count = 0;
+        let mut exclusive_start_key = None;
+
+        loop {
+            let res = self
+                .dynamodb_client
+                .query()
+                .table_name(self.table_name)
+                .key_condition_expression("PK = :PK AND begins_with(SK, :SK)")
+                .expression_attribute_values(":PK", AttributeValue::S(partition_key.clone()))
+                .expression_attribute_values(
+                    ":SK",
+                    AttributeValue::S(ListMemberKey::SORT_KEY_PREFIX.into()),
+                )
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .await
+                .map_err(aws_sdk_dynamodb::Error::from)?;
+
+            let keys: Vec<_> = res
+                .items
+                .unwrap_or_default()
+                .iter()
+                .map(|item| ListMemberKey::from_key(item).map(|key| key.to_key()))
+                .collect::<Result<_, _>>()?;
+
+            count += keys.len();
+
+            self.batch_delete(keys).await?;
+
+            exclusive_start_key = res.last_evaluated_key;
+
+            if exclusive_start_key.is_none() {
+                return Ok(count);
+            }
+        }
+    }
+
+    async fn delete_list(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let delete_list = Delete::builder()
+            .table_name(self.table_name)
+            .set_key(Some(ListKey::new(&list.id)?.to_key()))
+            .condition_expression("attribute_exists(PK)")
+            .build()?;
+
+        let delete_owner_list = Delete::builder()
+            .table_name(self.table_name)
+            .set_key(Some(OwnerListKey::new(&list.owner, &list.id)?.to_key()))
+            .build()?;
+
+        let delete_counter = Delete::builder()
+            .table_name(self.table_name)
+            .set_key(Some(ListCounterKey::new(&list.id)?.to_key()))
+            .build()?;
+
+        // the completed deletion is only kept for the clients to poll it
+        let expires_at =
+            deletion.completed_at.unwrap_or_else(Utc::now) + COMPLETED_LIST_DELETION_TTL;
+
+        let mut item = deletion.to_item();
+        item.extend(ListDeletionKey::new(&deletion.list_id)?.to_key());
+        item.insert(
+            EXPIRES_AT_ATTRIBUTE.into(),
+            AttributeValue::N(expires_at.timestamp().to_string()),
+        );
+
+        let put_deletion = Put::builder()
+            .table_name(self.table_name)
+            .set_item(Some(item))
+            .build()?;
+
+        self.write_items(
+            vec![
+                TransactWriteItem::builder().delete(delete_list).build(),
+                TransactWriteItem::builder()
+                    .delete(delete_owner_list)
+                    .build(),
+                TransactWriteItem::builder().delete(delete_counter).build(),
+                TransactWriteItem::builder().put(put_deletion).build(),
+            ],
+            outbox_record,
+            RepositoryError::NotFound,
+        )
+        .await
+    }
+
+    async fn owner_lists(
+        &self,
+        owner: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<ListPage, RepositoryError> {
+        let res = self
+            .dynamodb_client
+            .query()
+            .table_name(self.table_name)
+            .key_condition_expression("PK = :PK")
+            .expression_attribute_values(
+                ":PK",
+                AttributeValue::S(owner_lists_partition_key(owner)?),
+            )
+            .limit(limit)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await
+            .map_err(aws_sdk_dynamodb::Error::from)?;
+
+        let lists = res
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .map(List::from_item)
+            .collect::<Result<_, _>>()?;
+
+        Ok(ListPage {
+            lists,
+            last_evaluated_key: res.last_evaluated_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_mocks::{mock, mock_client, MockResponse, RuleMode};
+
+    use aws_sdk_dynamodb::{
+        config::{
+            interceptors::BeforeSerializationInterceptorContextRef, retry::RetryConfig,
+            timeout::TimeoutConfig, ConfigBag, Intercept, RuntimeComponents,
+        },
+        error::BoxError,
+        operation::{
+            batch_write_item::{BatchWriteItemInput, BatchWriteItemOutput},
+            get_item::GetItemOutput,
+            query::QueryOutput,
+            transact_write_items::TransactWriteItemsOutput,
+        },
+        types::{error::TransactionCanceledException, CancellationReason},
+    };
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{testing, DeletionStatus, EventEnvelope, Role, TodoEvent, TodoUpdate};
+
+    fn todo(completed: bool) -> Todo {
+        Todo {
+            completed,
+            owner: Some("titi".into()),
+            ..testing::todo("toto", "tata")
+        }
+    }
+
+    fn todo_key(todo_id: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("PK".into(), AttributeValue::S("TODO#toto".into())),
+            ("SK".into(), AttributeValue::S(format!("ID#{todo_id}"))),
+        ])
+    }
+
+    fn todo_item(todo_id: &str) -> HashMap<String, AttributeValue> {
+        let mut item = todo_key(todo_id);
+        item.extend([
+            ("id".into(), AttributeValue::S(todo_id.into())),
+            ("list_id".into(), AttributeValue::S("toto".into())),
+            ("title".into(), AttributeValue::S("Toto todo".into())),
+            (
+                "description".into(),
+                AttributeValue::S("This is a great description".into()),
+            ),
+            ("completed".into(), AttributeValue::Bool(true)),
+        ]);
+
+        item
+    }
+
+    fn outbox_record(event: TodoEvent) -> OutboxRecord {
+        OutboxRecord::new(&EventEnvelope::new(event)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let items: Vec<_> = req
+                    .transact_items()
+                    .iter()
+                    .flat_map(|item| item.put())
+                    .map(|put| put.item())
+                    .collect();
+
+                let list_check = req
+                    .transact_items()
+                    .last()
+                    .and_then(|item| item.condition_check());
+
+                matches!(items[..], [todo, outbox_record]
+                    if todo.get("PK") == Some(&AttributeValue::S("TODO#toto".into()))
+                        && todo.get("title") == Some(&AttributeValue::S("Toto todo".into()))
+                        && todo.get("completed") == Some(&AttributeValue::Bool(false))
+                        && todo.get("owner") == Some(&AttributeValue::S("titi".into()))
+                        && outbox_record.get("detail_type")
+                            == Some(&AttributeValue::S("TODO_CREATED".into())))
+                    && list_check.is_some_and(|check| {
+                        check.key().get("PK") == Some(&AttributeValue::S("LIST#toto".into()))
+                            && check.condition_expression()
+                                == "attribute_exists(PK) AND archived = :false"
+                    })
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        repository
+            .create(
+                &todo(false),
+                &outbox_record(TodoEvent::TodoCreated(todo(false))),
+            )
+            .await
+            .expect("failed to create todo");
+
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_in_closed_list() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| {
+                TransactWriteItemsError::TransactionCanceledException(
+                    TransactionCanceledException::builder()
+                        .cancellation_reasons(CancellationReason::builder().code("None").build())
+                        .cancellation_reasons(CancellationReason::builder().code("None").build())
+                        .cancellation_reasons(
+                            CancellationReason::builder()
+                                .code("ConditionalCheckFailed")
+                                .build(),
+                        )
+                        .build(),
+                )
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let err = repository
+            .create(
+                &todo(false),
+                &outbox_record(TodoEvent::TodoCreated(todo(false))),
+            )
+            .await
+            .expect_err("the list should be closed");
+
+        assert!(matches!(err, RepositoryError::ListClosed));
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let mock_get_item = mock!(aws_sdk_dynamodb::Client::get_item)
+            .match_requests(|req| req.key() == Some(&todo_key("tata")))
+            .then_output(|| {
+                let mut item = todo_item("tata");
+                item.remove("completed");
+
+                GetItemOutput::builder().set_item(Some(item)).build()
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_get_item]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let todo = repository
+            .get(&TodoKey::new("toto", "tata").unwrap())
+            .await
+            .expect("failed to get todo")
+            .expect("todo should exist");
+
+        assert_eq!(todo.id, "tata");
+        assert_eq!(todo.list_id, "toto");
+        // items written before completion existed default to not completed
+        assert!(!todo.completed);
+        assert_eq!(todo.completed_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let mock_query = mock!(aws_sdk_dynamodb::Client::query)
+            .match_requests(|req| {
+                req.limit() == Some(2)
+                    && req.exclusive_start_key().is_none()
+                    && req.key_condition_expression() == Some("PK = :PK AND begins_with(SK, :SK)")
+                    && req
+                        .expression_attribute_values()
+                        .and_then(|values| values.get(":SK"))
+                        == Some(&AttributeValue::S("ID#".into()))
+            })
+            .then_output(|| {
+                QueryOutput::builder()
+                    .items(todo_item("tata"))
+                    .set_last_evaluated_key(Some(todo_key("tata")))
+                    .build()
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_query]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let page = repository
+            .list("toto", 2, None)
+            .await
+            .expect("failed to list todos");
+
+        assert_eq!(page.todos.len(), 1);
+        assert_eq!(page.todos[0].id, "tata");
+        assert_eq!(page.last_evaluated_key, Some(todo_key("tata")));
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let items = req.transact_items();
+
+                items[0].delete().is_some_and(|delete| {
+                    delete.condition_expression() == Some("completed = :completed")
+                }) && items[1].put().is_some_and(|put| {
+                    put.item().get("detail_type") == Some(&AttributeValue::S("TODO_DELETED".into()))
+                })
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        repository
+            .delete(
+                &todo(true),
+                &outbox_record(TodoEvent::TodoDeleted(todo(true))),
+            )
+            .await
+            .expect("failed to delete todo");
+
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_condition_check_failure() {
+        for (item, expected) in [
+            (Some(todo_item("tata")), "conflicting write"),
+            (None, "item not found"),
+        ] {
+            let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+                .then_error(move || {
+                    TransactWriteItemsError::TransactionCanceledException(
+                        TransactionCanceledException::builder()
+                            .cancellation_reasons(
+                                CancellationReason::builder()
+                                    .code("ConditionalCheckFailed")
+                                    .set_item(item.clone())
+                                    .build(),
+                            )
+                            .cancellation_reasons(
+                                CancellationReason::builder().code("None").build(),
+                            )
+                            .build(),
+                    )
+                });
+            let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+            let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+            let err = repository
+                .delete(
+                    &todo(true),
+                    &outbox_record(TodoEvent::TodoDeleted(todo(true))),
+                )
+                .await
+                .expect_err("deletion should fail");
+
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let items = req.transact_items();
+
+                items[0].update().is_some_and(|update| {
+                    let values = update.expression_attribute_values();
+                    let value = |name: &str| values.and_then(|values| values.get(name));
+
+                    value(":title") == Some(&AttributeValue::S("Tata todo".into()))
+                        && value(":old_title") == Some(&AttributeValue::S("Toto todo".into()))
+                        && update.condition_expression().is_some_and(|condition| {
+                            condition.contains("attribute_not_exists(completed)")
+                        })
+                }) && items[1].put().is_some_and(|put| {
+                    put.item().get("detail_type") == Some(&AttributeValue::S("TODO_UPDATED".into()))
+                })
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let new_todo = Todo {
+            title: "Tata todo".into(),
+            ..todo(false)
+        };
+
+        repository
+            .update(
+                &todo(false),
+                &new_todo,
+                &outbox_record(TodoEvent::TodoUpdated(TodoUpdate {
+                    old: todo(false),
+                    new: new_todo.clone(),
+                })),
+            )
+            .await
+            .expect("failed to update todo");
+
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_completion() {
+        let completed_todo = Todo {
+            completed_at: Some("2024-05-01T10:00:00Z".parse().unwrap()),
+            ..todo(true)
+        };
+
+        for (todo, update_expression, condition_expression, detail_type) in [
+            (
+                completed_todo,
+                "SET completed = :completed, completed_at = :completed_at",
+                "attribute_exists(PK) AND (attribute_not_exists(completed) OR completed = :previous)",
+                "TODO_COMPLETED",
+            ),
+            (
+                todo(false),
+                "SET completed = :completed REMOVE completed_at",
+                "completed = :previous",
+                "TODO_REOPENED",
+            ),
+        ] {
+            let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+                .match_requests(move |req| {
+                    let items = req.transact_items();
+
+                    items[0].update().is_some_and(|update| {
+                        update.update_expression() == update_expression
+                            && update.condition_expression() == Some(condition_expression)
+                    }) && items[1].put().is_some_and(|put| {
+                        put.item().get("detail_type")
+                            == Some(&AttributeValue::S(detail_type.into()))
+                    })
+                })
+                .then_output(|| TransactWriteItemsOutput::builder().build());
+            let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+            let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+            let event = match todo.completed {
+                true => TodoEvent::TodoCompleted(todo.clone()),
+                false => TodoEvent::TodoReopened(todo.clone()),
+            };
+
+            repository
+                .set_completion(&todo, &outbox_record(event))
+                .await
+                .expect("failed to set todo completion");
+
+            assert_eq!(mock_transact_write_items.num_calls(), 1, "{detail_type}");
+        }
+    }
+
+    fn list() -> List {
+        testing::list("toto", "titi")
+    }
+
+    #[tokio::test]
+    async fn test_create_list() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let items: Vec<_> = req
+                    .transact_items()
+                    .iter()
+                    .flat_map(|item| item.put())
+                    .collect();
+
+                matches!(items[..], [list, owner_list, outbox_record]
+                    if list.item().get("PK") == Some(&AttributeValue::S("LIST#toto".into()))
+                        && list.condition_expression() == Some("attribute_not_exists(PK)")
+                        && owner_list.item().get("PK")
+                            == Some(&AttributeValue::S("OWNER#titi".into()))
+                        && owner_list.item().get("name")
+                            == Some(&AttributeValue::S("Toto list".into()))
+                        && outbox_record.item().get("detail_type")
+                            == Some(&AttributeValue::S("LIST_CREATED".into())))
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        repository
+            .create_list(&list(), &outbox_record(TodoEvent::ListCreated(list())))
+            .await
+            .expect("failed to create list");
+
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_owner_lists() {
+        let mock_query = mock!(aws_sdk_dynamodb::Client::query)
+            .match_requests(|req| {
+                req.expression_attribute_values()
+                    .and_then(|values| values.get(":PK"))
+                    == Some(&AttributeValue::S("OWNER#titi".into()))
+            })
+            .then_output(|| {
+                let mut item = list().to_item();
+                item.extend(OwnerListKey::new("titi", "toto").unwrap().to_key());
+
+                QueryOutput::builder().items(item).build()
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_query]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let page = repository
+            .owner_lists("titi", 2, None)
+            .await
+            .expect("failed to list lists");
+
+        assert_eq!(page.lists, [list()]);
+        assert_eq!(page.last_evaluated_key, None);
+    }
+
+    fn member() -> ListMember {
+        ListMember {
+            list_id: "toto".into(),
+            user_id: "arn:aws:iam::123456789012:user/tata".into(),
+            role: Role::Viewer,
+            added_at: "2024-05-01T10:00:00Z".parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_list_deletion_of_missing_list() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| {
+                let reason = |code: &str| CancellationReason::builder().code(code).build();
+
+                TransactWriteItemsError::TransactionCanceledException(
+                    TransactionCanceledException::builder()
+                        .set_cancellation_reasons(Some(vec![
+                            reason("None"),
+                            reason("ConditionalCheckFailed"),
+                            reason("None"),
+                            reason("None"),
+                        ]))
+                        .build(),
+                )
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let deletion = ListDeletion {
+            list_id: "toto".into(),
+            status: DeletionStatus::InProgress,
+            requested_by: "titi".into(),
+            requested_at: "2024-05-01T10:00:00Z".parse().unwrap(),
+            deleted_todos: 0,
+            completed_at: None,
+        };
+
+        let err = repository
+            .request_list_deletion(
+                &list(),
+                &deletion,
+                &outbox_record(TodoEvent::ListDeletionRequested(deletion.clone())),
+            )
+            .await
+            .expect_err("the list should not be found");
+
+        assert!(matches!(err, RepositoryError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_delete_todos() {
+        // a todo written without a title can't be read, it must be deleted all the same
+        let mut malformed = todo_key("tutu");
+        malformed.insert("title".into(), AttributeValue::N("1".into()));
+
+        let items: Vec<_> = (0..29)
+            .map(|i| todo_item(&format!("tata{i}")))
+            .chain([malformed])
+            .collect();
+
+        assert!(Todo::from_item(items[29].clone()).is_err());
+
+        let mock_query = mock!(aws_sdk_dynamodb::Client::query)
+            .match_requests(|req| {
+                req.projection_expression() == Some("PK, SK")
+                    && req.key_condition_expression() == Some("PK = :PK AND begins_with(SK, :SK)")
+                    && req.limit() == Some(30)
+            })
+            .then_output(move || {
+                QueryOutput::builder()
+                    .set_items(Some(
+                        items
+                            .iter()
+                            .map(|item| {
+                                item.iter()
+                                    .filter(|(name, _)| *name == "PK" || *name == "SK")
+                                    .map(|(name, value)| (name.clone(), value.clone()))
+                                    .collect()
+                            })
+                            .collect(),
+                    ))
+                    .last_evaluated_key("PK", AttributeValue::S("TODO#toto".into()))
+                    .last_evaluated_key("SK", AttributeValue::S("ID#tutu".into()))
+                    .build()
+            });
+
+        let batch_sizes = Arc::new(Mutex::new(Vec::new()));
+        let deleted = Arc::new(Mutex::new(HashSet::new()));
+
+        let mock_batch_write_item = mock!(aws_sdk_dynamodb::Client::batch_write_item)
+            .then_compute_output({
+                let batch_sizes = batch_sizes.clone();
+                let deleted = deleted.clone();
+
+                move |req| {
+                    let requests = req.request_items().unwrap()["toto"].clone();
+                    let mut batch_sizes = batch_sizes.lock().unwrap();
+
+                    batch_sizes.push(requests.len());
+
+                    // the last 2 todos of the first batch are throttled
+                    let unprocessed_items = match batch_sizes.len() {
+                        1 => Some(HashMap::from([("toto".into(), requests[23..].to_vec())])),
+                        _ => None,
+                    };
+
+                    // the throttled todos are sent again, the keys are only counted once
+                    deleted.lock().unwrap().extend(
+                        requests
+                            .iter()
+                            .flat_map(|request| request.delete_request())
+                            .map(|request| request.key()["SK"].as_s().unwrap().clone()),
+                    );
+
+                    BatchWriteItemOutput::builder()
+                        .set_unprocessed_items(unprocessed_items)
+                        .build()
+                }
+            });
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::MatchAny,
+            &[&mock_query, &mock_batch_write_item]
+        );
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let page = repository
+            .delete_todos("toto", 30, None)
+            .await
+            .expect("failed to delete todos");
+
+        assert_eq!(page.count, 30);
+        assert!(page.last_evaluated_key.is_some());
+        assert_eq!(*batch_sizes.lock().unwrap(), [25, 2, 5]);
+
+        let deleted = deleted.lock().unwrap();
+
+        assert_eq!(deleted.len(), 30);
+        assert!(deleted.contains("ID#tutu"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_todos_unprocessed() {
+        let mock_query = mock!(aws_sdk_dynamodb::Client::query)
+            .then_output(|| QueryOutput::builder().items(todo_key("tata")).build());
+        let mock_batch_write_item = mock!(aws_sdk_dynamodb::Client::batch_write_item)
+            .then_compute_output(|req| {
+                BatchWriteItemOutput::builder()
+                    .set_unprocessed_items(req.request_items().cloned())
+                    .build()
+            });
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::MatchAny,
+            &[&mock_query, &mock_batch_write_item]
+        );
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let err = repository
+            .delete_todos("toto", 30, None)
+            .await
+            .expect_err("the todo should be left unprocessed");
+
+        assert!(matches!(err, RepositoryError::Unprocessed { count: 1 }));
+        assert_eq!(
+            mock_batch_write_item.num_calls(),
+            MAX_BATCH_WRITE_ATTEMPTS as usize
+        );
+    }
+
+    /// The retry and timeout configs the batch writes are sent with.
+    #[derive(Debug, Default, Clone)]
+    struct BatchWritePolicies(Arc<Mutex<Vec<(RetryConfig, TimeoutConfig)>>>);
+
+    impl Intercept for BatchWritePolicies {
+        fn name(&self) -> &'static str {
+            "BatchWritePolicies"
+        }
+
+        fn read_before_serialization(
+            &self,
+            context: &BeforeSerializationInterceptorContextRef<'_>,
+            _runtime_components: &RuntimeComponents,
+            cfg: &mut ConfigBag,
+        ) -> Result<(), BoxError> {
+            if context
+                .input()
+                .downcast_ref::<BatchWriteItemInput>()
+                .is_some()
+            {
+                self.0.lock().unwrap().push((
+                    cfg.load::<RetryConfig>().cloned().unwrap(),
+                    cfg.load::<TimeoutConfig>().cloned().unwrap(),
+                ));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_todos_policies() {
+        let mock_query = mock!(aws_sdk_dynamodb::Client::query)
+            .then_output(|| QueryOutput::builder().items(todo_key("tata")).build());
+        let mock_batch_write_item = mock!(aws_sdk_dynamodb::Client::batch_write_item)
+            .then_output(|| BatchWriteItemOutput::builder().build());
+        let policies = BatchWritePolicies::default();
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::MatchAny,
+            &[&mock_query, &mock_batch_write_item],
+            |config| config.interceptor(policies.clone())
+        );
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        repository
+            .delete_todos("toto", 30, None)
+            .await
+            .expect("failed to delete todos");
+
+        // the client policies are overridden for the batch writes only
+        assert_ne!(
+            dynamodb_client.config().retry_config(),
+            Some(&RetryConfig::from(BATCH_WRITE_RETRY_POLICY))
+        );
+        assert_eq!(
+            *policies.0.lock().unwrap(),
+            vec![(
+                BATCH_WRITE_RETRY_POLICY.into(),
+                BATCH_WRITE_TIMEOUT_POLICY.into()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_member() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                let items = req.transact_items();
+
+                items[0].put().is_some_and(|put| {
+                    put.condition_expression() == Some("attribute_not_exists(PK)")
+                        && put.item().get("SK")
+                            == Some(&AttributeValue::S(
+                                "MEMBER#arn:aws:iam::123456789012:user/tata".into(),
+                            ))
+                        && put.item().get("role") == Some(&AttributeValue::S("viewer".into()))
+                }) && items[1].put().is_some_and(|put| {
+                    put.item().get("detail_type") == Some(&AttributeValue::S("LIST_SHARED".into()))
+                })
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        repository
+            .add_member(&member(), &outbox_record(TodoEvent::ListShared(member())))
+            .await
+            .expect("failed to add member");
+
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_member_condition_check_failure() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| {
+                TransactWriteItemsError::TransactionCanceledException(
+                    TransactionCanceledException::builder()
+                        .cancellation_reasons(
+                            CancellationReason::builder()
+                                .code("ConditionalCheckFailed")
+                                .build(),
+                        )
+                        .cancellation_reasons(CancellationReason::builder().code("None").build())
+                        .build(),
+                )
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let err = repository
+            .add_member(&member(), &outbox_record(TodoEvent::ListShared(member())))
+            .await
+            .expect_err("the member should exist");
+
+        assert!(matches!(err, RepositoryError::Conflict));
+    }
+
+    #[tokio::test]
+    async fn test_get_counter() {
+        let mock_get_item = mock!(aws_sdk_dynamodb::Client::get_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("SK"))
+                    == Some(&AttributeValue::S("COUNTER".into()))
+            })
+            .then_output(|| {
+                GetItemOutput::builder()
+                    .set_item(Some(HashMap::from([
+                        ("PK".into(), AttributeValue::S("TODO#toto".into())),
+                        ("SK".into(), AttributeValue::S("COUNTER".into())),
+                        ("todosCount".into(), AttributeValue::N("3".into())),
+                        ("completedCount".into(), AttributeValue::N("1".into())),
+                    ])))
+                    .build()
+            });
+        let mock_empty_get_item = mock!(aws_sdk_dynamodb::Client::get_item)
+            .then_output(|| GetItemOutput::builder().build());
+        let dynamodb_client =
+            mock_client!(aws_sdk_dynamodb, &[&mock_get_item, &mock_empty_get_item]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let counter = repository.get_counter("toto").await.unwrap();
+
+        assert_eq!(counter.todos_count, 3);
+        assert_eq!(counter.completed_count, 1);
+
+        let counter = repository.get_counter("toto").await.unwrap();
+
+        assert_eq!(counter.todos_count, 0);
+        assert_eq!(counter.completed_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_increment_counter_replayed_event() {
+        let processed = Arc::new(Mutex::new(HashSet::new()));
+        let increments = Arc::new(Mutex::new(0));
+
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_compute_response({
+                let processed = processed.clone();
+                let increments = increments.clone();
+
+                move |req| {
+                    let items = req.transact_items();
+                    let record = items[0].put().expect("missing event record");
+                    let counter = items[1].update().expect("missing counter update");
+                    let list_check = items[2].condition_check().expect("missing list check");
+
+                    assert_eq!(
+                        record.condition_expression(),
+                        Some("attribute_not_exists(PK)")
+                    );
+                    assert_eq!(
+                        counter.key().get("SK"),
+                        Some(&AttributeValue::S("COUNTER".into()))
+                    );
+                    assert_eq!(
+                        counter.update_expression(),
+                        "ADD todosCount :todos_count, completedCount :completed_count"
+                    );
+                    assert_eq!(
+                        list_check.key().get("PK"),
+                        Some(&AttributeValue::S("LIST#toto".into()))
+                    );
+
+                    if !processed
+                        .lock()
+                        .unwrap()
+                        .insert(record.item()["PK"].as_s().unwrap().clone())
+                    {
+                        return MockResponse::Error(
+                            TransactWriteItemsError::TransactionCanceledException(
+                                TransactionCanceledException::builder()
+                                    .cancellation_reasons(
+                                        CancellationReason::builder()
+                                            .code("ConditionalCheckFailed")
+                                            .build(),
+                                    )
+                                    .cancellation_reasons(
+                                        CancellationReason::builder().code("None").build(),
+                                    )
+                                    .build(),
+                            ),
+                        );
+                    }
+
+                    *increments.lock().unwrap() += 1;
+
+                    MockResponse::Output(TransactWriteItemsOutput::builder().build())
+                }
+            });
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::MatchAny,
+            &[&mock_transact_write_items]
+        );
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        let increment = CounterIncrement {
+            todos_count: -1,
+            completed_count: -1,
+        };
+
+        for (event_id, expected) in [
+            ("event-1", Processing::Processed),
+            ("event-1", Processing::Duplicate),
+            ("event-2", Processing::Processed),
+        ] {
+            let processing = repository
+                .increment_counter("toto", increment, "toto", event_id)
+                .await
+                .expect("failed to increment counter");
+
+            assert_eq!(processing, expected);
+        }
+
+        // the replayed event is only counted once
+        assert_eq!(*increments.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_increment_counter_failed_update() {
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .then_error(|| {
+                TransactWriteItemsError::TransactionCanceledException(
+                    TransactionCanceledException::builder()
+                        .cancellation_reasons(CancellationReason::builder().code("None").build())
+                        .cancellation_reasons(
+                            CancellationReason::builder()
+                                .code("ThrottlingError")
+                                .build(),
+                        )
+                        .build(),
+                )
+            });
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_transact_write_items]);
+        let repository = DynamoTodoRepository::new(&dynamodb_client, "toto");
+
+        // the event is not recorded either, so that it can be retried
+        repository
+            .increment_counter("toto", CounterIncrement::default(), "toto", "event-1")
+            .await
+            .expect_err("update should fail");
+    }
+}
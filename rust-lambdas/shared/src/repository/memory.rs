@@ -0,0 +1,677 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    keys::{ListMemberKey, OwnerListKey, TableKey, TodoKey},
+    CounterIncrement, DeletedTodos, EventEnvelope, List, ListCounter, ListDeletion, ListMember,
+    ListPage, OutboxRecord, Processing, RepositoryError, Todo, TodoEvent, TodoPage, TodoRepository,
+};
+
+#[derive(Default)]
+struct State {
+    /// Keyed by list and todo id, so that the todos of a list are in creation order.
+    todos: BTreeMap<(String, String), Todo>,
+    /// Keyed by id, so that the lists are in creation order.
+    lists: BTreeMap<String, List>,
+    counters: HashMap<String, ListCounter>,
+    /// Keyed by list and user id.
+    members: HashMap<(String, String), ListMember>,
+    /// Keyed by list id, kept once the list is deleted.
+    deletions: HashMap<String, ListDeletion>,
+    outbox_records: Vec<OutboxRecord>,
+    processed_events: HashSet<(String, String)>,
+}
+
+/// Todos kept in memory, to test the handlers without DynamoDB.
+#[derive(Clone, Default)]
+pub struct InMemoryTodoRepository {
+    state: Arc<Mutex<State>>,
+}
+
+impl InMemoryTodoRepository {
+    pub fn new() -> Self {
+        InMemoryTodoRepository::default()
+    }
+
+    /// The outbox records stored so far, in order.
+    pub fn outbox_records(&self) -> Vec<OutboxRecord> {
+        self.state.lock().unwrap().outbox_records.clone()
+    }
+
+    /// Create a todo with its event, to set up the tests.
+    pub async fn seed_todo(&self, todo: &Todo) {
+        let outbox_record = seed_record(TodoEvent::TodoCreated(todo.clone()));
+
+        self.create(todo, &outbox_record)
+            .await
+            .expect("unable to seed todo");
+    }
+
+    /// Create a list with its event, to set up the tests.
+    pub async fn seed_list(&self, list: &List) {
+        let outbox_record = seed_record(TodoEvent::ListCreated(list.clone()));
+
+        self.create_list(list, &outbox_record)
+            .await
+            .expect("unable to seed list");
+    }
+
+    /// Share a list with its event, to set up the tests.
+    pub async fn seed_member(&self, member: &ListMember) {
+        let outbox_record = seed_record(TodoEvent::ListShared(member.clone()));
+
+        self.add_member(member, &outbox_record)
+            .await
+            .expect("unable to seed member");
+    }
+
+    /// Whether a list exists and is not archived, so that its todos can be written.
+    fn list_open(state: &State, list_id: &str) -> bool {
+        state.lists.get(list_id).is_some_and(|list| !list.archived)
+    }
+
+    /// Store an outbox record, unless a record with the same id exists.
+    fn store_outbox_record(state: &mut State, outbox_record: &OutboxRecord) -> bool {
+        if state
+            .outbox_records
+            .iter()
+            .any(|record| record.id == outbox_record.id)
+        {
+            return false;
+        }
+
+        state.outbox_records.push(outbox_record.clone());
+
+        true
+    }
+}
+
+fn seed_record(event: TodoEvent) -> OutboxRecord {
+    OutboxRecord::new(&EventEnvelope::new(event)).expect("unable to serialize seeded event")
+}
+
+impl TodoRepository for InMemoryTodoRepository {
+    async fn create(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = TodoKey::new(&todo.list_id, &todo.id)?;
+        let mut state = self.state.lock().unwrap();
+
+        if !Self::list_open(&state, &todo.list_id) {
+            return Err(RepositoryError::ListClosed);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.todos.insert((key.list_id, key.todo_id), todo.clone());
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &TodoKey) -> Result<Option<Todo>, RepositoryError> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state
+            .todos
+            .get(&(key.list_id.clone(), key.todo_id.clone()))
+            .cloned())
+    }
+
+    async fn list(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<TodoPage, RepositoryError> {
+        let start_after = exclusive_start_key
+            .map(|key| TodoKey::from_key(&key).map(|key| key.todo_id))
+            .transpose()?;
+
+        let state = self.state.lock().unwrap();
+
+        let mut todos: Vec<Todo> = state
+            .todos
+            .iter()
+            .filter(|((todo_list_id, todo_id), _)| {
+                todo_list_id == list_id
+                    && start_after
+                        .as_ref()
+                        .is_none_or(|start_after| todo_id > start_after)
+            })
+            .map(|(_, todo)| todo.clone())
+            .collect();
+
+        let limit = limit.max(0) as usize;
+
+        let last_evaluated_key = match todos.len() > limit {
+            true => {
+                todos.truncate(limit);
+
+                todos
+                    .last()
+                    .map(|todo| TodoKey::new(list_id, &todo.id))
+                    .transpose()?
+                    .map(|key| key.to_key())
+            }
+            false => None,
+        };
+
+        Ok(TodoPage {
+            todos,
+            last_evaluated_key,
+        })
+    }
+
+    async fn delete(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (todo.list_id.clone(), todo.id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        match state.todos.get(&key) {
+            None => return Err(RepositoryError::NotFound),
+            Some(stored) if stored.completed != todo.completed => {
+                return Err(RepositoryError::Conflict)
+            }
+            Some(_) => {}
+        }
+
+        if !Self::list_open(&state, &todo.list_id) {
+            return Err(RepositoryError::ListClosed);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.todos.remove(&key);
+
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        old: &Todo,
+        new: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (old.list_id.clone(), old.id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        match state.todos.get(&key) {
+            None => return Err(RepositoryError::NotFound),
+            Some(stored)
+                if stored.title != old.title
+                    || stored.description != old.description
+                    || stored.completed != old.completed =>
+            {
+                return Err(RepositoryError::Conflict)
+            }
+            Some(_) => {}
+        }
+
+        if !Self::list_open(&state, &old.list_id) {
+            return Err(RepositoryError::ListClosed);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        if let Some(stored) = state.todos.get_mut(&key) {
+            stored.title = new.title.clone();
+            stored.description = new.description.clone();
+        }
+
+        Ok(())
+    }
+
+    async fn set_completion(
+        &self,
+        todo: &Todo,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (todo.list_id.clone(), todo.id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        match state.todos.get(&key) {
+            None => return Err(RepositoryError::NotFound),
+            Some(stored) if stored.completed == todo.completed => {
+                return Err(RepositoryError::Conflict)
+            }
+            Some(_) => {}
+        }
+
+        if !Self::list_open(&state, &todo.list_id) {
+            return Err(RepositoryError::ListClosed);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        if let Some(stored) = state.todos.get_mut(&key) {
+            stored.completed = todo.completed;
+            stored.completed_at = todo.completed_at;
+        }
+
+        Ok(())
+    }
+
+    async fn get_counter(&self, list_id: &str) -> Result<ListCounter, RepositoryError> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state.counters.get(list_id).cloned().unwrap_or_default())
+    }
+
+    async fn increment_counter(
+        &self,
+        list_id: &str,
+        increment: CounterIncrement,
+        consumer: &str,
+        event_id: &str,
+    ) -> Result<Processing, RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+        let processed_event = (event_id.to_string(), consumer.to_string());
+
+        if state.processed_events.contains(&processed_event) {
+            return Ok(Processing::Duplicate);
+        }
+
+        // late events of a deleted list must not recreate its counter
+        if !state.lists.contains_key(list_id) {
+            return Ok(Processing::Skipped);
+        }
+
+        state.processed_events.insert(processed_event);
+
+        let counter = state.counters.entry(list_id.into()).or_default();
+        counter.todos_count += increment.todos_count;
+        counter.completed_count += increment.completed_count;
+
+        Ok(Processing::Processed)
+    }
+
+    async fn get_member(&self, key: &ListMemberKey) -> Result<Option<ListMember>, RepositoryError> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state
+            .members
+            .get(&(key.list_id.clone(), key.user_id.clone()))
+            .cloned())
+    }
+
+    async fn add_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (member.list_id.clone(), member.user_id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        if state.members.contains_key(&key) || !Self::store_outbox_record(&mut state, outbox_record)
+        {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.members.insert(key, member.clone());
+
+        Ok(())
+    }
+
+    async fn update_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (member.list_id.clone(), member.user_id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        if !state.members.contains_key(&key) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.members.insert(key, member.clone());
+
+        Ok(())
+    }
+
+    async fn remove_member(
+        &self,
+        member: &ListMember,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let key = (member.list_id.clone(), member.user_id.clone());
+        let mut state = self.state.lock().unwrap();
+
+        if !state.members.contains_key(&key) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.members.remove(&key);
+
+        Ok(())
+    }
+
+    async fn create_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.lists.contains_key(&list.id)
+            || !Self::store_outbox_record(&mut state, outbox_record)
+        {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.lists.insert(list.id.clone(), list.clone());
+
+        Ok(())
+    }
+
+    async fn get_list(&self, list_id: &str) -> Result<Option<List>, RepositoryError> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state.lists.get(list_id).cloned())
+    }
+
+    async fn update_list(
+        &self,
+        list: &List,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.lists.contains_key(&list.id) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.lists.insert(list.id.clone(), list.clone());
+
+        Ok(())
+    }
+
+    async fn request_list_deletion(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.deletions.contains_key(&list.id) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        if !state.lists.contains_key(&list.id) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.lists.insert(list.id.clone(), list.clone());
+        state.deletions.insert(list.id.clone(), deletion.clone());
+
+        Ok(())
+    }
+
+    async fn get_list_deletion(
+        &self,
+        list_id: &str,
+    ) -> Result<Option<ListDeletion>, RepositoryError> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state.deletions.get(list_id).cloned())
+    }
+
+    async fn update_list_deletion(&self, deletion: &ListDeletion) -> Result<(), RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        state
+            .deletions
+            .insert(deletion.list_id.clone(), deletion.clone());
+
+        Ok(())
+    }
+
+    async fn delete_todos(
+        &self,
+        list_id: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<DeletedTodos, RepositoryError> {
+        let page = self.list(list_id, limit, exclusive_start_key).await?;
+        let mut state = self.state.lock().unwrap();
+
+        for todo in &page.todos {
+            state.todos.remove(&(todo.list_id.clone(), todo.id.clone()));
+        }
+
+        Ok(DeletedTodos {
+            count: page.todos.len(),
+            last_evaluated_key: page.last_evaluated_key,
+        })
+    }
+
+    async fn delete_members(&self, list_id: &str) -> Result<usize, RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        let count = state.members.len();
+        state
+            .members
+            .retain(|(member_list_id, _), _| member_list_id != list_id);
+
+        Ok(count - state.members.len())
+    }
+
+    async fn delete_list(
+        &self,
+        list: &List,
+        deletion: &ListDeletion,
+        outbox_record: &OutboxRecord,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.lists.contains_key(&list.id) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        if !Self::store_outbox_record(&mut state, outbox_record) {
+            return Err(RepositoryError::Conflict);
+        }
+
+        state.lists.remove(&list.id);
+        state.counters.remove(&list.id);
+        state.deletions.insert(list.id.clone(), deletion.clone());
+
+        Ok(())
+    }
+
+    async fn owner_lists(
+        &self,
+        owner: &str,
+        limit: i32,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<ListPage, RepositoryError> {
+        let start_after = exclusive_start_key
+            .map(|key| OwnerListKey::from_key(&key).map(|key| key.list_id))
+            .transpose()?;
+
+        let state = self.state.lock().unwrap();
+
+        let mut lists: Vec<List> = state
+            .lists
+            .values()
+            .filter(|list| {
+                list.owner == owner
+                    && start_after
+                        .as_ref()
+                        .is_none_or(|start_after| &list.id > start_after)
+            })
+            .cloned()
+            .collect();
+
+        let limit = limit.max(0) as usize;
+
+        let last_evaluated_key = match lists.len() > limit {
+            true => {
+                lists.truncate(limit);
+
+                lists
+                    .last()
+                    .map(|list| OwnerListKey::new(owner, &list.id))
+                    .transpose()?
+                    .map(|key| key.to_key())
+            }
+            false => None,
+        };
+
+        Ok(ListPage {
+            lists,
+            last_evaluated_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{list, todo},
+        TodoUpdate,
+    };
+
+    async fn repository_with_list() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", "toto")).await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let repository = repository_with_list().await;
+
+        for id in ["tata", "titi", "tutu"] {
+            repository.seed_todo(&todo("toto", id)).await;
+        }
+
+        let page = repository.list("toto", 2, None).await.unwrap();
+
+        assert_eq!(page.todos.len(), 2);
+        assert_eq!(page.todos[0].id, "tata");
+
+        let page = repository
+            .list("toto", 2, page.last_evaluated_key)
+            .await
+            .unwrap();
+
+        assert_eq!(page.todos.len(), 1);
+        assert_eq!(page.todos[0].id, "tutu");
+        assert_eq!(page.last_evaluated_key, None);
+        assert_eq!(repository.outbox_records().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_update_modified_todo() {
+        let repository = repository_with_list().await;
+        repository.seed_todo(&todo("toto", "tata")).await;
+
+        // the todo was completed since it was read
+        let old = Todo {
+            completed: true,
+            ..todo("toto", "tata")
+        };
+        let new = Todo {
+            title: "Tata todo".into(),
+            ..old.clone()
+        };
+        let outbox_record =
+            OutboxRecord::new(&EventEnvelope::new(TodoEvent::TodoUpdated(TodoUpdate {
+                old: old.clone(),
+                new: new.clone(),
+            })))
+            .unwrap();
+
+        let err = repository
+            .update(&old, &new, &outbox_record)
+            .await
+            .expect_err("update should conflict");
+
+        assert!(matches!(err, RepositoryError::Conflict));
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert_eq!(
+            repository.get(&key).await.unwrap().unwrap().title,
+            "Toto todo"
+        );
+        assert_eq!(repository.outbox_records().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_in_archived_list() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&List {
+                archived: true,
+                ..list("toto", "toto")
+            })
+            .await;
+
+        let todo = todo("toto", "tata");
+        let outbox_record =
+            OutboxRecord::new(&EventEnvelope::new(TodoEvent::TodoCreated(todo.clone()))).unwrap();
+
+        let err = repository
+            .create(&todo, &outbox_record)
+            .await
+            .expect_err("the list should be archived");
+
+        assert!(matches!(err, RepositoryError::ListClosed));
+        assert_eq!(repository.outbox_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_increment_counter_of_deleted_list() {
+        let repository = InMemoryTodoRepository::new();
+        let increment = CounterIncrement {
+            todos_count: 1,
+            completed_count: 0,
+        };
+
+        let processing = repository
+            .increment_counter("toto", increment, "toto", "event-1")
+            .await
+            .unwrap();
+
+        assert_eq!(processing, Processing::Skipped);
+        assert_eq!(repository.get_counter("toto").await.unwrap().todos_count, 0);
+    }
+}
@@ -0,0 +1,158 @@
+use std::future::Future;
+
+use aws_lambda_events::http::StatusCode;
+use lambda_http::{request::RequestContext, Error, Request, RequestExt};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use ts_rs::TS;
+
+use crate::FailureResponse;
+
+/// Stable, machine-readable error codes. Clients should branch on these rather
+/// than on the messages.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidRequest,
+    MissingParameter,
+    InvalidParameter,
+    InvalidId,
+    InvalidCursor,
+    Unauthorized,
+    Forbidden,
+    ValidationFailed,
+    TodoNotFound,
+    ListNotFound,
+    MemberNotFound,
+    DeletionNotFound,
+    RouteNotFound,
+    MethodNotAllowed,
+    Conflict,
+    InternalError,
+    ServiceUnavailable,
+}
+
+impl ErrorCode {
+    pub fn status_code(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest
+            | ErrorCode::MissingParameter
+            | ErrorCode::InvalidParameter
+            | ErrorCode::InvalidId
+            | ErrorCode::InvalidCursor => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::ValidationFailed => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::TodoNotFound
+            | ErrorCode::ListNotFound
+            | ErrorCode::MemberNotFound
+            | ErrorCode::DeletionNotFound
+            | ErrorCode::RouteNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// The JSON body of all the error responses.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    #[ts(type = "unknown")]
+    pub details: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
+
+/// Run an HTTP handler, turning its failure into an [`ErrorResponse`].
+///
+/// This is the only place where failures are converted, use it in every HTTP lambda.
+pub async fn respond<F, Fut>(
+    request: Request,
+    handler: F,
+) -> Result<(StatusCode, serde_json::Value), Error>
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<(StatusCode, serde_json::Value), FailureResponse>>,
+{
+    let request_id = request_id(&request);
+
+    match handler(request).await {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            let body = serde_json::to_value(ErrorResponse {
+                code: err.code,
+                message: err.message,
+                details: err.details,
+                request_id,
+            })
+            .unwrap_or_else(|err| {
+                error!(err = ?err, "Unable to serialize error response");
+
+                serde_json::Value::Null
+            });
+
+            Ok((err.status_code, body))
+        }
+    }
+}
+
+/// The API Gateway request id, which is also returned in the `apigw-requestid` header.
+fn request_id(request: &Request) -> Option<String> {
+    match request.request_context_ref() {
+        Some(RequestContext::ApiGatewayV2(context)) => context.request_id.clone(),
+        _ => request
+            .lambda_context_ref()
+            .map(|context| context.request_id.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ApiGatewayRequest;
+    use serde_json::json;
+
+    fn get_request() -> Request {
+        ApiGatewayRequest::new("GET").build()
+    }
+
+    #[tokio::test]
+    async fn test_respond_failure() {
+        let (status, body) = respond(get_request(), |_| async {
+            Err(
+                FailureResponse::new(ErrorCode::TodoNotFound, "Todo not found")
+                    .with_details(json!({ "todo_id": "tata" })),
+            )
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(status, 404);
+        assert_eq!(
+            body,
+            json!({
+                "code": "TODO_NOT_FOUND",
+                "message": "Todo not found",
+                "details": { "todo_id": "tata" },
+                "request_id": "request-id",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_respond_success() {
+        let (status, body) = respond(get_request(), |_| async { Ok((StatusCode::OK, json!({}))) })
+            .await
+            .unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({}));
+    }
+}
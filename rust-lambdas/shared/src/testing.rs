@@ -0,0 +1,158 @@
+//! Fixtures of the handler tests, to use along with [`crate::InMemoryTodoRepository`].
+//!
+//! ```ignore
+//! let repository = InMemoryTodoRepository::new();
+//! repository.seed_list(&list("toto", OWNER)).await;
+//!
+//! let request = ApiGatewayRequest::new("PATCH")
+//!     .user_arn(OWNER)
+//!     .path_parameter("listId", "toto")
+//!     .body(r#"{"name": "Tata list"}"#)
+//!     .build();
+//! ```
+
+use chrono::{DateTime, Utc};
+use lambda_http::Request;
+use serde_json::{json, Map, Value};
+
+use crate::{auth::Caller, List, ListMember, Role, Todo};
+
+/// An API Gateway HTTP API request, as received by the HTTP lambdas.
+pub struct ApiGatewayRequest {
+    method: String,
+    path: String,
+    authorizer: Value,
+    raw_query_string: String,
+    path_parameters: Map<String, Value>,
+    body: Option<String>,
+}
+
+impl ApiGatewayRequest {
+    /// A request without caller, body nor parameters.
+    pub fn new(method: &str) -> Self {
+        ApiGatewayRequest {
+            method: method.into(),
+            path: "/my/path".into(),
+            authorizer: Value::Null,
+            raw_query_string: String::new(),
+            path_parameters: Map::new(),
+            body: None,
+        }
+    }
+
+    /// The path of the route, only read by the router of the API lambda.
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sign the request as an IAM user, like the SigV4 requests.
+    pub fn user_arn(self, user_arn: &str) -> Self {
+        self.authorizer(json!({ "iam": { "userArn": user_arn } }))
+    }
+
+    /// The context set by the authorizer of the route, such as the claims of a JWT.
+    pub fn authorizer(mut self, authorizer: Value) -> Self {
+        self.authorizer = authorizer;
+        self
+    }
+
+    /// A parameter of the route, such as `listId`.
+    pub fn path_parameter(mut self, name: &str, value: &str) -> Self {
+        self.path_parameters.insert(name.into(), value.into());
+        self
+    }
+
+    /// The query string, without the leading `?`.
+    pub fn query(mut self, raw_query_string: &str) -> Self {
+        self.raw_query_string = raw_query_string.into();
+        self
+    }
+
+    pub fn body(mut self, body: &str) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn build(self) -> Request {
+        let req = json!({
+          "version": "2.0",
+          "routeKey": "$default",
+          "rawPath": self.path,
+          "rawQueryString": self.raw_query_string,
+          "cookies": [],
+          "headers": {},
+          "queryStringParameters": {},
+          "requestContext": {
+            "accountId": "123456789012",
+            "apiId": "api-id",
+            "authorizer": self.authorizer,
+            "domainName": "id.execute-api.us-east-1.amazonaws.com",
+            "domainPrefix": "id",
+            "http": {
+              "method": self.method,
+              "path": self.path,
+              "protocol": "HTTP/1.1",
+              "sourceIp": "IP",
+              "userAgent": "agent"
+            },
+            "requestId": "request-id",
+            "routeKey": "$default",
+            "stage": "$default",
+            "time": "12/Mar/2020:19:03:58 +0000"
+          },
+          "body": self.body,
+          "pathParameters": self.path_parameters,
+          "isBase64Encoded": false,
+          "stageVariables": {}
+        })
+        .to_string();
+
+        lambda_http::request::from_str(&req).expect("invalid API Gateway request")
+    }
+}
+
+/// The id stored for the IAM user signing the requests, see [`Caller::id`].
+pub fn iam_id(user_arn: &str) -> String {
+    Caller::Iam {
+        user_arn: user_arn.into(),
+    }
+    .id()
+}
+
+/// The date of the fixtures, so that they compare equal.
+pub fn fixture_date() -> DateTime<Utc> {
+    "2024-05-01T10:00:00Z".parse().unwrap()
+}
+
+/// An open todo without owner.
+pub fn todo(list_id: &str, id: &str) -> Todo {
+    Todo {
+        id: id.into(),
+        list_id: list_id.into(),
+        title: "Toto todo".into(),
+        description: "This is a great description".into(),
+        completed: false,
+        completed_at: None,
+        owner: None,
+    }
+}
+
+pub fn list(id: &str, owner: &str) -> List {
+    List {
+        id: id.into(),
+        name: "Toto list".into(),
+        created_at: fixture_date(),
+        owner: owner.into(),
+        archived: false,
+    }
+}
+
+pub fn member(list_id: &str, user_id: &str, role: Role) -> ListMember {
+    ListMember {
+        list_id: list_id.into(),
+        user_id: user_id.into(),
+        role,
+        added_at: fixture_date(),
+    }
+}
@@ -0,0 +1,291 @@
+use lambda_http::{Body, Request};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::debug;
+use ts_rs::TS;
+
+use crate::{ErrorCode, FailureResponse};
+
+/// Constraints on a text field, checked on its trimmed value.
+pub struct TextRules {
+    /// Reject empty and whitespace only values.
+    pub required: bool,
+    /// Maximum length, in characters.
+    pub max_length: usize,
+    pub charset: Charset,
+}
+
+/// The characters allowed in a text field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    /// Any visible text, without control characters nor invisible formatting characters
+    /// such as zero-width spaces and bidirectional overrides.
+    ///
+    /// Line breaks and tabs are only allowed when `multiline`.
+    Text { multiline: bool },
+    /// Visible ASCII characters, without whitespace, as in IAM ARNs and JWT subjects.
+    Identifier,
+}
+
+impl Charset {
+    fn allows(self, char: char) -> bool {
+        match self {
+            Charset::Text { multiline } => match char {
+                '\n' | '\r' | '\t' => multiline,
+                char if char.is_control() => false,
+                // zero-width and bidirectional formatting characters, byte order mark
+                '\u{200B}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}'
+                | '\u{FEFF}' => false,
+                // private use areas and noncharacters
+                '\u{E000}'..='\u{F8FF}' | '\u{FFFE}' | '\u{FFFF}' => false,
+                _ => true,
+            },
+            Charset::Identifier => char.is_ascii_graphic(),
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Charset::Text { multiline: true } => {
+                "must only contain visible characters, line breaks and tabs"
+            }
+            Charset::Text { multiline: false } => "must only contain visible characters",
+            Charset::Identifier => "must only contain visible ASCII characters",
+        }
+    }
+}
+
+pub const TODO_TITLE: TextRules = TextRules {
+    required: true,
+    max_length: 200,
+    charset: Charset::Text { multiline: false },
+};
+
+pub const TODO_DESCRIPTION: TextRules = TextRules {
+    required: false,
+    max_length: 2000,
+    charset: Charset::Text { multiline: true },
+};
+
+pub const LIST_NAME: TextRules = TextRules {
+    required: true,
+    max_length: 100,
+    charset: Charset::Text { multiline: false },
+};
+
+/// The id of a list member, an IAM ARN or a JWT subject.
+pub const MEMBER_USER_ID: TextRules = TextRules {
+    required: true,
+    max_length: 2048,
+    charset: Charset::Identifier,
+};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+/// The `details` of a `VALIDATION_FAILED` error response.
+#[derive(TS)]
+#[ts(export, export_to = "../../../integration-tests/bindings/")]
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ValidationErrors {
+    pub fields: Vec<FieldError>,
+}
+
+impl From<ValidationErrors> for FailureResponse {
+    fn from(errors: ValidationErrors) -> Self {
+        let failure = FailureResponse::new(ErrorCode::ValidationFailed, "Invalid fields");
+
+        match serde_json::to_value(errors) {
+            Ok(details) => failure.with_details(details),
+            Err(_) => failure,
+        }
+    }
+}
+
+/// Collects the errors of every field instead of stopping at the first one.
+#[derive(Default)]
+pub struct Validator {
+    errors: ValidationErrors,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// Check a text field, returning its trimmed value.
+    pub fn text(&mut self, field: &str, value: &str, rules: &TextRules) -> String {
+        let value = value.trim();
+
+        if rules.required && value.is_empty() {
+            self.reject(field, "must not be blank");
+        }
+
+        if value.chars().count() > rules.max_length {
+            self.reject(
+                field,
+                format!("must be at most {} characters", rules.max_length),
+            );
+        }
+
+        if !value.chars().all(|char| rules.charset.allows(char)) {
+            self.reject(field, rules.charset.description());
+        }
+
+        value.into()
+    }
+
+    pub fn reject(&mut self, field: &str, reason: impl Into<String>) {
+        self.errors.fields.push(FieldError {
+            field: field.into(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Return the validated value if all the fields are valid.
+    pub fn finish<T>(self, value: T) -> Result<T, ValidationErrors> {
+        if self.errors.fields.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Request bodies checked once deserialized.
+pub trait Validate: Sized {
+    /// Normalize the fields, reporting all the invalid ones.
+    fn validate(self) -> Result<Self, ValidationErrors>;
+}
+
+/// Deserialize and validate the JSON body of a request.
+///
+/// Malformed bodies are rejected with a 400, invalid fields with a 422.
+pub fn parse_body<T: DeserializeOwned + Validate>(request: &Request) -> Result<T, FailureResponse> {
+    let body = match request.body() {
+        Body::Text(body) => serde_json::from_str::<T>(body).map_err(|err| {
+            debug!(err = ?err, "Unable to deserialize body");
+
+            FailureResponse::new(ErrorCode::InvalidRequest, "Invalid request")
+                .with_details(serde_json::json!({ "reason": err.to_string() }))
+        }),
+        _ => Err(FailureResponse::new(
+            ErrorCode::InvalidRequest,
+            "Invalid request",
+        )),
+    }?;
+
+    Ok(body.validate()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let mut validator = Validator::new();
+
+        assert_eq!(validator.text("title", "  Toto  ", &TODO_TITLE), "Toto");
+        assert_eq!(
+            validator.text("description", "Toto\n\ttodo", &TODO_DESCRIPTION),
+            "Toto\n\ttodo"
+        );
+        assert_eq!(validator.text("description", "", &TODO_DESCRIPTION), "");
+        assert!(validator.finish(()).is_ok());
+    }
+
+    #[test]
+    fn test_text_errors() {
+        let mut validator = Validator::new();
+
+        validator.text("title", " \t ", &TODO_TITLE);
+        validator.text("title", "Toto\ntodo", &TODO_TITLE);
+        validator.text("description", &"a".repeat(2001), &TODO_DESCRIPTION);
+        validator.text("description", "Toto\u{0}", &TODO_DESCRIPTION);
+
+        let errors = validator.finish(()).unwrap_err();
+
+        assert_eq!(
+            errors.fields,
+            vec![
+                FieldError {
+                    field: "title".into(),
+                    reason: "must not be blank".into()
+                },
+                FieldError {
+                    field: "title".into(),
+                    reason: "must only contain visible characters".into()
+                },
+                FieldError {
+                    field: "description".into(),
+                    reason: "must be at most 2000 characters".into()
+                },
+                FieldError {
+                    field: "description".into(),
+                    reason: "must only contain visible characters, line breaks and tabs".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_max_length_counts_characters() {
+        let mut validator = Validator::new();
+
+        validator.text("title", &"é".repeat(200), &TODO_TITLE);
+
+        assert!(validator.finish(()).is_ok());
+    }
+
+    #[test]
+    fn test_text_charsets() {
+        let mut validator = Validator::new();
+
+        validator.text("title", "Toto\u{200B}todo", &TODO_TITLE);
+        validator.text("description", "Toto\u{202E}todo", &TODO_DESCRIPTION);
+        validator.text("title", "Toto\u{E000}", &TODO_TITLE);
+        validator.text(
+            "user_id",
+            "arn:aws:iam::123456789012:user/toto",
+            &MEMBER_USER_ID,
+        );
+        validator.text("user_id", "toto tata", &MEMBER_USER_ID);
+        validator.text("user_id", "tôto", &MEMBER_USER_ID);
+
+        let errors = validator.finish(()).unwrap_err();
+
+        assert_eq!(
+            errors.fields,
+            vec![
+                FieldError {
+                    field: "title".into(),
+                    reason: "must only contain visible characters".into()
+                },
+                FieldError {
+                    field: "description".into(),
+                    reason: "must only contain visible characters, line breaks and tabs".into()
+                },
+                FieldError {
+                    field: "title".into(),
+                    reason: "must only contain visible characters".into()
+                },
+                FieldError {
+                    field: "user_id".into(),
+                    reason: "must only contain visible ASCII characters".into()
+                },
+                FieldError {
+                    field: "user_id".into(),
+                    reason: "must only contain visible ASCII characters".into()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use lambda_http::Error;
+
+use shared::{http::HttpLambda, DynamoTodoRepository};
+use todos_api::{router, Api};
+
+/// All the HTTP routes in a single lambda, an alternative to one lambda per route.
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    HttpLambda::new()
+        .dynamodb()
+        .run(|request, resources| {
+            router(
+                request,
+                Api {
+                    repository: DynamoTodoRepository::new(
+                        resources.dynamodb_client(),
+                        resources.config().todos_table_name(),
+                    ),
+                    cursor_signing_key: resources.config().cursor_signing_key(),
+                    list_limits: resources.config().list_limits(),
+                },
+            )
+        })
+        .await
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use aws_lambda_events::http::{Method, StatusCode};
+use lambda_http::{
+    tracing::{self, debug},
+    Request, RequestExt,
+};
+use shared::{config::ListLimits, ErrorCode, FailureResponse, TodoRepository};
+
+/// The repository and config shared by all the routes.
+pub struct Api<'a, R> {
+    pub repository: R,
+    pub cursor_signing_key: Option<&'a [u8]>,
+    pub list_limits: ListLimits,
+}
+
+#[derive(Debug, PartialEq)]
+enum Route {
+    CreateTodo,
+    ListTodos,
+    GetTodo,
+    UpdateTodo,
+    DeleteTodo,
+    CompleteTodo,
+    CreateList,
+    ListLists,
+    GetList,
+    RenameList,
+    DeleteList,
+    ArchiveList,
+    GetListDeletion,
+    InviteMember,
+    UpdateMember,
+    RemoveMember,
+}
+
+/// Match a request with the same routes as the HTTP API, extracting the path parameters.
+fn match_route(
+    method: &Method,
+    path: &str,
+) -> Result<(Route, HashMap<String, String>), FailureResponse> {
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let (routes, parameters) = match segments[..] {
+        ["todos", list_id] => (
+            vec![
+                (Method::POST, Route::CreateTodo),
+                (Method::GET, Route::ListTodos),
+            ],
+            vec![("listId", list_id)],
+        ),
+        ["todos", list_id, todo_id] => (
+            vec![
+                (Method::GET, Route::GetTodo),
+                (Method::PATCH, Route::UpdateTodo),
+                (Method::DELETE, Route::DeleteTodo),
+            ],
+            vec![("listId", list_id), ("todoId", todo_id)],
+        ),
+        ["todos", list_id, todo_id, "completion"] => (
+            vec![(Method::PUT, Route::CompleteTodo)],
+            vec![("listId", list_id), ("todoId", todo_id)],
+        ),
+        ["lists"] => (
+            vec![
+                (Method::POST, Route::CreateList),
+                (Method::GET, Route::ListLists),
+            ],
+            vec![],
+        ),
+        ["lists", list_id] => (
+            vec![
+                (Method::GET, Route::GetList),
+                (Method::PATCH, Route::RenameList),
+                (Method::DELETE, Route::DeleteList),
+            ],
+            vec![("listId", list_id)],
+        ),
+        ["lists", list_id, "archive"] => (
+            vec![(Method::PUT, Route::ArchiveList)],
+            vec![("listId", list_id)],
+        ),
+        ["lists", list_id, "deletion"] => (
+            vec![(Method::GET, Route::GetListDeletion)],
+            vec![("listId", list_id)],
+        ),
+        // the member is passed as the `userId` query parameter
+        ["lists", list_id, "members"] => (
+            vec![
+                (Method::POST, Route::InviteMember),
+                (Method::PATCH, Route::UpdateMember),
+                (Method::DELETE, Route::RemoveMember),
+            ],
+            vec![("listId", list_id)],
+        ),
+        _ => {
+            return Err(FailureResponse::new(
+                ErrorCode::RouteNotFound,
+                "Route not found",
+            ))
+        }
+    };
+
+    let route = routes
+        .into_iter()
+        .find(|(route_method, _)| route_method == method)
+        .map(|(_, route)| route)
+        .ok_or(FailureResponse::new(
+            ErrorCode::MethodNotAllowed,
+            "Method not allowed",
+        ))?;
+
+    let parameters = parameters
+        .into_iter()
+        .map(|(name, value)| (name.into(), value.into()))
+        .collect();
+
+    Ok((route, parameters))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn router(
+    request: Request,
+    api: Api<'_, impl TodoRepository>,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let (route, parameters) = match_route(request.method(), request.uri().path())?;
+
+    debug!(route = ?route, "Matched route");
+
+    // the handlers read the path parameters set by API Gateway on the routes
+    let request = request.with_path_parameters(parameters);
+
+    let repository = api.repository;
+
+    match route {
+        Route::CreateTodo => create_todo::handler(request, &repository).await,
+        Route::ListTodos => {
+            list_todos::handler(
+                request,
+                &repository,
+                api.cursor_signing_key,
+                api.list_limits,
+            )
+            .await
+        }
+        Route::GetTodo => get_todo::handler(request, &repository).await,
+        Route::UpdateTodo => update_todo::handler(request, &repository).await,
+        Route::DeleteTodo => delete_todo::handler(request, &repository).await,
+        Route::CompleteTodo => complete_todo::handler(request, &repository).await,
+        Route::CreateList => create_list::handler(request, &repository).await,
+        Route::ListLists => {
+            list_lists::handler(
+                request,
+                &repository,
+                api.cursor_signing_key,
+                api.list_limits,
+            )
+            .await
+        }
+        Route::GetList => get_list::handler(request, &repository).await,
+        Route::RenameList => rename_list::handler(request, &repository).await,
+        Route::DeleteList => delete_list::handler(request, &repository).await,
+        Route::ArchiveList => archive_list::handler(request, &repository).await,
+        Route::GetListDeletion => get_list_deletion::handler(request, &repository).await,
+        Route::InviteMember => invite_member::handler(request, &repository).await,
+        Route::UpdateMember => update_member::handler(request, &repository).await,
+        Route::RemoveMember => remove_member::handler(request, &repository).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_mocks::{mock, mock_client};
+
+    use aws_sdk_dynamodb::operation::get_item::GetItemOutput;
+    use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsOutput;
+    use aws_sdk_dynamodb::types::AttributeValue;
+    use shared::{
+        testing::{iam_id, list, ApiGatewayRequest},
+        DynamoItem, DynamoTodoRepository,
+    };
+
+    fn get_request(method: &str, path: &str, body: Option<&str>) -> Request {
+        let request = ApiGatewayRequest::new(method)
+            .user_arn("arn:aws:iam::123456789012:user/toto")
+            .path(path);
+
+        match body {
+            Some(body) => request.body(body),
+            None => request,
+        }
+        .build()
+    }
+
+    fn api(dynamodb_client: &aws_sdk_dynamodb::Client) -> Api<'_, DynamoTodoRepository<'_>> {
+        Api {
+            repository: DynamoTodoRepository::new(dynamodb_client, "toto"),
+            cursor_signing_key: None,
+            list_limits: ListLimits::default(),
+        }
+    }
+
+    #[test]
+    fn test_match_route() {
+        let cases = [
+            (Method::POST, "/todos/toto", Route::CreateTodo),
+            (Method::GET, "/todos/toto", Route::ListTodos),
+            (Method::GET, "/todos/toto/tata", Route::GetTodo),
+            (Method::PATCH, "/todos/toto/tata", Route::UpdateTodo),
+            (Method::DELETE, "/todos/toto/tata", Route::DeleteTodo),
+            (
+                Method::PUT,
+                "/todos/toto/tata/completion",
+                Route::CompleteTodo,
+            ),
+            (Method::POST, "/lists", Route::CreateList),
+            (Method::GET, "/lists", Route::ListLists),
+            (Method::GET, "/lists/toto", Route::GetList),
+            (Method::PATCH, "/lists/toto", Route::RenameList),
+            (Method::DELETE, "/lists/toto", Route::DeleteList),
+            (Method::PUT, "/lists/toto/archive", Route::ArchiveList),
+            (Method::GET, "/lists/toto/deletion", Route::GetListDeletion),
+            (Method::POST, "/lists/toto/members", Route::InviteMember),
+            (Method::PATCH, "/lists/toto/members", Route::UpdateMember),
+            (Method::DELETE, "/lists/toto/members", Route::RemoveMember),
+        ];
+
+        for (method, path, expected_route) in cases {
+            let (route, _) = match_route(&method, path).unwrap();
+
+            assert_eq!(route, expected_route, "{method} {path}");
+        }
+    }
+
+    #[test]
+    fn test_match_route_failures() {
+        let err = match_route(&Method::GET, "/todos").unwrap_err();
+        assert_eq!(err.status_code, 404);
+
+        let err = match_route(&Method::GET, "/todos/toto/tata/toto/tata").unwrap_err();
+        assert_eq!(err.status_code, 404);
+
+        let err = match_route(&Method::DELETE, "/todos/toto").unwrap_err();
+        assert_eq!(err.status_code, 405);
+
+        let err = match_route(&Method::DELETE, "/lists").unwrap_err();
+        assert_eq!(err.status_code, 405);
+
+        let err = match_route(&Method::GET, "/lists/toto/members").unwrap_err();
+        assert_eq!(err.status_code, 405);
+    }
+
+    #[tokio::test]
+    async fn test_router_create_todo() {
+        let list = list("toto", &iam_id("arn:aws:iam::123456789012:user/toto"));
+        let mock_get_item = mock!(aws_sdk_dynamodb::Client::get_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("SK")) == Some(&AttributeValue::S("LIST".into()))
+            })
+            .then_output(move || {
+                GetItemOutput::builder()
+                    .set_item(Some(list.to_item()))
+                    .build()
+            });
+        let mock_transact_write_items = mock!(aws_sdk_dynamodb::Client::transact_write_items)
+            .match_requests(|req| {
+                req.transact_items()[0].put().is_some_and(|put| {
+                    put.item().get("PK") == Some(&AttributeValue::S("TODO#toto".into()))
+                })
+            })
+            .then_output(|| TransactWriteItemsOutput::builder().build());
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            &[&mock_get_item, &mock_transact_write_items]
+        );
+
+        let request = get_request(
+            "POST",
+            "/todos/toto",
+            Some("{\"title\": \"Toto todo\", \"description\": \"This is a great description\"}"),
+        );
+
+        let (status, _) = router(request, api(&dynamodb_client))
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 201);
+        assert_eq!(mock_transact_write_items.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_router_get_todo() {
+        let list = list("toto", &iam_id("arn:aws:iam::123456789012:user/toto"));
+        let mock_get_list = mock!(aws_sdk_dynamodb::Client::get_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("SK")) == Some(&AttributeValue::S("LIST".into()))
+            })
+            .then_output(move || {
+                GetItemOutput::builder()
+                    .set_item(Some(list.to_item()))
+                    .build()
+            });
+        let mock_get_item = mock!(aws_sdk_dynamodb::Client::get_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("SK"))
+                    == Some(&AttributeValue::S("ID#tata".into()))
+            })
+            .then_output(|| GetItemOutput::builder().build());
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[&mock_get_list, &mock_get_item]);
+
+        let err = router(
+            get_request("GET", "/todos/toto/tata", None),
+            api(&dynamodb_client),
+        )
+        .await
+        .expect_err("todo should not be found");
+
+        assert_eq!(err.code, ErrorCode::TodoNotFound);
+        assert_eq!(mock_get_item.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_router_not_found() {
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, &[]);
+
+        let err = router(
+            get_request("GET", "/users/toto", None),
+            api(&dynamodb_client),
+        )
+        .await
+        .expect_err("route should not be found");
+
+        assert_eq!(err.code, ErrorCode::RouteNotFound);
+    }
+}
@@ -0,0 +1,214 @@
+use aws_lambda_events::http::StatusCode;
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::ListMemberKey, parse_body, ErrorCode, EventEnvelope, FailureResponse,
+    ListMember, OutboxRecord, RepositoryError, Role, TodoEvent, TodoRepository, Validate,
+    ValidationErrors, Validator,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct UpdateMember {
+    role: Role,
+}
+
+impl Validate for UpdateMember {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::new();
+
+        // a list has a single owner
+        if self.role == Role::Owner {
+            validator.reject("role", "must be viewer or editor");
+        }
+
+        validator.finish(self)
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    // the user ids are ARNs, which contain slashes
+    let query_string_parameters = request.query_string_parameters();
+
+    let user_id = query_string_parameters
+        .first("userId")
+        .ok_or(FailureResponse::new(
+            ErrorCode::MissingParameter,
+            "Missing user id",
+        ))?;
+
+    let key = ListMemberKey::new(list_id, user_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let body = parse_body::<UpdateMember>(&request)?;
+
+    let start = Instant::now();
+
+    caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to update member")
+        })?
+        .require(Role::Owner)?;
+
+    let member = repository
+        .get_member(&key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get member");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to update member")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::MemberNotFound,
+            "Member not found",
+        ))?;
+
+    let member = ListMember {
+        role: body.role,
+        ..member
+    };
+
+    let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListShared(
+        member.clone(),
+    )))
+    .map_err(|err| {
+        error!(err = ?err, "Unable to serialize member");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize member")
+    })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .update_member(&member, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::MemberNotFound, "Member not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to update member");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to update member")
+            }
+        })?;
+
+    info!(list_id = list_id, role = ?member.role, "Successfully updated member");
+
+    debug!("Item updated in {:.2?}", start.elapsed());
+
+    let member = serde_json::to_value(member).map_err(|_| {
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize member")
+    })?;
+
+    Ok((StatusCode::OK, member))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, member, ApiGatewayRequest},
+        InMemoryTodoRepository,
+    };
+
+    const OWNER: &str = "arn:aws:iam::123456789012:user/toto";
+    const MEMBER: &str = "arn:aws:iam::123456789012:user/tata";
+
+    fn get_request(body: &str, member_arn: &str) -> Request {
+        get_request_as(body, member_arn, OWNER)
+    }
+
+    fn get_request_as(body: &str, member_arn: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("PATCH")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .query(&format!("userId={}", iam_id(member_arn)))
+            .body(body)
+            .build()
+    }
+
+    async fn repository_with_member() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository.seed_list(&list("toto", &iam_id(OWNER))).await;
+        repository
+            .seed_member(&member("toto", &iam_id(MEMBER), Role::Viewer))
+            .await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_member().await;
+
+        let (status, res) = handler(get_request("{\"role\": \"editor\"}", MEMBER), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let member: ListMember = serde_json::from_value(res).unwrap();
+
+        assert_eq!(member.role, Role::Editor);
+        assert_eq!(member.added_at.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+
+        let outbox_records = repository.outbox_records();
+
+        assert_eq!(outbox_records.len(), 3);
+        assert_eq!(outbox_records[2].detail_type, "LIST_SHARED");
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = repository_with_member().await;
+
+        let err = handler(
+            get_request(
+                "{\"role\": \"editor\"}",
+                "arn:aws:iam::123456789012:user/titi",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("member should not be found");
+
+        assert_eq!(err.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_owner() {
+        let repository = repository_with_member().await;
+
+        // a member can't promote themselves
+        let err = handler(
+            get_request_as("{\"role\": \"editor\"}", MEMBER, MEMBER),
+            &repository,
+        )
+        .await
+        .expect_err("only the owner should update members");
+
+        assert_eq!(err.status_code, 403);
+    }
+}
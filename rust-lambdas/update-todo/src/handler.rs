@@ -0,0 +1,364 @@
+use aws_lambda_events::http::StatusCode;
+use serde::Deserialize;
+use shared::{
+    auth::Caller, keys::TodoKey, parse_body, ErrorCode, EventEnvelope, FailureResponse,
+    OutboxRecord, RepositoryError, Role, Todo, TodoEvent, TodoRepository, TodoUpdate, Validate,
+    ValidationErrors, Validator, TODO_DESCRIPTION, TODO_TITLE,
+};
+
+use lambda_http::{
+    tracing::{self, debug, error, info},
+    Request, RequestExt,
+};
+
+use std::time::Instant;
+
+#[derive(Deserialize)]
+struct UpdateTodo {
+    title: Option<String>,
+    description: Option<String>,
+}
+
+impl Validate for UpdateTodo {
+    fn validate(self) -> Result<Self, ValidationErrors> {
+        let mut validator = Validator::new();
+
+        let todo = UpdateTodo {
+            title: self
+                .title
+                .map(|title| validator.text("title", &title, &TODO_TITLE)),
+            description: self
+                .description
+                .map(|description| validator.text("description", &description, &TODO_DESCRIPTION)),
+        };
+
+        validator.finish(todo)
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handler(
+    request: Request,
+    repository: &impl TodoRepository,
+) -> Result<(StatusCode, serde_json::Value), FailureResponse> {
+    let path_parameters = request.path_parameters();
+
+    let list_id = path_parameters.first("listId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing list id",
+    ))?;
+
+    let todo_id = path_parameters.first("todoId").ok_or(FailureResponse::new(
+        ErrorCode::MissingParameter,
+        "Missing todo id",
+    ))?;
+
+    let body = parse_body::<UpdateTodo>(&request)?;
+
+    if body.title.is_none() && body.description.is_none() {
+        return Err(FailureResponse::new(
+            ErrorCode::InvalidRequest,
+            "Nothing to update",
+        ));
+    }
+
+    let key = TodoKey::new(list_id, todo_id)
+        .map_err(|_| FailureResponse::new(ErrorCode::InvalidId, "Invalid id"))?;
+
+    let caller = Caller::from_request(&request)?;
+
+    let start = Instant::now();
+
+    caller
+        .list_access(repository, list_id)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get list access");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to update todo")
+        })?
+        .require(Role::Editor)?;
+
+    // transactions can't return the updated item, which the event needs
+    let old_todo = repository
+        .get(&key)
+        .await
+        .map_err(|err| {
+            error!(err = ?err, "Unable to get todo");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to get todo")
+        })?
+        .ok_or(FailureResponse::new(
+            ErrorCode::TodoNotFound,
+            "Todo not found",
+        ))?;
+
+    // only the supplied fields are updated
+    let new_todo = Todo {
+        title: body.title.unwrap_or_else(|| old_todo.title.clone()),
+        description: body
+            .description
+            .unwrap_or_else(|| old_todo.description.clone()),
+        ..old_todo.clone()
+    };
+
+    let outbox_record =
+        OutboxRecord::new(&EventEnvelope::new(TodoEvent::TodoUpdated(TodoUpdate {
+            old: old_todo.clone(),
+            new: new_todo.clone(),
+        })))
+        .map_err(|err| {
+            error!(err = ?err, "Unable to serialize todo");
+
+            FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
+        })?;
+
+    // the event is published by the outbox relay once the transaction is committed
+    repository
+        .update(&old_todo, &new_todo, &outbox_record)
+        .await
+        .map_err(|err| match err {
+            RepositoryError::Conflict => {
+                FailureResponse::new(ErrorCode::Conflict, "Todo was modified during update")
+            }
+            RepositoryError::ListClosed => {
+                FailureResponse::new(ErrorCode::Conflict, "The list is archived")
+            }
+            RepositoryError::NotFound => {
+                FailureResponse::new(ErrorCode::TodoNotFound, "Todo not found")
+            }
+            err => {
+                error!(err = ?err, "Unable to update todo");
+
+                FailureResponse::new(ErrorCode::InternalError, "Unable to update todo")
+            }
+        })?;
+
+    debug!("Item updated in {:.2?}", start.elapsed());
+
+    info!(
+        todo_id = todo_id,
+        list_id = list_id,
+        "Successfully updated todo",
+    );
+
+    let todo = serde_json::to_value(&new_todo).map_err(|err| {
+        error!(err = ?err, "Unable to serialize todo");
+
+        FailureResponse::new(ErrorCode::InternalError, "Unable to serialize todo")
+    })?;
+
+    Ok((StatusCode::OK, todo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{
+        testing::{iam_id, list, member, todo, ApiGatewayRequest},
+        InMemoryTodoRepository, List,
+    };
+
+    fn get_request(body: &str) -> Request {
+        get_request_as(body, "arn:aws:iam::123456789012:user/toto")
+    }
+
+    fn get_request_as(body: &str, user_arn: &str) -> Request {
+        ApiGatewayRequest::new("PATCH")
+            .user_arn(user_arn)
+            .path_parameter("listId", "toto")
+            .path_parameter("todoId", "tata")
+            .body(body)
+            .build()
+    }
+
+    async fn repository_with_todo() -> InMemoryTodoRepository {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+        repository
+            .seed_todo(&Todo {
+                owner: Some(iam_id("arn:aws:iam::123456789012:user/toto")),
+                ..todo("toto", "tata")
+            })
+            .await;
+
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_handler() {
+        let repository = repository_with_todo().await;
+
+        let (status, res) = handler(get_request("{\"title\": \"Tata todo\"}"), &repository)
+            .await
+            .expect("failed to handle event");
+
+        assert_eq!(status, 200);
+
+        let todo: Todo = serde_json::from_value(res).unwrap();
+
+        // the description was not supplied so it keeps its value
+        assert_eq!(todo.title, "Tata todo");
+        assert_eq!(todo.description, "This is a great description");
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert_eq!(
+            repository.get(&key).await.unwrap().unwrap().title,
+            "Tata todo"
+        );
+
+        let outbox_records = repository.outbox_records();
+        let envelope = outbox_records[2].envelope().unwrap();
+
+        assert!(matches!(envelope.event, TodoEvent::TodoUpdated(update)
+            if update.old.title == "Toto todo" && update.new.title == "Tata todo"));
+    }
+
+    #[tokio::test]
+    async fn test_handler_empty_update() {
+        let repository = repository_with_todo().await;
+
+        let err = handler(get_request("{}"), &repository)
+            .await
+            .expect_err("empty update should be rejected");
+
+        assert_eq!(err.status_code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_handler_invalid_title() {
+        let repository = repository_with_todo().await;
+
+        let err = handler(get_request("{\"title\": \"\"}"), &repository)
+            .await
+            .expect_err("blank title should be rejected");
+
+        assert_eq!(err.status_code, 422);
+        assert_eq!(err.code, ErrorCode::ValidationFailed);
+    }
+
+    #[tokio::test]
+    async fn test_handler_not_found() {
+        let repository = InMemoryTodoRepository::new();
+        repository
+            .seed_list(&list(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/toto"),
+            ))
+            .await;
+
+        let err = handler(
+            get_request("{\"description\": \"New description\"}"),
+            &repository,
+        )
+        .await
+        .expect_err("todo should not be found");
+
+        assert_eq!(err.status_code, 404);
+        // only the event of the list creation was stored
+        assert_eq!(repository.outbox_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_shared_list() {
+        let repository = repository_with_todo().await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Editor,
+            ))
+            .await;
+
+        let (status, _) = handler(
+            get_request_as(
+                "{\"title\": \"Tata todo\"}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect("an editor should update todos");
+
+        assert_eq!(status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_handler_viewer() {
+        let repository = repository_with_todo().await;
+        repository
+            .seed_member(&member(
+                "toto",
+                &iam_id("arn:aws:iam::123456789012:user/tata"),
+                Role::Viewer,
+            ))
+            .await;
+
+        let err = handler(
+            get_request_as(
+                "{\"title\": \"Tata todo\"}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("a viewer should not update todos");
+
+        assert_eq!(err.status_code, 403);
+        assert_eq!(err.message, "Insufficient role on the list");
+    }
+
+    #[tokio::test]
+    async fn test_handler_list_of_another_user() {
+        let repository = repository_with_todo().await;
+
+        let err = handler(
+            get_request_as(
+                "{\"title\": \"Tata todo\"}",
+                "arn:aws:iam::123456789012:user/tata",
+            ),
+            &repository,
+        )
+        .await
+        .expect_err("list should belong to another user");
+
+        assert_eq!(err.status_code, 403);
+
+        let key = TodoKey::new("toto", "tata").unwrap();
+
+        assert_eq!(
+            repository.get(&key).await.unwrap().unwrap().title,
+            "Toto todo"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_archived_list() {
+        let repository = repository_with_todo().await;
+        let archived = List {
+            archived: true,
+            ..list("toto", &iam_id("arn:aws:iam::123456789012:user/toto"))
+        };
+        let outbox_record = OutboxRecord::new(&EventEnvelope::new(TodoEvent::ListUpdated(
+            archived.clone(),
+        )))
+        .unwrap();
+        repository
+            .update_list(&archived, &outbox_record)
+            .await
+            .unwrap();
+
+        let err = handler(get_request("{\"title\": \"Tata todo\"}"), &repository)
+            .await
+            .expect_err("the todos of an archived list should be read-only");
+
+        assert_eq!(err.status_code, 409);
+        assert_eq!(err.message, "The list is archived");
+    }
+}
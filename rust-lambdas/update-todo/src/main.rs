@@ -0,0 +1,19 @@
+use lambda_http::Error;
+
+use shared::{http::HttpLambda, DynamoTodoRepository};
+use update_todo::handler;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    HttpLambda::new()
+        .dynamodb()
+        .run(|request, resources| async move {
+            let repository = DynamoTodoRepository::new(
+                resources.dynamodb_client(),
+                resources.config().todos_table_name(),
+            );
+
+            handler(request, &repository).await
+        })
+        .await
+}
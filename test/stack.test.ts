@@ -18,7 +18,46 @@ test('Resources are properly created', () => {
     Runtime: 'provided.al2023',
   });
 
-  template.resourceCountIs('AWS::Lambda::Function', 5);
+  template.resourceCountIs('AWS::Lambda::Function', 21);
   template.resourceCountIs('AWS::Events::EventBus', 1);
   template.resourceCountIs('AWS::DynamoDB::Table', 1);
+  // only outbox records are relayed from the table stream
+  template.resourceCountIs('AWS::Lambda::EventSourceMapping', 1);
+});
+
+test('HTTP routes share a single lambda in lambdalith mode', () => {
+  const app = new cdk.App();
+  const stack = new TodoApp.TodoAppStack(app, 'MyTestStack', {
+    lambdalith: true,
+  });
+
+  const template = Template.fromStack(stack);
+
+  // the HTTP lambdas are replaced by a single one
+  template.resourceCountIs('AWS::Lambda::Function', 6);
+  template.resourceCountIs('AWS::ApiGatewayV2::Route', 16);
+  template.resourceCountIs('AWS::ApiGatewayV2::Integration', 1);
+});
+
+test('HTTP routes are authorized by the JWT authorizer when configured', () => {
+  const app = new cdk.App();
+  const stack = new TodoApp.TodoAppStack(app, 'MyTestStack', {
+    jwtAuthorizer: {
+      issuer: 'https://toto.com',
+      audience: 'tata',
+      jwksUrl: 'https://toto.com/.well-known/jwks.json',
+    },
+  });
+
+  const template = Template.fromStack(stack);
+
+  template.resourceCountIs('AWS::Lambda::Function', 22);
+  template.hasResourceProperties('AWS::ApiGatewayV2::Authorizer', {
+    AuthorizerType: 'REQUEST',
+    EnableSimpleResponses: true,
+    IdentitySource: ['$request.header.Authorization'],
+  });
+  template.allResourcesProperties('AWS::ApiGatewayV2::Route', {
+    AuthorizationType: 'CUSTOM',
+  });
 });